@@ -17,6 +17,7 @@ android {
 
     buildFeatures {
         compose = true
+        buildConfig = true
     }
 
     composeOptions {
@@ -39,6 +40,11 @@ android {
         jvmTarget = "17"
     }
 
+    // GbtGenerator logs and traces through android.util / android.os; stub them in JVM tests.
+    testOptions {
+        unitTests.isReturnDefaultValues = true
+    }
+
     // Only bundle ARM64 native libs
     packagingOptions {
         jniLibs {
@@ -85,4 +91,7 @@ dependencies {
 
     // Coroutines
     implementation("org.jetbrains.kotlinx:kotlinx-coroutines-android:1.7.3")
+
+    // Unit tests
+    testImplementation("junit:junit:4.13.2")
 }
@@ -0,0 +1,97 @@
+package com.pocketnode.mempool
+
+/**
+ * Comparison of a mined block against the block 0 we projected for it, as returned by
+ * [GbtGenerator.auditBlock]. Fees are in sats, weights in WU.
+ */
+data class BlockAudit(
+    /** Share of the known mined weight that we had projected in block 0, in percent. */
+    val matchRate: Double = 0.0,
+    /** Projected in block 0 but not mined. */
+    val missingUids: IntArray = intArrayOf(),
+    /**
+     * Mined but not projected in block 0, including [unknownUids] but excluding
+     * [prioritizedUids] and [acceleratedUids].
+     */
+    val addedUids: IntArray = intArrayOf(),
+    /** Added transactions first seen less than the freshness window before the block. */
+    val freshUids: IntArray = intArrayOf(),
+    /** Mined uids we had no transaction data for. */
+    val unknownUids: IntArray = intArrayOf(),
+    /** Mined outside block 0 although we packed them well below block 0's cutoff rate. */
+    val prioritizedUids: IntArray = intArrayOf(),
+    /** Mined transactions with an acceleration applied. */
+    val acceleratedUids: IntArray = intArrayOf(),
+    /** Total fee and weight of our projected block 0 when the block arrived. */
+    val expectedFees: Long = 0,
+    val expectedWeight: Long = 0,
+    /** Total fee and weight of the mined transactions we had data for. */
+    val actualKnownFees: Long = 0,
+    val actualKnownWeight: Long = 0,
+    /** Mined transactions left out of the actual totals because we never saw them. */
+    val unknownTxCount: Int = 0
+) {
+    companion object {
+        /** Per-transaction statuses returned by [GbtGenerator.getAuditStatus]. */
+        const val STATUS_NOT_AUDITED = 0
+        /** In our block 0 and mined. */
+        const val STATUS_MATCHED = 1
+        /** In our block 0 but not mined. */
+        const val STATUS_MISSING = 2
+        /** Mined but not in our block 0. */
+        const val STATUS_ADDED = 3
+        /** Mined but not in our block 0, and too recently seen to expect it there. */
+        const val STATUS_FRESH = 4
+        /** Mined but never seen by us. */
+        const val STATUS_UNKNOWN = 5
+        const val STATUS_PRIORITIZED = 6
+        const val STATUS_ACCELERATED = 7
+    }
+
+    /** Status of every uid this audit mentions, keyed by uid. */
+    fun statuses(minedUids: IntArray): Map<Int, Int> {
+        val statuses = HashMap<Int, Int>()
+        for (uid in minedUids) statuses[uid] = STATUS_MATCHED
+        for (uid in missingUids) statuses[uid] = STATUS_MISSING
+        for (uid in addedUids) statuses[uid] = STATUS_ADDED
+        for (uid in freshUids) statuses[uid] = STATUS_FRESH
+        for (uid in unknownUids) statuses[uid] = STATUS_UNKNOWN
+        for (uid in prioritizedUids) statuses[uid] = STATUS_PRIORITIZED
+        for (uid in acceleratedUids) statuses[uid] = STATUS_ACCELERATED
+        return statuses
+    }
+    override fun equals(other: Any?): Boolean {
+        if (this === other) return true
+        if (javaClass != other?.javaClass) return false
+        other as BlockAudit
+        if (matchRate != other.matchRate) return false
+        if (!missingUids.contentEquals(other.missingUids)) return false
+        if (!addedUids.contentEquals(other.addedUids)) return false
+        if (!freshUids.contentEquals(other.freshUids)) return false
+        if (!unknownUids.contentEquals(other.unknownUids)) return false
+        if (!prioritizedUids.contentEquals(other.prioritizedUids)) return false
+        if (!acceleratedUids.contentEquals(other.acceleratedUids)) return false
+        if (expectedFees != other.expectedFees) return false
+        if (expectedWeight != other.expectedWeight) return false
+        if (actualKnownFees != other.actualKnownFees) return false
+        if (actualKnownWeight != other.actualKnownWeight) return false
+        if (unknownTxCount != other.unknownTxCount) return false
+        return true
+    }
+
+    override fun hashCode(): Int {
+        var result = matchRate.hashCode()
+        result = 31 * result + missingUids.contentHashCode()
+        result = 31 * result + addedUids.contentHashCode()
+        result = 31 * result + freshUids.contentHashCode()
+        result = 31 * result + unknownUids.contentHashCode()
+        result = 31 * result + prioritizedUids.contentHashCode()
+        result = 31 * result + acceleratedUids.contentHashCode()
+        result = 31 * result + expectedFees.hashCode()
+        result = 31 * result + expectedWeight.hashCode()
+        result = 31 * result + actualKnownFees.hashCode()
+        result = 31 * result + actualKnownWeight.hashCode()
+        result = 31 * result + unknownTxCount
+        return result
+    }
+}
@@ -0,0 +1,103 @@
+package com.pocketnode.mempool
+
+/**
+ * Settings for [GbtGenerator.create]. Zero means "default" for [maxBlockWeight]; see each
+ * field for what zero means elsewhere. Java callers can use [Builder].
+ */
+data class GbtConfig(
+    /** Block weight limit in WU, within [GbtGenerator.MIN_BLOCK_WEIGHT]..[GbtGenerator.MAX_BLOCK_WEIGHT]. */
+    val maxBlockWeight: Int = DEFAULT_MAX_BLOCK_WEIGHT,
+    /**
+     * Blocks to project. 0 projects everything into as many blocks as it takes, leaving
+     * overflow to transactions that can't be packed at all; values above
+     * [GbtGenerator.MAX_BLOCKS_LIMIT] are clamped.
+     */
+    val maxBlocks: Int = DEFAULT_MAX_BLOCKS,
+    /** Results also carry [GbtResult.blockTxids] and [GbtResult.overflowTxids]. */
+    val includeTxids: Boolean = false,
+    /**
+     * Invalid transactions throw instead of being dropped and counted, as do updates
+     * re-inserting a tracked uid with different fields.
+     */
+    val strict: Boolean = false,
+    /** How many removed transactions are kept for [GbtGenerator.reinstate]. */
+    val removedCacheSize: Int = GbtGenerator.DEFAULT_REMOVED_CACHE_SIZE,
+    /** Cap the tracked mempool when positive, evicting packages like bitcoind's TrimToSize. */
+    val maxTxCount: Int = 0,
+    val maxMemoryBytes: Long = 0L,
+    /**
+     * Each transaction's base rate is derived from its fee and weight, and the provided
+     * [ThreadTransaction.effectiveFeePerVsize] is only checked against it.
+     */
+    val deriveRates: Boolean = false,
+    /** Prices sigops into scoring and block space like Core does (see [sigopAdjustedWeight]); 0 packs by plain weight. */
+    val bytesPerSigop: Int = GbtGenerator.DEFAULT_BYTES_PER_SIGOP,
+    /**
+     * ThreadTransaction.POLICY_* traits the modeled miners accept. Transactions with other
+     * flags, and their descendants, go to overflow as [GbtResult.OVERFLOW_NON_STANDARD].
+     */
+    val acceptedPolicyMask: Int = ACCEPT_ALL_POLICIES,
+    /** Package scoring used for selection. */
+    val strategy: GbtSelectionStrategy = GbtSelectionStrategy.EFFECTIVE_RATE,
+    /**
+     * Sanity caps on a single make/update call, and on the same lengths read back from a
+     * [GbtRecorder] file: transactions (and removals), inputs per transaction, accelerations.
+     * Calls exceeding one are rejected whole rather than attempting a huge allocation.
+     */
+    val maxTxsPerCall: Int = DEFAULT_MAX_TXS_PER_CALL,
+    val maxInputsPerTx: Int = DEFAULT_MAX_INPUTS_PER_TX,
+    val maxAccelerations: Int = DEFAULT_MAX_ACCELERATIONS
+) {
+    companion object {
+        const val DEFAULT_MAX_BLOCK_WEIGHT = 4_000_000
+        const val DEFAULT_MAX_BLOCKS = 8
+        const val ACCEPT_ALL_POLICIES = -1
+        const val DEFAULT_MAX_TXS_PER_CALL = 2_000_000
+        const val DEFAULT_MAX_INPUTS_PER_TX = 1_000
+        const val DEFAULT_MAX_ACCELERATIONS = 100_000
+    }
+
+    /** Fluent construction for Java; unset fields keep their defaults. */
+    class Builder {
+        private var config = GbtConfig()
+
+        fun maxBlockWeight(value: Int) = apply { config = config.copy(maxBlockWeight = value) }
+        fun maxBlocks(value: Int) = apply { config = config.copy(maxBlocks = value) }
+        fun includeTxids(value: Boolean) = apply { config = config.copy(includeTxids = value) }
+        fun strict(value: Boolean) = apply { config = config.copy(strict = value) }
+        fun removedCacheSize(value: Int) = apply { config = config.copy(removedCacheSize = value) }
+        fun maxTxCount(value: Int) = apply { config = config.copy(maxTxCount = value) }
+        fun maxMemoryBytes(value: Long) = apply { config = config.copy(maxMemoryBytes = value) }
+        fun deriveRates(value: Boolean) = apply { config = config.copy(deriveRates = value) }
+        fun bytesPerSigop(value: Int) = apply { config = config.copy(bytesPerSigop = value) }
+        fun acceptedPolicyMask(value: Int) = apply { config = config.copy(acceptedPolicyMask = value) }
+        fun strategy(value: GbtSelectionStrategy) = apply { config = config.copy(strategy = value) }
+        fun maxTxsPerCall(value: Int) = apply { config = config.copy(maxTxsPerCall = value) }
+        fun maxInputsPerTx(value: Int) = apply { config = config.copy(maxInputsPerTx = value) }
+        fun maxAccelerations(value: Int) = apply { config = config.copy(maxAccelerations = value) }
+
+        fun build(): GbtConfig = config
+    }
+
+    /**
+     * The configuration a generator actually runs with: defaults filled in and [maxBlocks]
+     * clamped. All validation lives here.
+     *
+     * @throws IllegalArgumentException naming the first invalid field.
+     */
+    internal fun validated(): GbtConfig {
+        require(removedCacheSize >= 0) { "removedCacheSize must not be negative, got $removedCacheSize" }
+        require(maxTxCount >= 0) { "maxTxCount must not be negative, got $maxTxCount" }
+        require(maxMemoryBytes >= 0) { "maxMemoryBytes must not be negative, got $maxMemoryBytes" }
+        require(bytesPerSigop >= 0) { "bytesPerSigop must not be negative, got $bytesPerSigop" }
+        require(maxTxsPerCall > 0) { "maxTxsPerCall must be positive, got $maxTxsPerCall" }
+        require(maxInputsPerTx > 0) { "maxInputsPerTx must be positive, got $maxInputsPerTx" }
+        require(maxAccelerations > 0) { "maxAccelerations must be positive, got $maxAccelerations" }
+        return copy(
+            maxBlockWeight = if (maxBlockWeight == 0) {
+                DEFAULT_MAX_BLOCK_WEIGHT
+            } else GbtGenerator.checkMaxBlockWeight(maxBlockWeight),
+            maxBlocks = GbtGenerator.clampMaxBlocks(maxBlocks)
+        )
+    }
+}
@@ -0,0 +1,23 @@
+package com.pocketnode.mempool
+
+import java.io.IOException
+
+/*
+ * Distinct exception types for the generator's failure modes, so callers can branch on the
+ * class instead of parsing messages. Each extends the standard exception previously thrown in
+ * its place, so existing catch blocks keep working.
+ */
+
+/** A transaction rejected in strict mode; [uid] identifies it and [reason] says why. */
+class GbtInvalidInputException(val uid: Int, val reason: String) :
+    IllegalArgumentException("Invalid transaction $uid: $reason")
+
+/** [GbtGenerator.applyUpdates] would queue more than [limit] changes; [pending] are queued already. */
+class GbtBackpressureException(val pending: Int, val limit: Int) :
+    IllegalStateException("$pending updates pending, limit $limit")
+
+/** The generator's internal state failed a consistency check. */
+class GbtStateCorruptException(message: String) : IllegalStateException(message)
+
+/** A recording or result blob that isn't in the expected format. */
+class GbtFormatException(message: String) : IOException(message)
@@ -1,95 +1,2465 @@
 package com.pocketnode.mempool
 
+import android.content.ComponentCallbacks2
+import android.os.Trace
+import android.util.Log
+import com.pocketnode.BuildConfig
+import org.json.JSONArray
+import org.json.JSONObject
+import java.io.File
+import java.io.IOException
+import java.util.PriorityQueue
+import java.security.SecureRandom
+import java.util.Random
+import java.util.concurrent.atomic.AtomicLong
+import java.util.concurrent.atomic.AtomicLongArray
+import java.util.concurrent.locks.ReentrantReadWriteLock
+import kotlin.concurrent.read
+import kotlin.concurrent.write
+import kotlin.math.roundToLong
+
 /**
- * Kotlin-only GBT (getblocktemplate) algorithm — greedy fee-rate packing.
+ * Kotlin-only GBT (getblocktemplate) algorithm — ancestor-set fee-rate packing.
+ *
+ * The generator keeps its own copy of the mempool between calls so that [update]
+ * only needs the delta. Every mutation bumps [getChangeCounter]; when a run is requested
+ * and nothing changed since the previous one, the cached result is returned with
+ * [GbtResult.fromCache] set instead of repacking.
+ *
+ * Map iteration order depends on insertion history and table size, so anything that reaches
+ * the output is explicitly ordered: packing by rate, then [ThreadTransaction.order], then uid,
+ * and everything else by uid. The same logical mempool always yields the same template.
  */
-class GbtGenerator private constructor(
-    private val maxBlockWeight: Int,
-    private val maxBlocks: Int
-) {
+class GbtGenerator private constructor(private val config: GbtConfig) {
     companion object {
-        fun create(maxBlockWeight: Int, maxBlocks: Int): GbtGenerator {
-            return GbtGenerator(maxBlockWeight, maxBlocks)
+        private const val TAG = "GbtGenerator"
+
+        private const val MIN_TX_WEIGHT = 4
+        private const val MAX_TX_WEIGHT = 4_000_000
+        private const val MAX_TX_SIGOPS = 80_000
+        private const val MAX_BLOCK_SIGOPS = 80_000
+
+        /** Index layout of [getCounters]. All counters are cumulative except the map sizes. */
+        const val COUNTER_COMPUTES = 0
+        const val COUNTER_UPDATES = 1
+        const val COUNTER_TXS_INSERTED = 2
+        const val COUNTER_TXS_REMOVED = 3
+        /** Transactions rejected by validation. */
+        const val COUNTER_TXS_REJECTED = 4
+        /** Time spent packing in [compute], cache hits excluded. */
+        const val COUNTER_COMPUTE_MILLIS = 5
+        /** Epoch milliseconds of the last [compute], 0 if none. */
+        const val COUNTER_LAST_COMPUTE_TIME = 6
+        const val COUNTER_MAP_SIZE = 7
+        const val COUNTER_PEAK_MAP_SIZE = 8
+        /** Tracked transactions replaced by an update carrying the same uid with different fields. */
+        const val COUNTER_TXS_REPLACED = 9
+        const val COUNTER_COUNT = 10
+
+        /** BIP 431 limits, in vB. */
+        private const val MAX_TRUC_VSIZE = 10_000
+        private const val MAX_TRUC_CHILD_VSIZE = 1_000
+
+        /** Core's BlockAssembler gives up on a block this close to full after this many misses. */
+        private const val MAX_CONSECUTIVE_FAILURES = 1000
+        private const val BLOCK_FULL_MARGIN = 4000
+
+        /** Core's -bytespersigop default. */
+        const val DEFAULT_BYTES_PER_SIGOP = 20
+
+        /** Roughly three blocks' worth of transactions. */
+        const val DEFAULT_REMOVED_CACHE_SIZE = 15_000
+
+        /** Queued transactions and removals [applyUpdates] accepts while a run is in flight. */
+        const val MAX_PENDING_UPDATES = 10_000
+
+        /** Transactions first seen less than this long before a block are classified as fresh. */
+        const val DEFAULT_FRESH_WINDOW_SECONDS = 180L
+
+        /** Mined transactions packed below this fraction of block 0's cutoff rate count as prioritized. */
+        private const val PRIORITIZED_RATE_FACTOR = 0.8
+
+        /** Next-block cutoff reported when block 0 is empty: bitcoind's default minimum relay rate, in sat/vB. */
+        private const val MIN_RELAY_FEE_RATE = 1.0
+
+        /** Maps are rebuilt once they hold less than a quarter of their peak above this size. */
+        private const val SHRINK_MIN_PEAK = 100_000
+
+        /** Rough heap cost of one tracked transaction, excluding inputs and txid. */
+        private const val TX_OVERHEAD_BYTES = 160L
+        private const val TXID_BYTES = 112L
+
+        /** Same half-life bitcoind uses for its rolling minimum fee. */
+        private const val MIN_FEE_HALF_LIFE_SECONDS = 12 * 60 * 60
+
+        /** Relative difference between a provided and a derived rate that counts as a discrepancy. */
+        private const val RATE_TOLERANCE = 0.01
+
+        /** Relative fee noise applied per transaction in each [computeEnsemble] run. */
+        private const val ENSEMBLE_RATE_NOISE = 0.02
+
+        /** Upper bound on [GbtResult.blockRateSamples] per block. */
+        private const val BLOCK_RATE_SAMPLES = 100
+
+        /**
+         * Accepted block weight limits in WU. The floor leaves room for a coinbase plus one
+         * small transaction; the ceiling is the consensus limit.
+         */
+        const val MIN_BLOCK_WEIGHT = 5_000
+        const val MAX_BLOCK_WEIGHT = 4_000_000
+        /** Block weights in this range are more likely vbytes passed where WU were meant. */
+        private const val VBYTES_SUSPECT_MIN = 250_000
+        private const val VBYTES_SUSPECT_MAX = 1_000_000
+
+        /** Larger block counts are clamped to this; 0 means unlimited. */
+        const val MAX_BLOCKS_LIMIT = 64
+
+        /** Log-spaced sample rates in [GbtResult.depthCurveRates], plus a 0 point for zero-fee transactions. */
+        private const val DEPTH_CURVE_POINTS = 256
+
+        /** Longest the auto-compute thread sleeps between checks while nothing changes. */
+        private const val MAX_AUTO_COMPUTE_IDLE_MILLIS = 60_000L
+
+        /**
+         * @throws IllegalArgumentException if [config] is invalid; see [GbtConfig.validated].
+         */
+        fun create(config: GbtConfig): GbtGenerator = GbtGenerator(config.validated())
+
+        /** Shorthand for [create] with a [GbtConfig] built from these arguments. */
+        fun create(
+            maxBlockWeight: Int,
+            maxBlocks: Int,
+            includeTxids: Boolean = false,
+            strict: Boolean = false,
+            removedCacheSize: Int = DEFAULT_REMOVED_CACHE_SIZE,
+            maxTxCount: Int = 0,
+            maxMemoryBytes: Long = 0L,
+            deriveRates: Boolean = false,
+            bytesPerSigop: Int = DEFAULT_BYTES_PER_SIGOP
+        ): GbtGenerator {
+            return create(
+                GbtConfig(
+                    maxBlockWeight, maxBlocks, includeTxids, strict, removedCacheSize, maxTxCount, maxMemoryBytes,
+                    deriveRates, bytesPerSigop
+                )
+            )
+        }
+
+        internal fun checkMaxBlockWeight(maxBlockWeight: Int): Int {
+            require(maxBlockWeight in MIN_BLOCK_WEIGHT..MAX_BLOCK_WEIGHT) {
+                "maxBlockWeight $maxBlockWeight WU outside $MIN_BLOCK_WEIGHT..$MAX_BLOCK_WEIGHT"
+            }
+            if (maxBlockWeight in VBYTES_SUSPECT_MIN..VBYTES_SUSPECT_MAX) {
+                Log.w(TAG, "maxBlockWeight $maxBlockWeight WU is a quarter block or less; vbytes passed as weight?")
+            }
+            return maxBlockWeight
+        }
+
+        internal fun clampMaxBlocks(maxBlocks: Int): Int {
+            require(maxBlocks >= 0) { "maxBlocks must not be negative, got $maxBlocks" }
+            if (maxBlocks <= MAX_BLOCKS_LIMIT) return maxBlocks
+            Log.w(TAG, "maxBlocks $maxBlocks above limit, using $MAX_BLOCKS_LIMIT")
+            return MAX_BLOCKS_LIMIT
         }
     }
 
+    private val maxBlockWeight = config.maxBlockWeight
+    private val maxBlocks = config.maxBlocks
+    private val includeTxids = config.includeTxids
+    private val strict = config.strict
+    private val removedCacheSize = config.removedCacheSize
+    private val maxTxCount = config.maxTxCount
+    private val maxMemoryBytes = config.maxMemoryBytes
+    private val deriveRates = config.deriveRates
+    private val bytesPerSigop = config.bytesPerSigop
+    private val acceptedPolicyMask = config.acceptedPolicyMask
+    private val strategy = config.strategy
+
+    /**
+     * Guards all mutable state below: the transaction map together with its derived indexes
+     * ([txidIndex], [children], [recentlyRemoved]) and size accounting, the last run's positions,
+     * rates and cached result, and the watch and audit state. The map and its indexes are only
+     * ever changed together under the write lock, so a reader never sees one without the other.
+     * Queries take the read lock and run concurrently; anything that mutates, including queries
+     * with side effects like [getEvictedSinceLastCall], takes the write lock. The read lock can't
+     * be upgraded, so nothing called under it may take the write lock.
+     */
+    private val lock = ReentrantReadWriteLock()
+    private val computeLock = Any()
+    /**
+     * Guards [pendingUpdates], [pendingUpdateCount] and [runInFlight]. Taken before [lock],
+     * never while holding it, and held while a batch is applied so batches apply in order.
+     */
+    private val pendingLock = Any()
+    /** Batches and removals submitted while a [compute] run was in flight, oldest first. */
+    private val pendingUpdates = ArrayDeque<PendingUpdate>()
+    private var pendingUpdateCount = 0
+    private var runInFlight = false
+    /** Test seam: runs on the computing thread right after a [compute] run took its snapshot. */
+    @Volatile
+    internal var afterSnapshot: (() -> Unit)? = null
+    private val autoComputeLock = Any()
+    private var autoComputeThread: Thread? = null
+    private var threadTransactions = HashMap<Int, ThreadTransaction>()
+    private var txidIndex = HashMap<String, Int>()
+    /** Recently removed transactions, least recently removed first, for reorg recovery. */
+    private val recentlyRemoved = object : LinkedHashMap<Int, ThreadTransaction>() {
+        override fun removeEldestEntry(eldest: MutableMap.MutableEntry<Int, ThreadTransaction>?): Boolean {
+            return size > removedCacheSize
+        }
+    }
+    /** Reverse of [ThreadTransaction.inputs]: parent uid -> uids of tracked transactions spending it. */
+    private var children = HashMap<Int, MutableSet<Int>>()
+    /** Largest map size since the maps were last rebuilt; java.util.HashMap never shrinks its table. */
+    private var peakSize = 0
+    /**
+     * Largest uid inserted since the maps were last rebuilt, [Int.MIN_VALUE] if none. Removals
+     * don't lower it, so between rebuilds it's an upper bound on the tracked uids.
+     */
+    private var maxTrackedUid = Int.MIN_VALUE
+    private var accelerations: Map<Int, ThreadAcceleration> = emptyMap()
+    /** Estimated heap use of the tracked transactions, for [maxMemoryBytes]. */
+    private var estimatedBytes = 0L
+    /** Uids evicted by [trimToSize] and not yet handed out by [getEvictedSinceLastCall]. */
+    private var evictedUids = mutableListOf<Int>()
+    /** Highest package rate (sat/vB) evicted so far and when, decayed by [getMempoolMinFee]. */
+    private var minFeeRate = 0.0
+    private var minFeeRateTime = 0L
+
+    /** Written under [lock] only; volatile so [getChangeCounter] can read it without locking. */
+    @Volatile
+    private var changeCounter = 0L
+    private var cachedResult: GbtResult? = null
+    private var cachedChangeCounter = -1L
+    private var lastPositions = HashMap<Int, Int>()
+    /** Effective fee rate (sat/vB) each transaction was packed at in the last run. */
+    private var lastRates = HashMap<Int, Double>()
+    /** Effective fee rate of the last transaction packed into block 0 in the last run. */
+    private var lastBlockZeroCutoff = 0.0
+    /** Block 0 of the cached result as the run saw it, for [getBlockTemplate]. */
+    private var lastBlockZero: List<ThreadTransaction> = emptyList()
+    private var watched: Set<Int> = emptySet()
+    /** Watched uids already reported as never tracked, so they're reported only once. */
+    private val reportedUnknownWatched = HashSet<Int>()
+    /** The user's own transactions; dropped once reported as gone. See [setOwnTransactions]. */
+    private var own: MutableSet<Int> = HashSet()
+    private var lastAudit: BlockAudit? = null
+    private var lastAuditStatuses: Map<Int, Int> = emptyMap()
+    private var invalidTxCount = 0
+    private var rateDiscrepancyCount = 0
+    private var recorder: GbtRecorder? = null
+    private val counters = AtomicLongArray(COUNTER_COUNT)
+    /** Seed for everything randomized; see [setSeed]. */
+    @Volatile
+    private var seed = SecureRandom().nextLong()
+    /** Source of [GbtResult.runId]; every packing run, detached ones included, takes the next id. */
+    private val runIds = AtomicLong()
+    /** Read without [lock] by runs in flight, hence volatile. */
+    @Volatile
+    private var tracer: GbtTracer? = null
+    /** Packing and overflow order: best rate first, then [ThreadTransaction.order], then uid. */
+    private val byRate = compareByDescending<ThreadTransaction> { it.effectiveFeePerVsize }
+        .thenBy { it.order }
+        .thenBy { it.uid }
+
     fun make(
         mempool: List<ThreadTransaction>,
         accelerations: List<ThreadAcceleration> = emptyList(),
         maxUid: Int
     ): GbtResult? {
-        if (mempool.isEmpty()) return null
-        return runFallback(mempool, accelerations)
+        applyMake(mempool, accelerations)
+        checkMaxUid(maxUid)
+        return compute()
+    }
+
+    /** Replaces the tracked mempool without packing. Queued batches are dropped; the make supersedes them. */
+    internal fun applyMake(mempool: List<ThreadTransaction>, accelerations: List<ThreadAcceleration>) {
+        checkCallLimits(mempool, emptyList(), accelerations)
+        synchronized(pendingLock) {
+            lock.write {
+                val valid = prepareInserts(mempool)
+                record { recordMake(mempool, accelerations) }
+                clearPendingUpdates()
+                threadTransactions.clear()
+                maxTrackedUid = Int.MIN_VALUE
+                txidIndex.clear()
+                children.clear()
+                estimatedBytes = 0L
+                for (tx in valid) insertTransaction(tx)
+                this.accelerations = accelerations.associateBy { it.uid }
+                trimToSize()
+                changeCounter++
+                if (BuildConfig.DEBUG) checkState()
+            }
+        }
     }
 
     fun update(
         newTxs: List<ThreadTransaction> = emptyList(),
         removeTxs: List<Int> = emptyList(),
         accelerations: List<ThreadAcceleration> = emptyList(),
-        maxUid: Int
+        maxUid: Int,
+        removeDescendants: Boolean = false
     ): GbtResult? {
-        // Fallback: just do a full remake with newTxs
-        return runFallback(newTxs, accelerations)
+        applyUpdates(newTxs, removeTxs, accelerations, removeDescendants)
+        checkMaxUid(maxUid)
+        return compute()
+    }
+
+    /**
+     * Rejects a whole call that exceeds one of the [GbtConfig] size caps, before anything is
+     * recorded or applied.
+     *
+     * @throws IllegalArgumentException naming the exceeded limit, or [GbtInvalidInputException]
+     * for a transaction with too many inputs.
+     */
+    private fun checkCallLimits(
+        txs: List<ThreadTransaction>,
+        removals: List<Int>,
+        accelerations: List<ThreadAcceleration>
+    ) {
+        checkCount(txs.size, "transactions")
+        checkCount(removals.size, "removals")
+        require(accelerations.size <= config.maxAccelerations) {
+            "${accelerations.size} accelerations exceed maxAccelerations ${config.maxAccelerations}"
+        }
+        txs.firstOrNull { it.inputs.size > config.maxInputsPerTx }?.let {
+            throw GbtInvalidInputException(it.uid, "${it.inputs.size} inputs exceed maxInputsPerTx ${config.maxInputsPerTx}")
+        }
+    }
+
+    /** [checkCallLimits] for a single list of [what]: transactions, uids or txids. */
+    private fun checkCount(count: Int, what: String) {
+        require(count <= config.maxTxsPerCall) { "$count $what exceed maxTxsPerCall ${config.maxTxsPerCall}" }
+    }
+
+    /** Nothing is sized by max uid here, but an under-reported value points at a caller-side uid bug. */
+    private fun checkMaxUid(reportedMaxUid: Int) {
+        val actual = lock.read { maxTrackedUid }
+        if (reportedMaxUid < actual) Log.w(TAG, "Reported max uid $reportedMaxUid is below tracked max uid $actual")
+    }
+
+    /**
+     * Applies a batch without packing. With [removeDescendants], removing a transaction also
+     * removes everything spending it.
+     *
+     * While a [compute] run is in flight the batch is queued instead and this returns at
+     * once. Queued batches are applied in submission order when the run finishes, all of them
+     * before the next run takes its snapshot; [update] therefore packs its own batch. A batch
+     * that turns out to conflict in strict mode when applied is dropped whole and logged.
+     *
+     * Inserting a uid that is already tracked is a no-op if the transaction is identical
+     * (inputs included), so it doesn't invalidate the cached result. A differing transaction
+     * replaces the tracked one and is counted in [COUNTER_TXS_REPLACED], or throws in strict mode.
+     *
+     * @throws GbtBackpressureException if queueing the batch would exceed [MAX_PENDING_UPDATES].
+     */
+    fun applyUpdates(
+        newTxs: List<ThreadTransaction> = emptyList(),
+        removeTxs: List<Int> = emptyList(),
+        accelerations: List<ThreadAcceleration> = emptyList(),
+        removeDescendants: Boolean = false
+    ) {
+        checkCallLimits(newTxs, removeTxs, accelerations)
+        synchronized(pendingLock) {
+            if (!queueing()) {
+                applyBatch(newTxs, removeTxs, accelerations, removeDescendants)
+                return
+            }
+            // Shape checks don't depend on the state, so a strict caller still hears about them now.
+            if (strict) {
+                for (tx in newTxs) invalidReason(deriveRate(tx))?.let { throw GbtInvalidInputException(tx.uid, it) }
+            }
+            enqueue(newTxs.size + removeTxs.size) { applyBatch(newTxs, removeTxs, accelerations, removeDescendants) }
+        }
+    }
+
+    /** Whether a change must queue behind a run in flight or earlier queued changes. Called with [pendingLock] held. */
+    private fun queueing(): Boolean = runInFlight || pendingUpdates.isNotEmpty()
+
+    /**
+     * Queues [apply] behind the changes already pending; [changes] counts towards
+     * [MAX_PENDING_UPDATES]. Called with [pendingLock] held.
+     *
+     * @throws GbtBackpressureException if the queue would grow past [MAX_PENDING_UPDATES].
+     */
+    private fun enqueue(changes: Int, apply: () -> Unit) {
+        val update = PendingUpdate(maxOf(1, changes), apply)
+        if (pendingUpdateCount + update.size > MAX_PENDING_UPDATES) {
+            throw GbtBackpressureException(pendingUpdateCount, MAX_PENDING_UPDATES)
+        }
+        pendingUpdates.addLast(update)
+        pendingUpdateCount += update.size
+    }
+
+    /** Transactions and removals queued behind a run in flight and not yet applied. */
+    fun getPendingUpdateCount(): Int = synchronized(pendingLock) { pendingUpdateCount }
+
+    /** Applies queued batches in order. Called with [pendingLock] held. */
+    private fun drainPendingUpdates() {
+        while (true) {
+            val batch = pendingUpdates.removeFirstOrNull() ?: return
+            pendingUpdateCount -= batch.size
+            try {
+                batch.apply()
+            } catch (e: GbtInvalidInputException) {
+                Log.w(TAG, "Dropping queued update of ${batch.size} changes: ${e.message}")
+            }
+        }
+    }
+
+    private fun clearPendingUpdates() {
+        if (pendingUpdates.isEmpty()) return
+        Log.d(TAG, "Dropping ${pendingUpdates.size} queued updates")
+        pendingUpdates.clear()
+        pendingUpdateCount = 0
+    }
+
+    /** The batch itself; see [applyUpdates]. Called with [pendingLock] held. */
+    private fun applyBatch(
+        newTxs: List<ThreadTransaction>,
+        removeTxs: List<Int>,
+        accelerations: List<ThreadAcceleration>,
+        removeDescendants: Boolean
+    ) {
+        lock.write {
+            val removals = if (removeDescendants) withDescendantsLeavesFirst(removeTxs) else removeTxs
+            // Removals apply to the pre-update state, so a uid that is both removed and
+            // re-inserted in one batch ends up holding the new transaction. Within newTxs
+            // the last occurrence of a uid wins.
+            val inserts = newTxs.associateBy { it.uid }
+            if (inserts.size != newTxs.size) {
+                Log.d(TAG, "${newTxs.size - inserts.size} duplicate uids in update, keeping last occurrence")
+            }
+            val valid = prepareInserts(inserts.values)
+            val removalSet = removals.toHashSet()
+            val reinserted = valid.count { it.uid in removalSet }
+            if (reinserted > 0) Log.d(TAG, "$reinserted uids both removed and inserted, keeping new transactions")
+            // Resolved against the state the removals leave behind, so a strict conflict throws
+            // before anything is recorded or applied.
+            val changed = ArrayList<ThreadTransaction>(valid.size)
+            var replaced = 0L
+            for (derived in valid) {
+                val existing = if (derived.uid in removalSet) null else threadTransactions[derived.uid]
+                // The tracked copy was stamped with a first-seen time on insert; keep it.
+                val tx = if (existing != null && derived.firstSeen == null) {
+                    derived.copy(firstSeen = existing.firstSeen)
+                } else derived
+                if (existing != null) {
+                    if (existing == tx) continue
+                    if (strict) throw GbtInvalidInputException(tx.uid, "re-inserted with different fields")
+                    replaced++
+                }
+                changed.add(tx)
+            }
+            counters.incrementAndGet(COUNTER_UPDATES)
+            record { recordUpdate(newTxs, removals, accelerations) }
+
+            for (uid in removals) {
+                val removed = removeTransaction(uid) ?: continue
+                recentlyRemoved[uid] = removed
+                changeCounter++
+            }
+            counters.addAndGet(COUNTER_TXS_REPLACED, replaced)
+            for (tx in changed) {
+                insertTransaction(tx)
+                changeCounter++
+            }
+            setAccelerations(accelerations)
+            trimToSize()
+            if (peakSize > SHRINK_MIN_PEAK && threadTransactions.size < peakSize / 4) rebuildMaps()
+            if (BuildConfig.DEBUG) checkState()
+        }
+    }
+
+    /**
+     * Removes [uids] together with all of their in-mempool descendants, e.g. when the backend
+     * reports a conflict or eviction, so no tracked transaction is left spending a missing
+     * parent. Returns every uid actually removed, leaves first.
+     *
+     * While a [compute] run is in flight the removal queues behind earlier batches like
+     * [applyUpdates] does, descendants are resolved when it's applied, and an empty array is
+     * returned.
+     *
+     * @throws GbtBackpressureException if queueing the removal would exceed [MAX_PENDING_UPDATES].
+     */
+    fun removeWithDescendants(uids: IntArray): IntArray {
+        val roots = uids.toList()
+        checkCallLimits(emptyList(), roots, emptyList())
+        synchronized(pendingLock) {
+            if (!queueing()) return applyRemoveWithDescendants(roots)
+            enqueue(roots.size) { applyRemoveWithDescendants(roots) }
+            return IntArray(0)
+        }
+    }
+
+    /** The removal itself; see [removeWithDescendants]. */
+    private fun applyRemoveWithDescendants(uids: List<Int>): IntArray {
+        lock.write {
+            val removals = withDescendantsLeavesFirst(uids)
+            record { recordUpdate(emptyList(), removals, accelerations.values.toList()) }
+            for (uid in removals) {
+                recentlyRemoved[uid] = removeTransaction(uid) ?: continue
+                changeCounter++
+            }
+            if (BuildConfig.DEBUG) checkState()
+            return removals.toIntArray()
+        }
+    }
+
+    /**
+     * [removeWithDescendants] keyed by txid, e.g. for transactions confirmed in a new block.
+     * Txids are resolved through the generator's own index, so this only finds transactions
+     * inserted with a [ThreadTransaction.txid].
+     *
+     * Queues behind a run in flight like [removeWithDescendants]; the txids are then resolved
+     * when the removal is applied, and an empty [TxidRemoval] is returned.
+     *
+     * @throws GbtBackpressureException if queueing the removal would exceed [MAX_PENDING_UPDATES].
+     */
+    fun removeByTxid(txids: List<String>): TxidRemoval {
+        val requested = txids.toList()
+        checkCount(requested.size, "txids")
+        synchronized(pendingLock) {
+            if (!queueing()) return applyRemoveByTxid(requested)
+            enqueue(requested.size) { applyRemoveByTxid(requested) }
+            return TxidRemoval()
+        }
+    }
+
+    /** The removal itself; see [removeByTxid]. */
+    private fun applyRemoveByTxid(txids: List<String>): TxidRemoval {
+        lock.write {
+            val (found, missing) = txids.partition { it in txidIndex }
+            val removed = applyRemoveWithDescendants(found.map { txidIndex.getValue(it) })
+            return TxidRemoval(removed, missing.toTypedArray())
+        }
+    }
+
+    /** Tracked transactions in [uids] plus their descendants, each listed after all of its descendants. */
+    private fun withDescendantsLeavesFirst(uids: Collection<Int>): List<Int> {
+        val visited = HashSet<Int>()
+        val order = ArrayList<Int>()
+        for (root in uids) {
+            if (root !in threadTransactions || !visited.add(root)) continue
+            // Iterative post-order DFS over the child index; chains can be long.
+            val stack = ArrayDeque<Pair<Int, Iterator<Int>>>()
+            stack.addLast(root to children[root].orEmpty().toList().iterator())
+            while (stack.isNotEmpty()) {
+                val (uid, pending) = stack.last()
+                val next = pending.asSequence().firstOrNull { it in threadTransactions && visited.add(it) }
+                if (next != null) {
+                    stack.addLast(next to children[next].orEmpty().toList().iterator())
+                } else {
+                    stack.removeLast()
+                    order.add(uid)
+                }
+            }
+        }
+        return order
+    }
+
+    /**
+     * Moves transactions removed by earlier updates back into the mempool, e.g. after a reorg
+     * made their block stale. Returns the uids that were no longer cached and must be re-sent.
+     *
+     * While a [compute] run is in flight this queues behind earlier batches like [applyUpdates]
+     * does. The returned uids are then judged against the state at the time of the call; one
+     * that has dropped out of the cache by the time the queue drains is logged and skipped.
+     *
+     * @throws GbtBackpressureException if queueing would exceed [MAX_PENDING_UPDATES].
+     */
+    fun reinstate(uids: IntArray): IntArray {
+        val requested = uids.toList()
+        checkCount(requested.size, "uids")
+        synchronized(pendingLock) {
+            if (!queueing()) return applyReinstate(requested)
+            val missing = lock.read { requested.filter { it !in recentlyRemoved && it !in threadTransactions } }
+            enqueue(requested.size) {
+                val lost = applyReinstate(requested)
+                if (lost.isNotEmpty()) Log.w(TAG, "${lost.size} queued reinstatements no longer cached")
+            }
+            return missing.toIntArray()
+        }
+    }
+
+    /** The reinstatement itself; see [reinstate]. Returns the uids that couldn't be reinstated. */
+    private fun applyReinstate(uids: List<Int>): IntArray {
+        val missing = mutableListOf<Int>()
+        lock.write {
+            val reinstated = mutableListOf<ThreadTransaction>()
+            for (uid in uids) {
+                val tx = recentlyRemoved[uid]
+                if (tx == null) {
+                    if (uid !in threadTransactions) missing.add(uid)
+                    continue
+                }
+                reinstated.add(tx)
+            }
+            // Replayed as a plain insert: the replaying generator may not have them cached.
+            if (reinstated.isNotEmpty()) record { recordUpdate(reinstated, emptyList(), accelerations.values.toList()) }
+            for (tx in reinstated) {
+                insertTransaction(tx)
+                changeCounter++
+            }
+            if (BuildConfig.DEBUG) checkState()
+        }
+        return missing.toIntArray()
+    }
+
+    /**
+     * Starts recording every input that changes the tracked state, and every compute, to
+     * [path] for later [GbtReplay], or stops recording when [path] is null. The file starts
+     * with the config and the current state, so recording can begin at any point.
+     */
+    fun setRecording(path: String?, maxBytes: Long = GbtRecorder.DEFAULT_MAX_BYTES) {
+        lock.write {
+            recorder?.close()
+            recorder = path?.let {
+                GbtRecorder(File(it), config, maxBytes) {
+                    threadTransactions.values.sortedBy { tx -> tx.uid } to accelerations.values.sortedBy { a -> a.uid }
+                }
+            }
+        }
+    }
+
+    /** Starts collecting phase timings for a Chrome trace at [path]; see [GbtTracer]. */
+    fun startTrace(path: String) {
+        lock.write {
+            tracer = GbtTracer(File(path))
+        }
+    }
+
+    /** Writes the trace started by [startTrace]. Returns false if it couldn't be written. */
+    fun stopTrace(): Boolean {
+        val active = lock.write { tracer.also { tracer = null } } ?: return true
+        return try {
+            active.close()
+            true
+        } catch (e: IOException) {
+            Log.e(TAG, "Failed to write trace", e)
+            false
+        }
+    }
+
+    /**
+     * Runs [block] as a named phase: always an android.os.Trace section (close to free
+     * unless a system trace is capturing), and a [GbtTracer] event while one is active.
+     */
+    private inline fun <T> traced(name: String, vararg args: Pair<String, Any>, block: () -> T): T {
+        val active = tracer
+        val start = if (active != null) System.nanoTime() else 0L
+        Trace.beginSection(name)
+        try {
+            return block()
+        } finally {
+            Trace.endSection()
+            active?.record(name, start, System.nanoTime(), args)
+        }
+    }
+
+    /** Recording is best effort: an I/O failure stops it rather than failing the caller. */
+    private fun record(block: GbtRecorder.() -> Unit) {
+        val active = recorder ?: return
+        try {
+            active.block()
+        } catch (e: IOException) {
+            Log.e(TAG, "Recording failed, stopping", e)
+            recorder = null
+            try { active.close() } catch (ignored: IOException) { }
+        }
+    }
+
+    /** Uids dropped by the size cap since the previous call. The caller can stop sending updates for them. */
+    fun getEvictedSinceLastCall(): IntArray = lock.write {
+        evictedUids.toIntArray().also { evictedUids = mutableListOf() }
+    }
+
+    /**
+     * Fee rate (sat/vB) a transaction currently needs to stay under the size cap: the highest
+     * evicted package rate, halving every 12 hours like bitcoind's rolling minimum fee.
+     * 0 if nothing has been evicted.
+     */
+    fun getMempoolMinFee(): Double = lock.read {
+        if (minFeeRate == 0.0) return@read 0.0
+        val elapsed = System.currentTimeMillis() / 1000 - minFeeRateTime
+        minFeeRate * Math.pow(0.5, elapsed.toDouble() / MIN_FEE_HALF_LIFE_SECONDS)
+    }
+
+    /**
+     * Evicts packages until the mempool fits [maxTxCount] and [maxMemoryBytes], the way
+     * bitcoind's TrimToSize does: the transaction with the lowest descendant score (the
+     * higher of its own rate and its descendant package rate, acceleration included) goes
+     * first, together with all of its descendants, so no retained transaction loses a parent.
+     *
+     * A transaction's own rate is a lower bound on its descendant score, so candidates are
+     * queued by that and only walk their descendants once they reach the front of the queue;
+     * the well-paying bulk of the mempool is never scored.
+     */
+    private fun trimToSize() {
+        if (!overCap()) return
+        // Exact descendant scores computed so far; queue entries that disagree are stale.
+        val scores = HashMap<Int, Double>()
+        val heap = PriorityQueue(threadTransactions.values.map { EvictionCandidate(ownScore(it), it.uid, exact = false) })
+        val before = threadTransactions.size
+        while (overCap()) {
+            val candidate = heap.poll() ?: break
+            val uid = candidate.uid
+            if (uid !in threadTransactions) continue
+            if (!candidate.exact) {
+                if (uid in scores) continue
+                val score = descendantScore(uid)
+                scores[uid] = score
+                heap.add(EvictionCandidate(score, uid, exact = true))
+                continue
+            }
+            if (scores[uid] != candidate.score) continue
+            val evicted = withDescendantsLeavesFirst(listOf(uid))
+            val parents = evicted.flatMap { threadTransactions.getValue(it).inputs.asList() }
+            for (victim in evicted) {
+                removeTransaction(victim)
+                scores.remove(victim)
+                evictedUids.add(victim)
+                changeCounter++
+            }
+            minFeeRate = maxOf(getMempoolMinFee(), candidate.score)
+            minFeeRateTime = System.currentTimeMillis() / 1000
+            // Ancestors lost descendants, so their scores may have changed; back to the lower bound.
+            for (ancestor in walk(parents) { threadTransactions[it]?.inputs?.asList() }) {
+                scores.remove(ancestor)
+                heap.add(EvictionCandidate(ownScore(threadTransactions.getValue(ancestor)), ancestor, exact = false))
+            }
+        }
+        Log.d(TAG, "Size cap reached, evicted ${before - threadTransactions.size} transactions")
+    }
+
+    private fun overCap(): Boolean {
+        return (maxTxCount > 0 && threadTransactions.size > maxTxCount) ||
+            (maxMemoryBytes > 0 && estimatedBytes > maxMemoryBytes)
+    }
+
+    private fun descendantScore(uid: Int): Double {
+        val tx = threadTransactions.getValue(uid)
+        val descendants = walk(children[uid].orEmpty()) { children[it] }.map { threadTransactions.getValue(it) }
+        val packageVsize = weightToVsize(tx.weight) + descendants.sumOf { weightToVsize(it.weight).toLong() }
+        val packageRate = (evictionFee(tx) + descendants.sumOf { evictionFee(it) }).toDouble() / packageVsize
+        return maxOf(ownScore(tx), packageRate)
+    }
+
+    /** [tx]'s own rate, acceleration included: the lower bound of its [descendantScore]. */
+    private fun ownScore(tx: ThreadTransaction): Double = evictionFee(tx).toDouble() / weightToVsize(tx.weight)
+
+    private fun evictionFee(tx: ThreadTransaction): Long = tx.fee + (accelerations[tx.uid]?.delta ?: 0L)
+
+    private fun estimateBytes(tx: ThreadTransaction): Long {
+        return TX_OVERHEAD_BYTES + 4L * tx.inputs.size + if (tx.txid != null) TXID_BYTES else 0L
+    }
+
+    /**
+     * Transactions whose projection changes are reported in [GbtResult.watchedChanges].
+     * Replaces the previous watch list.
+     */
+    fun setWatched(uids: IntArray) {
+        lock.write {
+            watched = uids.toHashSet()
+            reportedUnknownWatched.retainAll(watched)
+        }
+    }
+
+    /**
+     * The user's own transactions, reported in every result's [GbtResult.ownTxPositions].
+     * Replaces the previous set. A uid that isn't tracked at the next run, because it was
+     * mined, removed or never added, is reported once as [GbtResult.UNKNOWN_UID] and dropped.
+     */
+    fun setOwnTransactions(uids: IntArray) {
+        lock.write { own = uids.toHashSet() }
+    }
+
+    /**
+     * Seeds all randomized work (currently [computeEnsemble]) so a reported result can be
+     * reproduced. Defaults to a random seed per generator; results record it in [GbtResult.seed].
+     */
+    fun setSeed(seed: Long) {
+        this.seed = seed
+    }
+
+    /** The configuration in effect, with defaults filled in and limits applied. */
+    fun getConfig(): GbtConfig = config
+
+    /** Number of transactions currently tracked. Doesn't wait for an in-flight run. */
+    fun size(): Int = lock.read { threadTransactions.size }
+
+    /** Copy of the tracked transactions by uid, for comparing generator states in tests. */
+    internal fun trackedTransactions(): Map<Int, ThreadTransaction> = lock.read { HashMap(threadTransactions) }
+
+    /** Entry count the maps were last sized for; java.util.HashMap hides its capacity, so tests read this. */
+    internal fun mapSizedFor(): Int = lock.read { peakSize }
+
+    /**
+     * The [n] transactions with the highest effective fee rate in the last run, best first,
+     * ties broken by lower uid. Before the first run the caller-provided rates are used.
+     */
+    fun getTopRates(n: Int): RankedRates = lock.read {
+        val rates = if (cachedResult != null) lastRates else threadTransactions.mapValues { it.value.effectiveFeePerVsize }
+        val ranking = compareByDescending<Map.Entry<Int, Double>> { it.value }.thenBy { it.key }
+        if (n <= 0) return@read RankedRates()
+        // Min-heap of the best n seen so far: the worst kept entry sits on top.
+        val heap = PriorityQueue(minOf(n, maxOf(1, rates.size)), ranking.reversed())
+        for (entry in rates.entries) {
+            if (heap.size < n) {
+                heap.add(entry)
+            } else if (ranking.compare(entry, heap.peek()) < 0) {
+                heap.poll()
+                heap.add(entry)
+            }
+        }
+        val top = heap.sortedWith(ranking)
+        RankedRates(uids = IntArray(top.size) { top[it].key }, rates = DoubleArray(top.size) { top[it].value })
+    }
+
+    /**
+     * Projected block index of [uid] in the last run, [GbtResult.OVERFLOW_BLOCK] if it didn't
+     * fit, or [GbtResult.UNKNOWN_UID] if it wasn't part of that run.
+     */
+    fun getTxPosition(uid: Int): Int = lock.read { lastPositions[uid] ?: GbtResult.UNKNOWN_UID }
+
+    /** [getTxPosition] for a batch, plus the fee rate each uid was packed at (0 if unknown). */
+    fun getConfirmationEtas(uids: IntArray): ConfirmationEtas = lock.read {
+        ConfirmationEtas(
+            blocks = IntArray(uids.size) { lastPositions[uids[it]] ?: GbtResult.UNKNOWN_UID },
+            rates = DoubleArray(uids.size) { lastRates[uids[it]] ?: 0.0 }
+        )
+    }
+
+    /**
+     * Total vsize and fee (sats, acceleration-adjusted) of the transactions the last run packed
+     * at [rate] sat/vB or better, i.e. the mempool depth a transaction at that rate competes with.
+     * Overflow doesn't count, whatever its rate.
+     */
+    fun getDepthAtRate(rate: Double): MempoolDepth = lock.read {
+        var vsize = 0L
+        var fees = 0L
+        for ((uid, packedRate) in lastRates) {
+            if (packedRate < rate) continue
+            val block = lastPositions[uid]
+            if (block == null || block == GbtResult.OVERFLOW_BLOCK) continue
+            val tx = threadTransactions[uid] ?: continue
+            vsize += weightToVsize(tx.weight)
+            fees += tx.fee + (accelerations[uid]?.delta ?: 0L)
+        }
+        MempoolDepth(vsize, fees)
+    }
+
+    /**
+     * The stored accelerations with their effect in the last run. Read-only: uses the last
+     * result as is and never triggers a run.
+     */
+    fun getAccelerations(): AppliedAccelerations = lock.read {
+        val applied = accelerations.values.sortedBy { it.uid }
+        AppliedAccelerations(
+            uids = IntArray(applied.size) { applied[it].uid },
+            deltas = LongArray(applied.size) { applied[it].delta },
+            boostedRates = DoubleArray(applied.size) { lastRates[applied[it].uid] ?: 0.0 },
+            unboostedRates = DoubleArray(applied.size) { i ->
+                // Sigop-scaled like the base rates in GbtResult.rates.
+                val tx = threadTransactions[applied[i].uid] ?: return@DoubleArray 0.0
+                tx.effectiveFeePerVsize * weightToVsize(tx.weight) / weightToVsize(packWeight(tx))
+            },
+            projectedBlocks = IntArray(applied.size) { lastPositions[applied[it].uid] ?: GbtResult.UNKNOWN_UID },
+            stale = BooleanArray(applied.size) { applied[it].uid !in threadTransactions }
+        )
+    }
+
+    /** In-mempool ancestors of [uid], nearest first, or null if [uid] isn't tracked. */
+    fun getAncestors(uid: Int): IntArray? = lock.read {
+        val tx = threadTransactions[uid] ?: return@read null
+        walk(tx.inputs.asList()) { threadTransactions[it]?.inputs?.asList() }
+    }
+
+    /** In-mempool descendants of [uid], nearest first, or null if [uid] isn't tracked. */
+    fun getDescendants(uid: Int): IntArray? = lock.read {
+        if (uid !in threadTransactions) return@read null
+        walk(children[uid].orEmpty()) { children[it] }
+    }
+
+    /** Breadth-first walk over tracked transactions starting from [start]. */
+    private fun walk(start: Collection<Int>, next: (Int) -> Collection<Int>?): IntArray {
+        val seen = LinkedHashSet<Int>()
+        val queue = ArrayDeque<Int>()
+        for (uid in start) if (uid in threadTransactions && seen.add(uid)) queue.add(uid)
+        while (queue.isNotEmpty()) {
+            for (uid in next(queue.removeFirst()).orEmpty()) {
+                if (uid in threadTransactions && seen.add(uid)) queue.add(uid)
+            }
+        }
+        return seen.toIntArray()
+    }
+
+    /** [GbtResult.templateHash] of the last run, or 0 before the first run. */
+    fun getTemplateHash(): Long = lock.read { cachedResult?.templateHash ?: 0L }
+
+    /**
+     * Paged access to the heavy sections of the last full result, so callers can compute
+     * without [GbtResult.DETAIL_RATES] / [GbtResult.DETAIL_CLUSTERS] and fetch only what they
+     * display. The retained result is replaced by the next [compute] and dropped by [reset].
+     * Pass the [GbtResult.runId] the pages belong to as `runId` to get nothing back once the
+     * retained result has moved on, instead of mixing pages from two runs; 0 skips the check.
+     */
+    fun getRatesCount(): Int = lock.read { cachedResult?.rates?.size ?: 0 }
+
+    /** [GbtResult.runId] of the retained result, or 0 if there is none. */
+    fun getResultRunId(): Long = lock.read { cachedResult?.runId ?: 0L }
+
+    /** Up to [limit] rows of [GbtResult.rates] starting at [offset]; empty past the end. */
+    fun getRatesPage(offset: Int, limit: Int, runId: Long = 0L): Array<DoubleArray> = lock.read {
+        val rates = retainedResult(runId)?.rates ?: return@read emptyArray()
+        if (offset < 0 || limit <= 0 || offset >= rates.size) return@read emptyArray()
+        rates.copyOfRange(offset, minOf(rates.size, offset + limit))
+    }
+
+    fun getClusterCount(): Int = lock.read { cachedResult?.clusters?.size ?: 0 }
+
+    /** Members of cluster [index] of the last result, or null if out of range. */
+    fun getCluster(index: Int, runId: Long = 0L): IntArray? = lock.read {
+        retainedResult(runId)?.clusters?.getOrNull(index)
+    }
+
+    private fun retainedResult(runId: Long): GbtResult? {
+        val result = cachedResult ?: return null
+        return if (runId == 0L || result.runId == runId) result else null
+    }
+
+    /**
+     * Lock-free check for "is there anything new": compare against [GbtResult.changeCounter] of
+     * the last result and skip [compute] when equal. Bumped by each inserted or replaced
+     * transaction, each removal (explicit, by txid, with descendants, or evicted by the size
+     * caps), each [reinstate]d transaction, any change to the acceleration set, [reset], and
+     * each completed [compute] run that publishes a result for the current state, so a poller
+     * also learns about results someone else computed. The published result embeds the bumped
+     * value. Cached returns, override and detached runs ([simulate], [computeNextBlock] and
+     * the like), [setWatched] and [setSeed] don't bump it.
+     */
+    fun getChangeCounter(): Long = changeCounter
+
+    /** Number of changes applied since the state the last result was computed from. */
+    fun pendingChangeCount(): Long = lock.read {
+        if (cachedChangeCounter < 0) changeCounter else changeCounter - cachedChangeCounter
+    }
+
+    /**
+     * Snapshot of the diagnostics counters, indexed by the COUNTER_* constants. Doesn't take
+     * the generator lock except to read the current map size.
+     */
+    fun getCounters(): LongArray {
+        val values = LongArray(COUNTER_COUNT) { counters.get(it) }
+        values[COUNTER_MAP_SIZE] = size().toLong()
+        return values
+    }
+
+    /**
+     * Drops all mempool state, e.g. after the RPC connection was re-established. The
+     * diagnostics counters survive unless [resetCounters] is set.
+     */
+    fun reset(resetCounters: Boolean = false) {
+        synchronized(pendingLock) {
+            lock.write {
+                record { recordReset(resetCounters) }
+                clearPendingUpdates()
+                if (resetCounters) for (i in 0 until COUNTER_COUNT) counters.set(i, 0L)
+                threadTransactions.clear()
+                maxTrackedUid = Int.MIN_VALUE
+                txidIndex.clear()
+                children.clear()
+                recentlyRemoved.clear()
+                accelerations = emptyMap()
+                estimatedBytes = 0L
+                evictedUids = mutableListOf()
+                minFeeRate = 0.0
+                cachedResult = null
+                lastPositions = HashMap()
+                lastRates = HashMap()
+                lastBlockZeroCutoff = 0.0
+                lastBlockZero = emptyList()
+                lastAudit = null
+                lastAuditStatuses = emptyMap()
+                reportedUnknownWatched.clear()
+                changeCounter++
+            }
+        }
+    }
+
+    /**
+     * Derives and validates a whole batch before the caller records or applies any of it: in
+     * strict mode the first invalid transaction throws with the state untouched, otherwise
+     * invalid ones are counted and left out of the returned list.
+     */
+    private fun prepareInserts(txs: Collection<ThreadTransaction>): List<ThreadTransaction> {
+        val derived = txs.map { deriveRate(it) }
+        if (strict) {
+            for (tx in derived) invalidReason(tx)?.let { throw GbtInvalidInputException(tx.uid, it) }
+        }
+        txs.forEachIndexed { i, raw -> checkProvidedRate(raw, derived[i]) }
+        return derived.filter { validate(it) }
+    }
+
+    /** With [deriveRates], replaces the provided rate by fee / vsize. */
+    private fun deriveRate(tx: ThreadTransaction): ThreadTransaction {
+        if (!deriveRates || tx.weight <= 0) return tx
+        return tx.copy(effectiveFeePerVsize = tx.fee.toDouble() / weightToVsize(tx.weight))
+    }
+
+    /**
+     * Counts providers whose rate disagrees with the derived one by more than [RATE_TOLERANCE],
+     * so upstream rate bugs show up in the results.
+     */
+    private fun checkProvidedRate(raw: ThreadTransaction, derived: ThreadTransaction) {
+        if (derived === raw) return
+        val provided = raw.effectiveFeePerVsize
+        val rate = derived.effectiveFeePerVsize
+        if (!(Math.abs(provided - rate) <= rate * RATE_TOLERANCE)) {
+            Log.d(TAG, "Transaction ${raw.uid}: provided rate $provided, derived $rate")
+            rateDiscrepancyCount++
+        }
+    }
+
+    /** Returns false (or throws in strict mode) for transactions the packer can't score. */
+    private fun validate(tx: ThreadTransaction): Boolean {
+        val reason = invalidReason(tx) ?: return true
+        if (strict) throw GbtInvalidInputException(tx.uid, reason)
+        Log.w(TAG, "Rejected transaction ${tx.uid}: $reason")
+        invalidTxCount++
+        counters.incrementAndGet(COUNTER_TXS_REJECTED)
+        return false
+    }
+
+    private fun invalidReason(tx: ThreadTransaction): String? {
+        if (tx.weight < MIN_TX_WEIGHT || tx.weight > MAX_TX_WEIGHT) return "weight ${tx.weight} out of range"
+        if (tx.fee < 0) return "negative fee ${tx.fee}"
+        if (!tx.effectiveFeePerVsize.isFinite() || tx.effectiveFeePerVsize < 0) {
+            return "bad effective fee rate ${tx.effectiveFeePerVsize}"
+        }
+        if (tx.sigops < 0 || tx.sigops > MAX_TX_SIGOPS) return "sigops ${tx.sigops} out of range"
+        if (tx.uid in tx.inputs) return "spends itself"
+        if (tx.inputs.toSet().size != tx.inputs.size) return "duplicate inputs"
+        return null
+    }
+
+    /**
+     * Releases memory in response to [android.content.ComponentCallbacks2.onTrimMemory]: the
+     * maps are rebuilt at their current size, and from TRIM_MEMORY_RUNNING_LOW up the
+     * recently-removed cache used by [reinstate] is dropped too.
+     */
+    fun trimMemory(level: Int) {
+        lock.write {
+            rebuildMaps()
+            if (level >= ComponentCallbacks2.TRIM_MEMORY_RUNNING_LOW) recentlyRemoved.clear()
+        }
+    }
+
+    /** Copies the maps into right-sized ones. Runs only work on snapshots, so this is safe mid-compute. */
+    private fun rebuildMaps() {
+        threadTransactions = HashMap(threadTransactions)
+        txidIndex = HashMap(txidIndex)
+        children = HashMap(children)
+        peakSize = threadTransactions.size
+        maxTrackedUid = threadTransactions.keys.maxOrNull() ?: Int.MIN_VALUE
+    }
+
+    private fun insertTransaction(transaction: ThreadTransaction) {
+        val tx = if (transaction.firstSeen == null) {
+            transaction.copy(firstSeen = System.currentTimeMillis() / 1000)
+        } else transaction
+        recentlyRemoved.remove(tx.uid)
+        peakSize = maxOf(peakSize, threadTransactions.size + 1)
+        maxTrackedUid = maxOf(maxTrackedUid, tx.uid)
+        threadTransactions.put(tx.uid, tx)?.let { unindex(it) }
+        counters.incrementAndGet(COUNTER_TXS_INSERTED)
+        counters.accumulateAndGet(COUNTER_PEAK_MAP_SIZE, threadTransactions.size.toLong()) { a, b -> maxOf(a, b) }
+        estimatedBytes += estimateBytes(tx)
+        for (input in tx.inputs) children.getOrPut(input) { HashSet() }.add(tx.uid)
+        val txid = tx.txid ?: return
+        val existing = txidIndex.put(txid, tx.uid)
+        if (existing != null && existing != tx.uid && threadTransactions.containsKey(existing)) {
+            Log.w(TAG, "txid $txid mapped to both uid $existing and uid ${tx.uid}")
+        }
+    }
+
+    private fun removeTransaction(uid: Int): ThreadTransaction? {
+        return threadTransactions.remove(uid)?.also {
+            unindex(it)
+            counters.incrementAndGet(COUNTER_TXS_REMOVED)
+        }
+    }
+
+    private fun unindex(tx: ThreadTransaction) {
+        estimatedBytes -= estimateBytes(tx)
+        for (input in tx.inputs) {
+            val spenders = children[input] ?: continue
+            spenders.remove(tx.uid)
+            if (spenders.isEmpty()) children.remove(input)
+        }
+        val txid = tx.txid ?: return
+        if (txidIndex[txid] == tx.uid) txidIndex.remove(txid)
+    }
+
+    /**
+     * Walks the whole tracked graph and cross-checks it against the indexes and counters kept
+     * alongside it. Doesn't throw; takes a few hundred ms at 300k transactions, so call it
+     * opportunistically. Debug builds run it after every update.
+     */
+    fun verifyState(): GbtStateReport = lock.read {
+        val orphans = mutableListOf<Int>()
+        val duplicateInputs = mutableListOf<Int>()
+        val txidMismatches = mutableListOf<Int>()
+        val expectedChildren = HashMap<Int, MutableSet<Int>>()
+        var expectedBytes = 0L
+        for (tx in threadTransactions.values) {
+            if (tx.inputs.any { it !in threadTransactions }) orphans.add(tx.uid)
+            if (tx.inputs.toSet().size != tx.inputs.size) duplicateInputs.add(tx.uid)
+            if (tx.txid != null && txidIndex[tx.txid] != tx.uid) txidMismatches.add(tx.uid)
+            for (input in tx.inputs) expectedChildren.getOrPut(input) { HashSet() }.add(tx.uid)
+            expectedBytes += estimateBytes(tx)
+        }
+        val childMismatches = HashSet<Int>()
+        for (parent in expectedChildren.keys + children.keys) {
+            val expected = expectedChildren[parent].orEmpty()
+            val actual = children[parent].orEmpty()
+            childMismatches.addAll(expected - actual)
+            childMismatches.addAll(actual - expected)
+        }
+        val stalePositions = if (cachedResult != null && cachedChangeCounter == changeCounter) {
+            lastPositions.keys.filter { it !in threadTransactions }
+        } else emptyList()
+
+        fun sample(uids: Collection<Int>) = uids.sorted().take(GbtStateReport.MAX_REPORTED_UIDS).toIntArray()
+        GbtStateReport(
+            txCount = threadTransactions.size,
+            orphanCount = orphans.size,
+            orphanUids = sample(orphans),
+            childIndexMismatchCount = childMismatches.size,
+            childIndexMismatchUids = sample(childMismatches),
+            duplicateInputCount = duplicateInputs.size,
+            duplicateInputUids = sample(duplicateInputs),
+            txidIndexMismatchCount = txidMismatches.size,
+            txidIndexMismatchUids = sample(txidMismatches),
+            countersMatch = expectedBytes == estimatedBytes,
+            stalePositionCount = stalePositions.size,
+            stalePositionUids = sample(stalePositions)
+        )
+    }
+
+    private fun checkState() {
+        val report = verifyState()
+        if (!report.isConsistent) throw GbtStateCorruptException("Inconsistent GBT state: $report")
+    }
+
+    private fun setAccelerations(accelerations: List<ThreadAcceleration>) {
+        val newAccelerations = accelerations.associateBy { it.uid }
+        if (newAccelerations != this.accelerations) {
+            this.accelerations = newAccelerations
+            changeCounter++
+        }
+    }
+
+    /**
+     * Packs the current mempool. [lock] is only held while snapshotting the state and while
+     * publishing the result, so queries and updates aren't blocked for the whole run;
+     * [computeLock] keeps concurrent runs from interleaving their diffs.
+     *
+     * A non-zero [maxBlockWeightOverride] or non-null [maxBlocksOverride] replaces the configured
+     * block weight / block count for this run only; [maxBlocksOverride] is interpreted as in
+     * [create], so 0 asks for unlimited blocks. Such runs neither use nor replace the cached
+     * result, and carry no [GbtResult.diff].
+     * [detail] is a mask of GbtResult.DETAIL_* flags; omitted sections are left empty.
+     */
+    fun compute(
+        maxBlockWeightOverride: Int = 0,
+        maxBlocksOverride: Int? = null,
+        detail: Int = GbtResult.DETAIL_ALL
+    ): GbtResult? {
+        val runMaxBlockWeight = if (maxBlockWeightOverride > 0) checkMaxBlockWeight(maxBlockWeightOverride) else maxBlockWeight
+        val runMaxBlocks = if (maxBlocksOverride != null) clampMaxBlocks(maxBlocksOverride) else maxBlocks
+        if (runMaxBlockWeight != maxBlockWeight || runMaxBlocks != maxBlocks) {
+            return packDetached(runMaxBlockWeight, runMaxBlocks, detail)
+        }
+
+        synchronized(computeLock) {
+            // Batches arriving from here on queue up behind this run; see applyUpdates.
+            synchronized(pendingLock) { runInFlight = true }
+            try {
+                counters.incrementAndGet(COUNTER_COMPUTES)
+                counters.set(COUNTER_LAST_COMPUTE_TIME, System.currentTimeMillis())
+                val snapshot = lock.write {
+                    record { recordCompute() }
+                    val invalid = invalidTxCount
+                    val discrepancies = rateDiscrepancyCount
+                    invalidTxCount = 0
+                    rateDiscrepancyCount = 0
+                    val cached = cachedResult
+                    if (cached != null && cachedChangeCounter == changeCounter) {
+                        return cached.copy(
+                            fromCache = true,
+                            diff = GbtResultDiff(),
+                            invalidTxCount = invalid,
+                            rateDiscrepancyCount = discrepancies,
+                            watchedChanges = GbtWatchedChanges(),
+                            ownTxPositions = ownPositions(lastPositions, lastRates, threadTransactions),
+                            changeCounter = cachedChangeCounter
+                        )
+                            .withDetail(detail)
+                    }
+                    MempoolSnapshot(HashMap(threadTransactions), accelerations, changeCounter, invalid, discrepancies)
+                }
+                afterSnapshot?.invoke()
+
+                val runId = runIds.incrementAndGet()
+                val started = System.nanoTime()
+                val packed = traced("gbt.run", "run" to runId, "txs" to snapshot.transactions.size) {
+                    runFallback(snapshot.transactions.values.toList(), snapshot.accelerations, maxBlockWeight, maxBlocks, runId)
+                }.also {
+                    counters.addAndGet(COUNTER_COMPUTE_MILLIS, (System.nanoTime() - started) / 1_000_000)
+                }?.let { if (includeTxids) traced("gbt.txids", "run" to runId) { withTxids(it, snapshot.transactions) } else it }
+
+                lock.write {
+                    // A run that's already stale when it finishes leaves the counter alone, so
+                    // pendingChangeCount still counts exactly the changes it missed.
+                    val counter = if (packed != null && changeCounter == snapshot.changeCounter) {
+                        ++changeCounter
+                    } else snapshot.changeCounter
+                    val result = packed?.let {
+                        val positions = positionsOf(it)
+                        val rates = ratesOf(it, snapshot.transactions)
+                        val watchedChanges = watchedChanges(lastPositions, positions, lastRates, rates)
+                        lastRates = rates
+                        lastBlockZeroCutoff = it.nextBlockCutoffRate
+                        it.copy(
+                            diff = diffPositions(lastPositions, positions),
+                            invalidTxCount = snapshot.invalidTxCount,
+                            rateDiscrepancyCount = snapshot.rateDiscrepancyCount,
+                            watchedChanges = watchedChanges,
+                            ownTxPositions = ownPositions(positions, rates, snapshot.transactions),
+                            changeCounter = counter
+                        ).also { lastPositions = positions }
+                    }
+                    cachedResult = result
+                    cachedChangeCounter = counter
+                    lastBlockZero = result?.blocks?.firstOrNull()?.map { snapshot.transactions.getValue(it) }.orEmpty()
+                    return result?.withDetail(detail)
+                }
+            } finally {
+                synchronized(pendingLock) {
+                    try {
+                        drainPendingUpdates()
+                    } finally {
+                        runInFlight = false
+                    }
+                }
+            }
+        }
+    }
+
+    /** [compute] encoded with [GbtResultBlob], for passing the result to another process. */
+    fun computeToBlob(detail: Int = GbtResult.DETAIL_ALL): ByteArray? {
+        return compute(detail = detail)?.let { GbtResultBlob.encode(it) }
+    }
+
+    /**
+     * Projects only the next block. Packing stops as soon as block 0 is full; everything
+     * after that is reported in overflow as [GbtResult.OVERFLOW_NOT_EVALUATED]. Like override
+     * runs, the result isn't cached and carries no diff.
+     */
+    fun computeNextBlock(detail: Int = GbtResult.DETAIL_BLOCKS or GbtResult.DETAIL_WEIGHTS): GbtResult? {
+        return packDetached(maxBlockWeight, maxBlocks, detail, nextBlockOnly = true)
+    }
+
+    /**
+     * Scenario run of the current mempool with a different block weight and block count,
+     * e.g. "what if blocks were half the size". Returns a full result and leaves the tracked
+     * state, cached result and template hash untouched. Waits for an in-flight [compute]
+     * rather than interleaving with it. [maxBlocks] is interpreted as in [create].
+     */
+    fun simulate(maxBlockWeight: Int, maxBlocks: Int): GbtResult? {
+        synchronized(computeLock) {
+            return packDetached(checkMaxBlockWeight(maxBlockWeight), clampMaxBlocks(maxBlocks), GbtResult.DETAIL_ALL)
+        }
+    }
+
+    /**
+     * Projects the current mempool once per entry of [maxBlockWeights], e.g. today's 4 MWU
+     * blocks next to 1 MB-era ones, returning one slim projection per limit in the same order.
+     * The snapshot, its ancestor sets and the initial package scores are built once; each limit
+     * only reruns the packing loop on a copy of those scores, with the same sigop pricing and
+     * [maxBlocks]. Like [simulate], this waits for an in-flight [compute], and every entry is
+     * packed from the one snapshot taken then, so each entry's blocks match a [simulate] run
+     * with that weight over the same state. Leaves the tracked state and cached result
+     * untouched; returns an empty array if packing fails.
+     */
+    fun computeMulti(maxBlockWeights: IntArray): Array<GbtBlockProjection> {
+        val weights = maxBlockWeights.map { checkMaxBlockWeight(it) }
+        synchronized(computeLock) {
+            val (transactions, currentAccelerations) = lock.read { HashMap(threadTransactions) to accelerations }
+            val runId = runIds.incrementAndGet()
+            try {
+                val graph = buildGraph(transactions.values.toList(), currentAccelerations, runId)
+                val initial = newStates(graph)
+                val inputsByUid = graph.scorable.associate { it.uid to it.inputs }
+                return weights.map { weight ->
+                    val states = HashMap<Int, PackingTx>(initial.size)
+                    for ((uid, state) in initial) states[uid] = state.copy()
+                    val packed = pack(
+                        graph, states, weight, maxBlocks, runId,
+                        nextBlockOnly = false, includeRateSamples = false, includeCpfpEdges = false
+                    )
+                    enforceBlockWeights(
+                        packed.blocks, packed.blockWeights, packed.blockVsizes, packed.blockAdjustedVsizes,
+                        mutableListOf(), mutableListOf(), graph.scorable, weight
+                    )
+                    val blocks = Array(packed.blocks.size) { parentsFirst(packed.blocks[it], inputsByUid) }
+                    GbtBlockProjection(
+                        maxBlockWeight = weight,
+                        blocks = blocks,
+                        blockWeights = packed.blockWeights.toIntArray(),
+                        blockFees = LongArray(blocks.size) { i ->
+                            blocks[i].sumOf { uid -> transactions.getValue(uid).fee + (currentAccelerations[uid]?.delta ?: 0L) }
+                        }
+                    )
+                }.toTypedArray()
+            } catch (e: Exception) {
+                Log.e(TAG, "Run $runId: error in multi-limit packing", e)
+                return emptyArray()
+            }
+        }
+    }
+
+    /** Packs a snapshot of the current state without reading or updating the cached result. */
+    private fun packDetached(
+        maxBlockWeight: Int,
+        maxBlocks: Int,
+        detail: Int,
+        nextBlockOnly: Boolean = false
+    ): GbtResult? {
+        val snapshot = lock.read { MempoolSnapshot(HashMap(threadTransactions), accelerations, changeCounter, 0, 0) }
+        return runFallback(
+            snapshot.transactions.values.toList(), snapshot.accelerations, maxBlockWeight, maxBlocks, runIds.incrementAndGet(),
+            nextBlockOnly = nextBlockOnly,
+            includeClusters = detail and GbtResult.DETAIL_CLUSTERS != 0,
+            includeTxStats = detail and GbtResult.DETAIL_TX_STATS != 0,
+            includeRateSamples = detail and GbtResult.DETAIL_RATE_SAMPLES != 0,
+            includeCpfpEdges = detail and GbtResult.DETAIL_CPFP_EDGES != 0,
+            includeDepthCurve = detail and GbtResult.DETAIL_DEPTH_CURVE != 0
+        )
+            ?.let { if (includeTxids) withTxids(it, snapshot.transactions) else it }
+            ?.copy(changeCounter = snapshot.changeCounter)
+            ?.withDetail(detail)
+    }
+
+    /**
+     * What-if run with [delta] sats added to [uid]'s acceleration. Packs a copy of the current
+     * state, so neither the tracked mempool nor the cached result is touched. Returns null if
+     * [uid] isn't tracked. The copy and the current position come from one snapshot, and like
+     * [simulate] this waits for an in-flight [compute] rather than interleaving with it.
+     */
+    fun previewAcceleration(uid: Int, delta: Long): AccelerationPreview? {
+        synchronized(computeLock) {
+            val (transactions, currentAccelerations, currentBlock) = lock.read {
+                if (uid !in threadTransactions) return null
+                Triple(HashMap(threadTransactions), accelerations, lastPositions[uid] ?: GbtResult.UNKNOWN_UID)
+            }
+            val boosted = HashMap(currentAccelerations)
+            boosted[uid] = ThreadAcceleration(uid, (currentAccelerations[uid]?.delta ?: 0L) + delta)
+            val result = runFallback(
+                transactions.values.toList(), boosted, maxBlockWeight, maxBlocks, runIds.incrementAndGet(),
+                includeClusters = false, includeTxStats = false, includeRateSamples = false, includeCpfpEdges = false,
+                includeDepthCurve = false
+            ) ?: return null
+            return AccelerationPreview(
+                currentBlock = currentBlock,
+                newBlock = positionsOf(result)[uid] ?: GbtResult.UNKNOWN_UID,
+                newRate = ratesOf(result, transactions)[uid] ?: 0.0
+            )
+        }
+    }
+
+    /**
+     * Packs a snapshot of the current state [k] times, each with every transaction's fee and
+     * rate scaled by an independent factor within ±[ENSEMBLE_RATE_NOISE], to model miners
+     * whose selection differs slightly from ours. Transactions far from a block cutoff land in
+     * the same place every time; those near one spread over the neighbouring outcomes. The
+     * snapshot, its exclusions and its ancestor sets are built once; each run only rescores and
+     * repeats the packing loop. Results are deterministic for a given [setSeed] seed and state;
+     * neither the tracked mempool nor the cached result is touched.
+     */
+    fun computeEnsemble(k: Int): EnsembleProbabilities {
+        require(k > 0) { "k must be positive, got $k" }
+        val (transactions, currentAccelerations) = lock.read { HashMap(threadTransactions) to accelerations }
+        val mempool = transactions.values.sortedBy { it.uid }
+        val index = HashMap<Int, Int>(mempool.size)
+        mempool.forEachIndexed { i, tx -> index[tx.uid] = i }
+        val inBlockZero = IntArray(mempool.size)
+        val inFirstBlocks = IntArray(mempool.size)
+        val inOverflow = IntArray(mempool.size)
+        val runSeed = seed
+        val random = Random(runSeed)
+        var runs = 0
+        val runId = runIds.incrementAndGet()
+        val graph = try {
+            buildGraph(mempool, currentAccelerations, runId)
+        } catch (e: Exception) {
+            Log.e(TAG, "Run $runId: error preparing ensemble", e)
+            null
+        }
+        if (graph != null) repeat(k) {
+            // graph.scorable is in uid order, so each transaction draws the same noise for a given seed.
+            val perturbed = graph.scorable.map { tx ->
+                val factor = 1 + (random.nextDouble() * 2 - 1) * ENSEMBLE_RATE_NOISE
+                tx.copy(fee = (tx.fee * factor).roundToLong(), effectiveFeePerVsize = tx.effectiveFeePerVsize * factor)
+            }
+            val packed = try {
+                pack(
+                    graph, newStates(graph, perturbed), maxBlockWeight, maxBlocks, runId,
+                    nextBlockOnly = false, includeRateSamples = false, includeCpfpEdges = false
+                )
+            } catch (e: Exception) {
+                Log.e(TAG, "Run $runId: error in ensemble run ${it + 1} of $k", e)
+                return@repeat
+            }
+            runs++
+            val placed = BooleanArray(mempool.size)
+            packed.blocks.forEachIndexed { block, uids ->
+                for (uid in uids) {
+                    val i = index.getValue(uid)
+                    placed[i] = true
+                    if (block < EnsembleProbabilities.FIRST_BLOCKS) {
+                        inFirstBlocks[i]++
+                        if (block == 0) inBlockZero[i]++
+                    }
+                }
+            }
+            for (i in placed.indices) if (!placed[i]) inOverflow[i]++
+        }
+        fun fractions(counts: IntArray) = DoubleArray(counts.size) { if (runs > 0) counts[it].toDouble() / runs else 0.0 }
+        return EnsembleProbabilities(
+            uids = IntArray(mempool.size) { mempool[it].uid },
+            blockZero = fractions(inBlockZero),
+            firstBlocks = fractions(inFirstBlocks),
+            overflow = fractions(inOverflow),
+            runs = runs,
+            seed = runSeed
+        )
+    }
+
+    /**
+     * Compares [minedUids] against block 0 of the last run. Call before computing on the
+     * post-block mempool; transactions already removed are looked up among the recently
+     * removed ones. [blockTime] is in epoch seconds.
+     */
+    fun auditBlock(
+        minedUids: IntArray,
+        blockTime: Long = System.currentTimeMillis() / 1000,
+        freshWindowSeconds: Long = DEFAULT_FRESH_WINDOW_SECONDS
+    ): BlockAudit = lock.write {
+        fun lookup(uid: Int) = threadTransactions[uid] ?: recentlyRemoved[uid]
+        val projected = cachedResult?.blocks?.firstOrNull() ?: intArrayOf()
+        val projectedSet = projected.toHashSet()
+        val minedSet = minedUids.toHashSet()
+
+        val accelerated = minedUids.filter { it in accelerations }
+        val prioritized = minedUids.filter { uid ->
+            if (uid in projectedSet || uid in accelerations) return@filter false
+            val rate = lastRates[uid] ?: lookup(uid)?.effectiveFeePerVsize ?: return@filter false
+            rate < lastBlockZeroCutoff * PRIORITIZED_RATE_FACTOR
+        }.toHashSet()
+        val added = minedUids.filter { it !in projectedSet && it !in accelerations && it !in prioritized }
+        val unknown = minedUids.filter { lookup(it) == null }
+        val fresh = added.filter { uid ->
+            val firstSeen = lookup(uid)?.firstSeen ?: return@filter false
+            blockTime - firstSeen < freshWindowSeconds
+        }
+        val known = minedUids.mapNotNull { lookup(it) }
+        val actualWeight = known.sumOf { it.weight.toLong() }
+        val matchedWeight = known.filter { it.uid in projectedSet }.sumOf { it.weight.toLong() }
+        val template = projected.mapNotNull { lookup(it) }
+
+        BlockAudit(
+            matchRate = if (actualWeight > 0) matchedWeight * 100.0 / actualWeight else 0.0,
+            missingUids = projected.filter { it !in minedSet }.toIntArray(),
+            addedUids = added.toIntArray(),
+            freshUids = fresh.toIntArray(),
+            unknownUids = unknown.toIntArray(),
+            prioritizedUids = minedUids.filter { it in prioritized }.toIntArray(),
+            acceleratedUids = accelerated.toIntArray(),
+            expectedFees = template.sumOf { it.fee },
+            expectedWeight = template.sumOf { it.weight.toLong() },
+            actualKnownFees = known.sumOf { it.fee },
+            actualKnownWeight = actualWeight,
+            unknownTxCount = unknown.size
+        ).also {
+            lastAudit = it
+            lastAuditStatuses = it.statuses(minedUids)
+        }
+    }
+
+    /** Classification of [uid] in the most recent [auditBlock], one of the BlockAudit.STATUS_* constants. */
+    fun getAuditStatus(uid: Int): Int = lock.read {
+        lastAuditStatuses[uid] ?: BlockAudit.STATUS_NOT_AUDITED
+    }
+
+    /**
+     * Block 0 of the last run in the shape of Bitcoin Core's getblocktemplate: a "transactions"
+     * array of {txid, depends, fee, sigops, weight} in block order, where depends holds the
+     * 1-based positions of in-block parents, plus total "fees", "sigops" and "weight". Fees are
+     * the transactions' own fees, without accelerations. Raw transaction data isn't tracked,
+     * so "data" and "hash" are omitted. Returns null before the first run.
+     *
+     * Transactions are described as the run saw them, so the template stays complete after
+     * they're removed or the removed cache is dropped.
+     *
+     * @throws IllegalStateException if a block 0 transaction has no txid.
+     */
+    fun getBlockTemplate(): String? = lock.read {
+        val block = cachedResult?.blocks?.firstOrNull() ?: return@read null
+        val txs = lastBlockZero
+        val missingTxids = txs.filter { it.txid == null }.map { it.uid }
+        if (missingTxids.isNotEmpty()) {
+            throw IllegalStateException("Block 0 transactions without txid: ${missingTxids.take(10)}")
+        }
+        val positions = HashMap<Int, Int>(block.size)
+        block.forEachIndexed { index, uid -> positions[uid] = index + 1 }
+        val transactions = JSONArray()
+        for (tx in txs) {
+            transactions.put(
+                JSONObject()
+                    .put("txid", tx.txid)
+                    .put("depends", JSONArray(tx.inputs.mapNotNull { positions[it] }.sorted()))
+                    .put("fee", tx.fee)
+                    .put("sigops", tx.sigops)
+                    .put("weight", tx.weight)
+            )
+        }
+        JSONObject()
+            .put("transactions", transactions)
+            .put("fees", txs.sumOf { it.fee })
+            .put("sigops", txs.sumOf { it.sigops })
+            .put("weight", txs.sumOf { it.weight })
+            .toString()
+    }
+
+    /** The most recent [auditBlock] result, or null if no block has been audited since the last reset. */
+    fun getLastAuditSummary(): BlockAudit? = lock.read { lastAudit }
+
+    private fun withTxids(result: GbtResult, transactions: Map<Int, ThreadTransaction>): GbtResult {
+        fun txidOf(uid: Int) = transactions[uid]?.txid ?: ""
+        return result.copy(
+            blockTxids = Array(result.blocks.size) { i -> Array(result.blocks[i].size) { j -> txidOf(result.blocks[i][j]) } },
+            overflowTxids = Array(result.overflow.size) { i -> txidOf(result.overflow[i]) }
+        )
+    }
+
+    private fun ratesOf(result: GbtResult, transactions: Map<Int, ThreadTransaction>): HashMap<Int, Double> {
+        val rates = HashMap<Int, Double>(transactions.size)
+        for ((uid, tx) in transactions) rates[uid] = tx.effectiveFeePerVsize
+        for (rate in result.rates) rates[rate[0].toInt()] = rate[2]
+        return rates
+    }
+
+    private fun positionsOf(result: GbtResult): HashMap<Int, Int> {
+        val positions = HashMap<Int, Int>(result.overflow.size + result.blocks.sumOf { it.size })
+        result.blocks.forEachIndexed { index, block -> for (uid in block) positions[uid] = index }
+        for (uid in result.overflow) positions[uid] = GbtResult.OVERFLOW_BLOCK
+        return positions
+    }
+
+    private fun watchedChanges(
+        previousPositions: Map<Int, Int>,
+        positions: Map<Int, Int>,
+        previousRates: Map<Int, Double>,
+        rates: Map<Int, Double>
+    ): GbtWatchedChanges {
+        val changed = watched.sorted().filter { uid ->
+            val oldBlock = previousPositions[uid]
+            val newBlock = positions[uid]
+            when {
+                newBlock != null -> {
+                    reportedUnknownWatched.remove(uid)
+                    oldBlock != newBlock || previousRates[uid] != rates[uid]
+                }
+                // Left the mempool: report the transition, then stay quiet like a never-seen uid.
+                oldBlock != null -> {
+                    reportedUnknownWatched.add(uid)
+                    true
+                }
+                else -> reportedUnknownWatched.add(uid)
+            }
+        }
+        return GbtWatchedChanges(
+            uids = changed.toIntArray(),
+            oldBlocks = IntArray(changed.size) { previousPositions[changed[it]] ?: GbtResult.UNKNOWN_UID },
+            newBlocks = IntArray(changed.size) { positions[changed[it]] ?: GbtResult.UNKNOWN_UID },
+            oldRates = DoubleArray(changed.size) { previousRates[changed[it]] ?: 0.0 },
+            newRates = DoubleArray(changed.size) { rates[changed[it]] ?: 0.0 }
+        )
+    }
+
+    /** Called under the write lock; drops own uids that are reported as gone. */
+    private fun ownPositions(
+        positions: Map<Int, Int>,
+        rates: Map<Int, Double>,
+        transactions: Map<Int, ThreadTransaction>
+    ): GbtOwnPositions {
+        val uids = own.sorted()
+        val blocks = IntArray(uids.size) { positions[uids[it]] ?: GbtResult.UNKNOWN_UID }
+        own.removeAll(uids.filterIndexed { i, _ -> blocks[i] == GbtResult.UNKNOWN_UID }.toSet())
+        return GbtOwnPositions(
+            uids = uids.toIntArray(),
+            blocks = blocks,
+            rates = DoubleArray(uids.size) { rates[uids[it]] ?: 0.0 },
+            nextBlockCount = blocks.count { it == 0 },
+            pendingFees = uids.sumOf { transactions[it]?.fee ?: 0L }
+        )
+    }
+
+    private fun diffPositions(previous: Map<Int, Int>, current: Map<Int, Int>): GbtResultDiff {
+        val added = mutableListOf<Int>()
+        val moved = mutableListOf<Int>()
+        val movedFrom = mutableListOf<Int>()
+        val movedTo = mutableListOf<Int>()
+        for ((uid, block) in current) {
+            val previousBlock = previous[uid]
+            if (previousBlock == null) {
+                added.add(uid)
+            } else if (previousBlock != block) {
+                moved.add(uid)
+                movedFrom.add(previousBlock)
+                movedTo.add(block)
+            }
+        }
+        val removed = previous.keys.filter { it !in current }
+        return GbtResultDiff(
+            addedUids = added.toIntArray(),
+            removedUids = removed.toIntArray(),
+            movedUids = moved.toIntArray(),
+            movedFromBlock = movedFrom.toIntArray(),
+            movedToBlock = movedTo.toIntArray()
+        )
     }
 
     private fun runFallback(
         mempool: List<ThreadTransaction>,
-        accelerations: List<ThreadAcceleration>
+        accelerationMap: Map<Int, ThreadAcceleration>,
+        maxBlockWeight: Int,
+        maxBlocks: Int,
+        runId: Long,
+        nextBlockOnly: Boolean = false,
+        includeClusters: Boolean = true,
+        includeTxStats: Boolean = true,
+        includeRateSamples: Boolean = true,
+        includeCpfpEdges: Boolean = true,
+        includeDepthCurve: Boolean = true
     ): GbtResult? {
-        if (mempool.isEmpty()) return null
         try {
-            val accelerationMap = accelerations.associateBy { it.uid }
-            val adjustedMempool = mempool.map { tx ->
-                val acceleration = accelerationMap[tx.uid]
-                if (acceleration != null) {
-                    val newFee = tx.fee + acceleration.delta
-                    val newEffective = newFee / (tx.weight / 4.0)
-                    tx.copy(fee = newFee, effectiveFeePerVsize = newEffective)
-                } else tx
-            }
-
-            val sortedTxs = adjustedMempool.sortedByDescending { it.effectiveFeePerVsize }
-            val blocks = mutableListOf<IntArray>()
-            val blockWeights = mutableListOf<Int>()
+            val graph = buildGraph(mempool, accelerationMap, runId)
+            val adjustedMempool = graph.adjustedMempool
+            val packing = pack(
+                graph, newStates(graph), maxBlockWeight, maxBlocks, runId, nextBlockOnly, includeRateSamples, includeCpfpEdges
+            )
+            val blocks = packing.blocks
+            val blockWeights = packing.blockWeights
+            val packedRates = packing.packedRates
             val overflow = mutableListOf<Int>()
-            var currentBlock = mutableListOf<Int>()
-            var currentWeight = 0
-            var blockCount = 0
-
-            for (tx in sortedTxs) {
-                if (blockCount >= maxBlocks) { overflow.add(tx.uid); continue }
-                if (currentWeight + tx.weight <= maxBlockWeight) {
-                    currentBlock.add(tx.uid)
-                    currentWeight += tx.weight
-                } else {
-                    if (blockCount < maxBlocks - 1) {
-                        blocks.add(currentBlock.toIntArray())
-                        blockWeights.add(currentWeight)
-                        blockCount++
-                        currentBlock = mutableListOf(tx.uid)
-                        currentWeight = tx.weight
-                    } else {
-                        overflow.add(tx.uid)
-                    }
+            val overflowReasons = mutableListOf<Int>()
+
+            val notEvaluated = nextBlockOnly && maxBlocks != 1
+            val leftOver = packing.states.values.filter { !it.used }
+            traced("gbt.overflow", "run" to runId, "txs" to leftOver.size) {
+                for (tx in leftOver.map { it.tx }.sortedWith(byRate)) {
+                    overflow.add(tx.uid)
+                    overflowReasons.add(if (notEvaluated) GbtResult.OVERFLOW_NOT_EVALUATED else GbtResult.OVERFLOW_NO_SPACE)
                 }
             }
-            if (currentBlock.isNotEmpty()) {
-                blocks.add(currentBlock.toIntArray())
-                blockWeights.add(currentWeight)
+            // Purge pressure: what it takes to make the projection at all. TRUC-rejected and
+            // unscorable transactions don't count, as no rate would get them in.
+            val overflowTopRate = leftOver.maxOfOrNull { it.score } ?: 0.0
+            val overflowVsize = leftOver.sumOf { weightToVsize(it.tx.weight).toLong() }
+            val overflowFees = leftOver.sumOf { it.tx.fee }
+            enforceBlockWeights(
+                blocks, blockWeights, packing.blockVsizes, packing.blockAdjustedVsizes, overflow, overflowReasons,
+                graph.scorable, maxBlockWeight
+            )
+            val inputsByUid = graph.scorable.associate { it.uid to it.inputs }
+            for (i in blocks.indices) blocks[i] = parentsFirst(blocks[i], inputsByUid)
+            for ((uid, reason) in graph.trucExcluded.entries.sortedBy { it.key }) {
+                overflow.add(uid)
+                overflowReasons.add(reason)
+            }
+            for (uid in graph.nonStandard.sorted()) {
+                overflow.add(uid)
+                overflowReasons.add(GbtResult.OVERFLOW_NON_STANDARD)
             }
+            graph.unscorable.forEach {
+                overflow.add(it.uid)
+                overflowReasons.add(GbtResult.OVERFLOW_UNSCORABLE)
+            }
+            val clusters = if (includeClusters) traced("gbt.clusters", "run" to runId) { buildClusters(mempool) } else emptyList()
+            val txStats = if (includeTxStats) traced("gbt.txStats", "run" to runId) { buildTxStats(adjustedMempool) } else null
+            val overflowTotals = overflowTotalsByReason(overflow, overflowReasons, adjustedMempool)
+            val adjustedByUid = adjustedMempool.associateBy { it.uid }
+            val clusterTxs = clusters.map { members -> members.map { adjustedByUid.getValue(it) } }
+            val clusterFees = LongArray(clusters.size) { i -> clusterTxs[i].sumOf { it.fee } }
+            val clusterVsizes = IntArray(clusters.size) { i -> clusterTxs[i].sumOf { weightToVsize(it.weight) } }
+            val (depthCurveRates, depthCurveVsizes) = if (includeDepthCurve) {
+                depthCurve(adjustedMempool, packedRates, overflow.toHashSet())
+            } else doubleArrayOf() to longArrayOf()
 
             return GbtResult(
                 blocks = blocks.toTypedArray(),
                 blockWeights = blockWeights.toIntArray(),
-                clusters = emptyArray(),
-                rates = emptyArray(),
-                overflow = overflow.toIntArray()
+                blockTxCounts = IntArray(blocks.size) { blocks[it].size },
+                blockVsizes = packing.blockVsizes.toIntArray(),
+                blockAdjustedVsizes = packing.blockAdjustedVsizes.toIntArray(),
+                blockRateSamples = packing.blockRateSamples.toTypedArray(),
+                cpfpEdges = packing.cpfpEdges.toTypedArray(),
+                clusters = clusters.toTypedArray(),
+                clusterUids = clusters.flatMap { it.asList() }.toIntArray(),
+                clusterIds = clusters.flatMapIndexed { id, members -> List(members.size) { id } }.toIntArray(),
+                clusterFees = clusterFees,
+                clusterVsizes = clusterVsizes,
+                clusterRates = DoubleArray(clusters.size) { clusterFees[it].toDouble() / clusterVsizes[it] },
+                clusterAccelerated = BooleanArray(clusters.size) { i -> clusters[i].any { it in accelerationMap } },
+                rates = ratesSection(mempool, adjustedMempool, graph.baseRates, packedRates),
+                overflow = overflow.toIntArray(),
+                overflowReasons = overflowReasons.toIntArray(),
+                overflowReasonCodes = overflowTotals.keys.toIntArray(),
+                overflowReasonCounts = overflowTotals.values.map { it.count }.toIntArray(),
+                overflowReasonWeights = overflowTotals.values.map { it.weight }.toLongArray(),
+                overflowReasonFees = overflowTotals.values.map { it.fees }.toLongArray(),
+                txStats = txStats,
+                nextBlockCutoffRate = packing.nextBlockCutoffRate,
+                overflowTopRate = overflowTopRate,
+                overflowVsize = overflowVsize,
+                overflowFees = overflowFees,
+                depthCurveRates = depthCurveRates,
+                depthCurveVsizes = depthCurveVsizes,
+                templateHash = templateHash(blocks, blockWeights),
+                maxUid = mempool.maxOfOrNull { it.uid } ?: 0,
+                effectiveMaxBlocks = maxBlocks,
+                strategy = strategy,
+                seed = seed,
+                runId = runId
             )
         } catch (e: Exception) {
-            android.util.Log.e("GbtGenerator", "Error in fallback", e)
+            Log.e(TAG, "Run $runId: error in fallback", e)
             return null
         }
     }
 
-    fun destroy() { /* no-op */ }
+    /**
+     * Everything about a snapshot that doesn't depend on the block limits: acceleration and
+     * sigop adjusted rates, the exclusions, and each scorable transaction's in-run ancestors and
+     * spenders. Several runs over one snapshot build this once and only repeat [pack].
+     */
+    private fun buildGraph(
+        mempool: List<ThreadTransaction>,
+        accelerationMap: Map<Int, ThreadAcceleration>,
+        runId: Long
+    ): PackingGraph {
+        val baseRates = HashMap<Int, Double>()
+        val adjustedMempool = mempool.sortedBy { it.uid }.map { tx ->
+            // Sigop-heavy transactions are scored per adjusted vsize, as miners see them.
+            val sigopScale = weightToVsize(tx.weight).toDouble() / weightToVsize(packWeight(tx))
+            val acceleration = accelerationMap[tx.uid]
+            val adjusted = if (acceleration != null) {
+                val newFee = tx.fee + acceleration.delta
+                val newEffective = newFee.toDouble() / weightToVsize(tx.weight) * sigopScale
+                tx.copy(fee = newFee, effectiveFeePerVsize = newEffective)
+            } else if (sigopScale < 1.0) {
+                tx.copy(effectiveFeePerVsize = tx.effectiveFeePerVsize * sigopScale)
+            } else tx
+            if (adjusted !== tx) baseRates[tx.uid] = tx.effectiveFeePerVsize * sigopScale
+            adjusted
+        }
+
+        // A NaN score would sort ahead of everything (Double.compareTo is a total order with
+        // NaN greatest), so unscorable transactions go straight to overflow instead.
+        val (scoredTxs, unscorable) = adjustedMempool.partition { !it.effectiveFeePerVsize.isNaN() }
+        unscorable.forEach { Log.w(TAG, "Run $runId: NaN fee rate for transaction ${it.uid}, moving to overflow") }
+        val nonStandard = policyExclusions(scoredTxs)
+        val standardTxs = if (nonStandard.isEmpty()) scoredTxs else scoredTxs.filter { it.uid !in nonStandard }
+        val trucExcluded = trucExclusions(standardTxs, byRate)
+        val scorable = standardTxs.filter { it.uid !in trucExcluded }
+
+        val ancestors = HashMap<Int, Set<Int>>(scorable.size)
+        val spenders = HashMap<Int, MutableList<Int>>()
+        traced("gbt.ancestors", "run" to runId, "txs" to scorable.size) {
+            val byUid = scorable.associateBy { it.uid }
+            for (tx in scorable) {
+                ancestors[tx.uid] = ancestorsOf(tx.uid, byUid)
+                for (input in tx.inputs) if (input in byUid) spenders.getOrPut(input) { mutableListOf() }.add(tx.uid)
+            }
+        }
+        return PackingGraph(adjustedMempool, baseRates, unscorable, nonStandard, trucExcluded, scorable, ancestors, spenders)
+    }
+
+    /**
+     * Fresh packing state for one run over [graph], scored from [txs]: the graph's scorable
+     * transactions, or copies of them with other fees and rates but the same uids and inputs.
+     */
+    private fun newStates(graph: PackingGraph, txs: List<ThreadTransaction> = graph.scorable): HashMap<Int, PackingTx> {
+        val states = HashMap<Int, PackingTx>(txs.size)
+        for (tx in txs) states[tx.uid] = PackingTx(tx, packWeight(tx), HashSet(graph.ancestors.getValue(tx.uid)), strategy)
+        for (state in states.values) {
+            for (ancestor in state.ancestors) {
+                val a = states.getValue(ancestor)
+                state.ancestorFee += a.tx.fee
+                state.ancestorPackWeight += a.packWeight
+                state.ancestorSigops += a.tx.sigops
+            }
+            state.rescore()
+        }
+        return states
+    }
+
+    /**
+     * Fills blocks from [states], which the run consumes. Ancestor-set scoring as in Core's
+     * BlockAssembler: a transaction is selected together with its not yet selected ancestors at
+     * their combined rate, and once an ancestor is selected through another package, every
+     * descendant is re-scored without it. Block 0 exists even when nothing could be packed.
+     *
+     * A block stops polling as soon as the lightest remaining package can't fit its free room,
+     * so small blocks in unlimited mode don't each walk the whole heap. Candidates it missed
+     * carry over to the next block once each.
+     */
+    private fun pack(
+        graph: PackingGraph,
+        states: Map<Int, PackingTx>,
+        maxBlockWeight: Int,
+        maxBlocks: Int,
+        runId: Long,
+        nextBlockOnly: Boolean,
+        includeRateSamples: Boolean,
+        includeCpfpEdges: Boolean
+    ): PackedBlocks {
+        val packed = PackedBlocks(states)
+        val blocks = packed.blocks
+        // A heap rather than a sort: a next-block run only looks at the top of it.
+        val heap = PriorityQueue(states.values.map { Candidate(it) })
+        val lightest = PriorityQueue(states.values.map { SizedCandidate(it) })
+        // Missed by the current block and retried by the next. A rescored state leaves it for the heap.
+        val deferred = LinkedHashSet<PackingTx>()
+
+        fun lightestRemaining(): Long {
+            while (true) {
+                val top = lightest.peek() ?: return Long.MAX_VALUE
+                if (!top.state.used && top.packageWeight == top.state.packageWeight) return top.packageWeight
+                lightest.poll()
+            }
+        }
+
+        while ((maxBlocks == 0 || blocks.size < maxBlocks) && (heap.isNotEmpty() || deferred.isNotEmpty())) {
+            deferred.forEach { if (!it.used) heap.add(Candidate(it)) }
+            deferred.clear()
+            val block = mutableListOf<Int>()
+            var weight = 0
+            var vsize = 0
+            // Block space is accounted in sigop-adjusted weight; both it and the sigops cap must hold.
+            var packWeight = 0L
+            var sigops = 0
+            var failures = 0
+            val sampler = if (includeRateSamples) RateSampler(BLOCK_RATE_SAMPLES) else null
+            // (child, ancestor) for every ancestor pulled in by a package; filtered by the cutoff below.
+            val packageLinks = if (includeCpfpEdges) mutableListOf<Pair<Int, Int>>() else null
+            var blockCutoff = 0.0
+            traced("gbt.block", "run" to runId, "index" to blocks.size) {
+                while (true) {
+                    val candidate = heap.poll() ?: break
+                    val state = candidate.state
+                    if (state.used || candidate.score != state.score) continue
+                    if (packWeight + state.packageWeight > maxBlockWeight ||
+                        sigops + state.ancestorSigops + state.tx.sigops > MAX_BLOCK_SIGOPS
+                    ) {
+                        // Retried for the next block. Like Core, give up on a nearly full block
+                        // after many consecutive misses instead of trying every remaining package.
+                        deferred.add(state)
+                        if (maxBlockWeight - packWeight < lightestRemaining()) break
+                        if (++failures > MAX_CONSECUTIVE_FAILURES && packWeight > maxBlockWeight - BLOCK_FULL_MARGIN) break
+                        continue
+                    }
+                    failures = 0
+                    val rate = state.score
+                    sampler?.add(rate)
+                    blockCutoff = rate
+                    packageLinks?.let { links -> state.ancestors.forEach { links.add(state.tx.uid to it) } }
+                    val selected = state.ancestors.sorted() + state.tx.uid
+                    for (uid in selected) {
+                        val member = states.getValue(uid)
+                        member.used = true
+                        block.add(uid)
+                        weight += member.tx.weight
+                        vsize += weightToVsize(member.tx.weight)
+                        packWeight += member.packWeight
+                        sigops += member.tx.sigops
+                        packed.packedRates[uid] = rate
+                    }
+                    if (blocks.isEmpty()) packed.nextBlockCutoffRate = rate
+                    for (uid in selected) {
+                        val member = states.getValue(uid)
+                        for (descendant in descendantsOf(uid, graph.spenders)) {
+                            val d = states.getValue(descendant)
+                            if (d.used || !d.ancestors.remove(uid)) continue
+                            d.ancestorFee -= member.tx.fee
+                            d.ancestorPackWeight -= member.packWeight
+                            d.ancestorSigops -= member.tx.sigops
+                            d.rescore()
+                            deferred.remove(d)
+                            heap.add(Candidate(d))
+                            lightest.add(SizedCandidate(d))
+                        }
+                    }
+                }
+            }
+            // Nothing fits even into an empty block; the rest goes to overflow.
+            if (block.isEmpty()) break
+            blocks.add(block.toIntArray())
+            packed.blockWeights.add(weight)
+            packed.blockVsizes.add(vsize)
+            packed.blockAdjustedVsizes.add(weightToVsize(packWeight).toInt())
+            if (sampler != null) packed.blockRateSamples.add(sampler.toArray())
+            if (packageLinks != null) {
+                val edges = packageLinks
+                    .filter { (_, parent) -> states.getValue(parent).tx.effectiveFeePerVsize < blockCutoff }
+                    .distinctBy { it.second }
+                    .sortedWith(compareBy({ it.first }, { it.second }))
+                packed.cpfpEdges.add(edges.flatMap { listOf(it.first, it.second) }.toIntArray())
+            }
+            if (nextBlockOnly) break
+        }
+        // Block 0 exists even when nothing could be packed, so callers never index an empty list.
+        if (blocks.isEmpty()) {
+            blocks.add(intArrayOf())
+            packed.blockWeights.add(0)
+            packed.blockVsizes.add(0)
+            packed.blockAdjustedVsizes.add(0)
+            if (includeRateSamples) packed.blockRateSamples.add(doubleArrayOf())
+            if (includeCpfpEdges) packed.cpfpEdges.add(intArrayOf())
+        }
+        return packed
+    }
+
+    /**
+     * Transactions carrying policy flags outside [acceptedPolicyMask], plus everything spending
+     * them, since a miner that won't take a parent can't take its children either.
+     */
+    private fun policyExclusions(mempool: List<ThreadTransaction>): Set<Int> {
+        val rejected = acceptedPolicyMask.inv()
+        val flagged = mempool.filter { it.policyFlags and rejected != 0 }
+        if (flagged.isEmpty()) return emptySet()
+        val spenders = HashMap<Int, MutableList<Int>>()
+        for (tx in mempool) for (input in tx.inputs) spenders.getOrPut(input) { mutableListOf() }.add(tx.uid)
+        val excluded = HashSet<Int>()
+        val queue = ArrayDeque(flagged.map { it.uid })
+        while (queue.isNotEmpty()) {
+            val uid = queue.removeFirst()
+            if (excluded.add(uid)) spenders[uid]?.let { queue.addAll(it) }
+        }
+        return excluded
+    }
+
+    /**
+     * Applies the TRUC (BIP 431) topology rules to flagged transactions and returns the ones a
+     * compliant miner wouldn't include, with their GbtResult.OVERFLOW_TRUC_* reason. A TRUC
+     * transaction may have at most one in-mempool parent and stay within the size limits, and
+     * a TRUC parent keeps only its best child by [byRate] (sibling eviction). Everything
+     * spending an excluded transaction is excluded too, as it can't be mined without it.
+     */
+    private fun trucExclusions(
+        mempool: List<ThreadTransaction>,
+        byRate: Comparator<ThreadTransaction>
+    ): Map<Int, Int> {
+        val byUid = mempool.associateBy { it.uid }
+        val excluded = HashMap<Int, Int>()
+        val childrenOfTruc = HashMap<Int, MutableList<ThreadTransaction>>()
+        for (tx in mempool) {
+            if (!tx.isTruc) continue
+            val parents = tx.inputs.filter { it in byUid }
+            val vsize = weightToVsize(tx.weight)
+            when {
+                parents.size > 1 -> excluded[tx.uid] = GbtResult.OVERFLOW_TRUC_INVALID
+                vsize > MAX_TRUC_VSIZE || (parents.isNotEmpty() && vsize > MAX_TRUC_CHILD_VSIZE) ->
+                    excluded[tx.uid] = GbtResult.OVERFLOW_TRUC_OVERSIZED
+                parents.isNotEmpty() && byUid.getValue(parents[0]).isTruc ->
+                    childrenOfTruc.getOrPut(parents[0]) { mutableListOf() }.add(tx)
+            }
+        }
+        for (siblings in childrenOfTruc.values) {
+            if (siblings.size < 2) continue
+            siblings.sortedWith(byRate).drop(1).forEach { excluded[it.uid] = GbtResult.OVERFLOW_TRUC_SIBLING_EVICTED }
+        }
+        if (excluded.isEmpty()) return excluded
+        val spenders = HashMap<Int, MutableList<Int>>()
+        for (tx in mempool) for (input in tx.inputs) spenders.getOrPut(input) { mutableListOf() }.add(tx.uid)
+        val queue = ArrayDeque(excluded.keys.flatMap { spenders[it].orEmpty() })
+        while (queue.isNotEmpty()) {
+            val uid = queue.removeFirst()
+            if (uid in excluded) continue
+            excluded[uid] = GbtResult.OVERFLOW_TRUC_ANCESTOR_EXCLUDED
+            spenders[uid]?.let { queue.addAll(it) }
+        }
+        return excluded
+    }
+
+    /**
+     * Reorders [block] so every transaction follows its parents in the same block, as
+     * getblocktemplate consumers require. Otherwise the packing order is kept: a transaction
+     * only moves earlier when one of its descendants pulls it forward.
+     */
+    private fun parentsFirst(block: IntArray, inputsByUid: Map<Int, IntArray>): IntArray {
+        val inBlock = block.toHashSet()
+        val emitted = LinkedHashSet<Int>(block.size)
+        val visiting = HashSet<Int>()
+        val stack = ArrayDeque<Int>()
+        for (uid in block) {
+            if (uid in emitted) continue
+            stack.addLast(uid)
+            visiting.add(uid)
+            while (stack.isNotEmpty()) {
+                val top = stack.last()
+                val parent = inputsByUid[top]?.firstOrNull { it in inBlock && it !in emitted && it !in visiting }
+                if (parent != null) {
+                    stack.addLast(parent)
+                    visiting.add(parent)
+                } else {
+                    stack.removeLast()
+                    visiting.remove(top)
+                    emitted.add(top)
+                }
+            }
+        }
+        return emitted.toIntArray()
+    }
+
+    /**
+     * Checks that every block's recorded weight is the sum of its transactions' weights and
+     * within [maxBlockWeight]. Packing guarantees both, so a failure is a bug: debug builds
+     * throw, release builds log, recompute the weights and move trailing transactions of an
+     * overweight block to overflow.
+     */
+    private fun enforceBlockWeights(
+        blocks: MutableList<IntArray>,
+        blockWeights: MutableList<Int>,
+        blockVsizes: MutableList<Int>,
+        blockAdjustedVsizes: MutableList<Int>,
+        overflow: MutableList<Int>,
+        overflowReasons: MutableList<Int>,
+        packed: List<ThreadTransaction>,
+        maxBlockWeight: Int
+    ) {
+        val weights = packed.associate { it.uid to it.weight }
+        val packWeights = packed.associate { it.uid to packWeight(it) }
+        for (i in blocks.indices) {
+            val actual = blocks[i].sumOf { weights.getValue(it) }
+            if (actual == blockWeights[i] && actual <= maxBlockWeight) continue
+            val message = "Block $i recorded weight ${blockWeights[i]}, actual $actual, limit $maxBlockWeight"
+            if (BuildConfig.DEBUG) throw GbtStateCorruptException(message)
+            Log.e(TAG, "$message, correcting")
+            val kept = blocks[i].toMutableList()
+            var weight = actual
+            while (weight > maxBlockWeight && kept.isNotEmpty()) {
+                val dropped = kept.removeAt(kept.lastIndex)
+                weight -= weights.getValue(dropped)
+                overflow.add(dropped)
+                overflowReasons.add(GbtResult.OVERFLOW_NO_SPACE)
+            }
+            blocks[i] = kept.toIntArray()
+            blockWeights[i] = weight
+            blockVsizes[i] = kept.sumOf { weightToVsize(weights.getValue(it)) }
+            blockAdjustedVsizes[i] = weightToVsize(kept.sumOf { packWeights.getValue(it) })
+        }
+    }
+
+    /**
+     * [GbtResult.rates] rows for transactions packed at a rate other than the caller-provided
+     * one, whether through acceleration, sigop pricing or their ancestor package.
+     */
+    private fun ratesSection(
+        mempool: List<ThreadTransaction>,
+        adjustedMempool: List<ThreadTransaction>,
+        baseRates: Map<Int, Double>,
+        packedRates: Map<Int, Double>
+    ): Array<DoubleArray> {
+        val providedRates = mempool.associate { it.uid to it.effectiveFeePerVsize }
+        return adjustedMempool.mapNotNull { tx ->
+            val provided = providedRates.getValue(tx.uid)
+            val final = packedRates[tx.uid] ?: tx.effectiveFeePerVsize
+            if (final == provided) null
+            else doubleArrayOf(tx.uid.toDouble(), baseRates[tx.uid] ?: provided, final)
+        }.toTypedArray()
+    }
+
+    /** Count, weight and (adjusted) fees of the overflow per reason, keyed by ascending reason code. */
+    private fun overflowTotalsByReason(
+        overflow: List<Int>,
+        reasons: List<Int>,
+        adjustedMempool: List<ThreadTransaction>
+    ): Map<Int, OverflowTotals> {
+        val byUid = adjustedMempool.associateBy { it.uid }
+        val totals = sortedMapOf<Int, OverflowTotals>()
+        overflow.forEachIndexed { i, uid ->
+            val tx = byUid.getValue(uid)
+            val bucket = totals.getOrPut(reasons[i]) { OverflowTotals() }
+            bucket.count++
+            bucket.weight += tx.weight
+            bucket.fees += tx.fee
+        }
+        return totals
+    }
+
+    /**
+     * Cumulative vsize at or above [DEPTH_CURVE_POINTS] rates spaced logarithmically from the
+     * highest rate down to the lowest positive one. Like [getDepthAtRate], only packed
+     * transactions count, at the rate they were packed at; overflow is left out.
+     */
+    private fun depthCurve(
+        adjustedMempool: List<ThreadTransaction>,
+        packedRates: Map<Int, Double>,
+        overflow: Set<Int>
+    ): Pair<DoubleArray, LongArray> {
+        val points = adjustedMempool.mapNotNull { tx ->
+            if (tx.uid in overflow) return@mapNotNull null
+            val rate = packedRates[tx.uid] ?: return@mapNotNull null
+            rate to weightToVsize(tx.weight).toLong()
+        }.sortedByDescending { it.first }
+        if (points.isEmpty()) return doubleArrayOf() to longArrayOf()
+        val top = points.first().first
+        val floor = points.lastOrNull { it.first > 0 }?.first
+        val rates = mutableListOf<Double>()
+        if (floor == null || floor == top) {
+            rates.add(top)
+        } else {
+            for (i in 0 until DEPTH_CURVE_POINTS - 1) {
+                rates.add(top * Math.pow(floor / top, i.toDouble() / (DEPTH_CURVE_POINTS - 1)))
+            }
+            rates.add(floor)
+        }
+        if (floor != null && points.last().first <= 0.0) rates.add(0.0)
+
+        val vsizes = LongArray(rates.size)
+        var next = 0
+        var cumulative = 0L
+        rates.forEachIndexed { i, rate ->
+            while (next < points.size && points[next].first >= rate) cumulative += points[next++].second
+            vsizes[i] = cumulative
+        }
+        return rates.toDoubleArray() to vsizes
+    }
+
+    /** In-run descendants of [uid] through the [spenders] index. */
+    private fun descendantsOf(uid: Int, spenders: Map<Int, List<Int>>): Set<Int> {
+        val descendants = LinkedHashSet<Int>()
+        val stack = ArrayDeque<Int>()
+        stack.add(uid)
+        while (stack.isNotEmpty()) {
+            for (spender in spenders[stack.removeLast()].orEmpty()) {
+                if (descendants.add(spender)) stack.add(spender)
+            }
+        }
+        return descendants
+    }
+
+    private fun packWeight(tx: ThreadTransaction): Int = sigopAdjustedWeight(tx.weight, tx.sigops, bytesPerSigop)
+
+    /** FNV-1a over the ordered uids and weight of every block; any change in composition or order changes it. */
+    private fun templateHash(blocks: List<IntArray>, blockWeights: List<Int>): Long {
+        var hash = -0x340d631b7bdddcdbL // FNV-1a 64-bit offset basis
+        fun mix(value: Int) {
+            for (shift in 0 until 32 step 8) {
+                hash = hash xor ((value ushr shift) and 0xff).toLong()
+                hash *= 0x100000001b3L
+            }
+        }
+        blocks.forEachIndexed { index, block ->
+            mix(block.size)
+            block.forEach { mix(it) }
+            mix(blockWeights[index])
+        }
+        return hash
+    }
+
+    /**
+     * Groups transactions connected through in-mempool inputs. Only clusters with at least
+     * two members are returned, each sorted by uid and ordered by their smallest uid.
+     */
+    private fun buildClusters(mempool: List<ThreadTransaction>): List<IntArray> {
+        val parent = HashMap<Int, Int>(mempool.size)
+        for (tx in mempool) parent[tx.uid] = tx.uid
+        fun find(uid: Int): Int {
+            var root = uid
+            while (parent[root] != root) root = parent[root]!!
+            var node = uid
+            while (node != root) node = parent.put(node, root)!!
+            return root
+        }
+        for (tx in mempool) {
+            for (input in tx.inputs) {
+                if (input !in parent) continue
+                val a = find(tx.uid)
+                val b = find(input)
+                if (a != b) parent[maxOf(a, b)] = minOf(a, b)
+            }
+        }
+        return mempool.groupBy({ find(it.uid) }, { it.uid })
+            .values
+            .filter { it.size > 1 }
+            .map { it.sorted().toIntArray() }
+            .sortedBy { it[0] }
+    }
+
+    /**
+     * Walks each transaction's in-mempool ancestor set once, so shared ancestors in diamond
+     * shaped graphs are counted a single time. Fees are acceleration-adjusted.
+     */
+    private fun buildTxStats(mempool: List<ThreadTransaction>): GbtTxStats {
+        val byUid = mempool.associateBy { it.uid }
+        val uids = byUid.keys.sorted()
+        val descendantCounts = HashMap<Int, Int>(byUid.size)
+        val ancestorCounts = IntArray(uids.size)
+        val ancestorWeights = LongArray(uids.size)
+        val ancestorFees = LongArray(uids.size)
+        val now = System.currentTimeMillis() / 1000
+        uids.forEachIndexed { i, uid ->
+            val ancestors = ancestorsOf(uid, byUid)
+            val tx = byUid.getValue(uid)
+            ancestorCounts[i] = ancestors.size
+            ancestorWeights[i] = tx.weight + ancestors.sumOf { byUid.getValue(it).weight.toLong() }
+            ancestorFees[i] = tx.fee + ancestors.sumOf { byUid.getValue(it).fee }
+            for (ancestor in ancestors) descendantCounts[ancestor] = (descendantCounts[ancestor] ?: 0) + 1
+        }
+        return GbtTxStats(
+            uids = uids.toIntArray(),
+            ancestorCounts = ancestorCounts,
+            descendantCounts = IntArray(uids.size) { descendantCounts[uids[it]] ?: 0 },
+            ancestorWeights = ancestorWeights,
+            ancestorFees = ancestorFees,
+            ages = LongArray(uids.size) { i -> byUid.getValue(uids[i]).firstSeen?.let { maxOf(0L, now - it) } ?: 0L }
+        )
+    }
+
+    /** In-mempool ancestors of [uid], excluding [uid] itself. */
+    private fun ancestorsOf(uid: Int, byUid: Map<Int, ThreadTransaction>): Set<Int> {
+        val ancestors = HashSet<Int>()
+        val stack = ArrayDeque<Int>()
+        stack.add(uid)
+        while (stack.isNotEmpty()) {
+            val tx = byUid[stack.removeLast()] ?: continue
+            for (input in tx.inputs) {
+                if (input in byUid && input != uid && ancestors.add(input)) stack.add(input)
+            }
+        }
+        return ancestors
+    }
+
+    fun destroy() {
+        stopAutoCompute()
+        setRecording(null)
+        stopTrace()
+    }
+
+    /**
+     * Recomputes on a background thread whenever the state changed since the last result and
+     * at least [minIntervalMillis] passed since the previous check, passing each fresh result
+     * to [listener] on that thread. Updates arriving in between are coalesced into one run.
+     * While nothing changes the checks back off, up to [MAX_AUTO_COMPUTE_IDLE_MILLIS] apart.
+     * Replaces a scheduler that is already running, after waiting for it to stop.
+     */
+    fun startAutoCompute(minIntervalMillis: Long, listener: (GbtResult) -> Unit) {
+        require(minIntervalMillis > 0) { "minIntervalMillis must be positive, got $minIntervalMillis" }
+        val worker = Thread({ autoComputeLoop(minIntervalMillis, listener) }, "gbt-auto-compute").apply { isDaemon = true }
+        // The old worker is joined outside autoComputeLock, since its listener may be calling
+        // stopAutoCompute itself. Retry if a concurrent start installed a worker meanwhile.
+        while (true) {
+            stopAutoCompute()
+            synchronized(autoComputeLock) {
+                if (autoComputeThread == null) {
+                    autoComputeThread = worker
+                    worker.start()
+                    return
+                }
+            }
+        }
+    }
+
+    /** Stops the [startAutoCompute] thread, waiting for a run in progress to finish. */
+    fun stopAutoCompute() {
+        val thread = synchronized(autoComputeLock) { autoComputeThread.also { autoComputeThread = null } } ?: return
+        thread.interrupt()
+        // The listener may stop the scheduler from the worker thread itself.
+        if (thread !== Thread.currentThread()) thread.join()
+    }
+
+    private fun autoComputeLoop(minIntervalMillis: Long, listener: (GbtResult) -> Unit) {
+        var idleMillis = minIntervalMillis
+        try {
+            while (!Thread.currentThread().isInterrupted) {
+                Thread.sleep(idleMillis)
+                if (pendingChangeCount() == 0L) {
+                    idleMillis = minOf(idleMillis * 2, maxOf(minIntervalMillis, MAX_AUTO_COMPUTE_IDLE_MILLIS))
+                    continue
+                }
+                idleMillis = minIntervalMillis
+                val result = compute() ?: continue
+                if (result.fromCache) continue
+                try {
+                    listener(result)
+                } catch (e: InterruptedException) {
+                    // Stopped while the listener was blocked; keep the stop request.
+                    Thread.currentThread().interrupt()
+                } catch (e: Exception) {
+                    Log.e(TAG, "Run ${result.runId}: auto-compute listener failed", e)
+                }
+            }
+        } catch (e: InterruptedException) {
+            // Stopped by stopAutoCompute.
+        }
+    }
+
+    /**
+     * Packing state of one transaction: its not yet selected in-run ancestors and their
+     * totals, scored by [strategy]. A zero-fee parent scores 0 alone but rides into the block
+     * with its fee-paying child, at the child's package rate.
+     */
+    private class PackingTx(
+        val tx: ThreadTransaction,
+        val packWeight: Int,
+        val ancestors: HashSet<Int>,
+        private val strategy: GbtSelectionStrategy
+    ) {
+        var ancestorFee = 0L
+        var ancestorPackWeight = 0L
+        var ancestorSigops = 0
+        var score = 0.0
+        var used = false
+        /** Sigop-adjusted weight of this transaction with its unselected ancestors. */
+        val packageWeight get() = ancestorPackWeight + packWeight
+
+        fun rescore() {
+            score = strategy.score(
+                tx.effectiveFeePerVsize, tx.fee, weightToVsize(packWeight.toLong()),
+                ancestorFee + tx.fee, weightToVsize(packageWeight), ancestors.isNotEmpty()
+            )
+        }
+
+        /** Independent copy, for packing the same initial state more than once. */
+        fun copy(): PackingTx = PackingTx(tx, packWeight, HashSet(ancestors), strategy).also {
+            it.ancestorFee = ancestorFee
+            it.ancestorPackWeight = ancestorPackWeight
+            it.ancestorSigops = ancestorSigops
+            it.score = score
+            it.used = used
+        }
+    }
+
+    /** See [buildGraph]. [ancestors] and [spenders] only cover [scorable] transactions. */
+    private class PackingGraph(
+        val adjustedMempool: List<ThreadTransaction>,
+        /** Rates before acceleration deltas, for transactions whose rate gets adjusted. */
+        val baseRates: Map<Int, Double>,
+        val unscorable: List<ThreadTransaction>,
+        val nonStandard: Set<Int>,
+        val trucExcluded: Map<Int, Int>,
+        val scorable: List<ThreadTransaction>,
+        val ancestors: Map<Int, Set<Int>>,
+        val spenders: Map<Int, List<Int>>
+    )
+
+    /** Output of one [pack] run: per-block lists in block order, plus the consumed [states]. */
+    private class PackedBlocks(val states: Map<Int, PackingTx>) {
+        val blocks = mutableListOf<IntArray>()
+        val blockWeights = mutableListOf<Int>()
+        val blockVsizes = mutableListOf<Int>()
+        val blockAdjustedVsizes = mutableListOf<Int>()
+        val blockRateSamples = mutableListOf<DoubleArray>()
+        val cpfpEdges = mutableListOf<IntArray>()
+        val packedRates = HashMap<Int, Double>()
+        var nextBlockCutoffRate = MIN_RELAY_FEE_RATE
+    }
+
+    /** A change queued behind a run in flight; [size] is what it counts towards [MAX_PENDING_UPDATES]. */
+    private class PendingUpdate(val size: Int, val apply: () -> Unit)
+
+    /** [pack]'s lightest-package heap entry; stale once [state] is rescored or selected. */
+    private class SizedCandidate(val state: PackingTx) : Comparable<SizedCandidate> {
+        val packageWeight = state.packageWeight
+
+        override fun compareTo(other: SizedCandidate): Int = packageWeight.compareTo(other.packageWeight)
+    }
+
+    /**
+     * [trimToSize] queue entry, lowest [score] first, then uid. [score] is the exact descendant
+     * score if [exact], otherwise the transaction's own rate as a lower bound for it.
+     */
+    private class EvictionCandidate(val score: Double, val uid: Int, val exact: Boolean) : Comparable<EvictionCandidate> {
+        override fun compareTo(other: EvictionCandidate): Int {
+            val byScore = score.compareTo(other.score)
+            return if (byScore != 0) byScore else uid.compareTo(other.uid)
+        }
+    }
+
+    /** Heap entry; stale once [state] is rescored or selected. Best score first, then order, then uid. */
+    private class Candidate(val state: PackingTx) : Comparable<Candidate> {
+        val score = state.score
+
+        override fun compareTo(other: Candidate): Int {
+            val byScore = other.score.compareTo(score)
+            if (byScore != 0) return byScore
+            val byOrder = state.tx.order.compareTo(other.state.tx.order)
+            if (byOrder != 0) return byOrder
+            return state.tx.uid.compareTo(other.state.tx.uid)
+        }
+    }
+
+    /**
+     * Package rates added to one block, downsampled to at most [max] values (at least 2). Keeps
+     * every stride-th rate and doubles the stride whenever the buffer fills, so memory stays
+     * bounded however many packages the block takes; the first and last rates are kept exactly.
+     */
+    private class RateSampler(private val max: Int) {
+        private val samples = DoubleArray(2 * max)
+        private var count = 0
+        private var stride = 1
+        private var seen = 0
+        private var last = 0.0
+
+        fun add(rate: Double) {
+            if (seen % stride == 0) {
+                if (count == samples.size) {
+                    for (i in 0 until count / 2) samples[i] = samples[2 * i]
+                    count /= 2
+                    stride *= 2
+                }
+                if (seen % stride == 0) samples[count++] = rate
+            }
+            last = rate
+            seen++
+        }
+
+        fun toArray(): DoubleArray {
+            if (seen == 0) return doubleArrayOf()
+            val kept = samples.copyOf(count).toMutableList()
+            if ((seen - 1) % stride != 0) kept.add(last)
+            if (kept.size <= max) return kept.toDoubleArray()
+            return DoubleArray(max) { kept[(it.toLong() * (kept.size - 1) / (max - 1)).toInt()] }
+        }
+    }
+
+    private class OverflowTotals {
+        var count = 0
+        var weight = 0L
+        var fees = 0L
+    }
+
+    private class MempoolSnapshot(
+        val transactions: Map<Int, ThreadTransaction>,
+        val accelerations: Map<Int, ThreadAcceleration>,
+        val changeCounter: Long,
+        val invalidTxCount: Int,
+        val rateDiscrepancyCount: Int
+    )
 }
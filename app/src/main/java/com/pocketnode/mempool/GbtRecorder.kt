@@ -0,0 +1,260 @@
+package com.pocketnode.mempool
+
+import java.io.BufferedOutputStream
+import java.io.DataInputStream
+import java.io.DataOutputStream
+import java.io.EOFException
+import java.io.File
+import java.io.FileOutputStream
+import java.io.IOException
+
+/**
+ * Records the inputs [GbtGenerator] receives so a user's exact update sequence can be replayed
+ * with [GbtReplay]. The file is a version header and the generator's [config], a MAKE of the
+ * state at the time the file was started ([baseState]), then MAKE / UPDATE / COMPUTE / RESET
+ * records. Once it grows past [maxBytes] it's rotated to `<name>.1` before the next record, and
+ * the fresh file starts from the state at that point, so each file replays on its own.
+ */
+class GbtRecorder(
+    private val file: File,
+    private val config: GbtConfig,
+    private val maxBytes: Long = DEFAULT_MAX_BYTES,
+    /** Tracked transactions and accelerations, read under the generator's lock. */
+    private val baseState: () -> Pair<List<ThreadTransaction>, List<ThreadAcceleration>>
+) {
+    companion object {
+        const val DEFAULT_MAX_BYTES = 16L * 1024 * 1024
+
+        internal const val MAGIC = 0x47425452 // "GBTR"
+        internal const val VERSION = 4
+        internal const val RECORD_MAKE = 1
+        internal const val RECORD_UPDATE = 2
+        internal const val RECORD_COMPUTE = 3
+        internal const val RECORD_RESET = 4
+
+        /** Largest list capacity reserved from a length prefix before its items are read. */
+        private const val MAX_PREALLOCATED = 1024
+
+        internal fun writeConfig(out: DataOutputStream, config: GbtConfig) {
+            out.writeInt(config.maxBlockWeight)
+            out.writeInt(config.maxBlocks)
+            out.writeBoolean(config.includeTxids)
+            out.writeBoolean(config.strict)
+            out.writeInt(config.removedCacheSize)
+            out.writeInt(config.maxTxCount)
+            out.writeLong(config.maxMemoryBytes)
+            out.writeBoolean(config.deriveRates)
+            out.writeInt(config.bytesPerSigop)
+            out.writeInt(config.acceptedPolicyMask)
+            out.writeUTF(config.strategy.name)
+            out.writeInt(config.maxTxsPerCall)
+            out.writeInt(config.maxInputsPerTx)
+            out.writeInt(config.maxAccelerations)
+        }
+
+        /** @throws GbtFormatException for an unknown strategy or a config [GbtConfig.validated] rejects. */
+        internal fun readConfig(input: DataInputStream): GbtConfig {
+            val config = GbtConfig(
+                maxBlockWeight = input.readInt(),
+                maxBlocks = input.readInt(),
+                includeTxids = input.readBoolean(),
+                strict = input.readBoolean(),
+                removedCacheSize = input.readInt(),
+                maxTxCount = input.readInt(),
+                maxMemoryBytes = input.readLong(),
+                deriveRates = input.readBoolean(),
+                bytesPerSigop = input.readInt(),
+                acceptedPolicyMask = input.readInt(),
+                strategy = input.readUTF().let { name ->
+                    GbtSelectionStrategy.values().firstOrNull { it.name == name }
+                        ?: throw GbtFormatException("Corrupt data: unknown strategy $name")
+                },
+                maxTxsPerCall = input.readInt(),
+                maxInputsPerTx = input.readInt(),
+                maxAccelerations = input.readInt()
+            )
+            try {
+                return config.validated()
+            } catch (e: IllegalArgumentException) {
+                throw GbtFormatException("Corrupt data: ${e.message}")
+            }
+        }
+
+        /** Reads a length prefix, rejecting negative or implausibly large values. */
+        internal fun readCount(input: DataInputStream, max: Int): Int {
+            val count = input.readInt()
+            if (count < 0 || count > max) throw GbtFormatException("Corrupt data: length $count outside 0..$max")
+            return count
+        }
+
+        /**
+         * Reads [count] items, growing the list as they arrive. The caps come from the recorded
+         * config, which is itself untrusted, so nothing is sized by [count] up front: a corrupt
+         * length runs into the end of the file instead of allocating.
+         */
+        private inline fun <T> readList(count: Int, read: () -> T): List<T> {
+            val items = ArrayList<T>(minOf(count, MAX_PREALLOCATED))
+            repeat(count) { items.add(read()) }
+            return items
+        }
+
+        /**
+         * Length prefixes here are bounded by the replaying generator's [GbtConfig] caps, so a
+         * corrupt file can't produce a batch the generator would reject anyway.
+         */
+        internal fun readTransactions(input: DataInputStream, limits: GbtConfig): List<ThreadTransaction> {
+            return readList(readCount(input, limits.maxTxsPerCall)) { readTransaction(input, limits) }
+        }
+
+        internal fun readUids(input: DataInputStream, limits: GbtConfig): List<Int> {
+            return readList(readCount(input, limits.maxTxsPerCall)) { input.readInt() }
+        }
+
+        internal fun readAccelerations(input: DataInputStream, limits: GbtConfig): List<ThreadAcceleration> {
+            return readList(readCount(input, limits.maxAccelerations)) { ThreadAcceleration(input.readInt(), input.readLong()) }
+        }
+
+        internal fun writeTransaction(out: DataOutputStream, tx: ThreadTransaction) {
+            out.writeInt(tx.uid)
+            out.writeInt(tx.order)
+            out.writeLong(tx.fee)
+            out.writeInt(tx.weight)
+            out.writeInt(tx.sigops)
+            out.writeDouble(tx.effectiveFeePerVsize)
+            out.writeInt(tx.inputs.size)
+            tx.inputs.forEach { out.writeInt(it) }
+            out.writeUTF(tx.txid ?: "")
+            out.writeLong(tx.firstSeen ?: -1L)
+            out.writeBoolean(tx.isTruc)
+            out.writeInt(tx.policyFlags)
+        }
+
+        internal fun readTransaction(input: DataInputStream, limits: GbtConfig): ThreadTransaction {
+            val uid = input.readInt()
+            val order = input.readInt()
+            val fee = input.readLong()
+            val weight = input.readInt()
+            val sigops = input.readInt()
+            val effectiveFeePerVsize = input.readDouble()
+            val inputs = readList(readCount(input, limits.maxInputsPerTx)) { input.readInt() }.toIntArray()
+            val txid = input.readUTF().ifEmpty { null }
+            val firstSeen = input.readLong().takeIf { it >= 0 }
+            val isTruc = input.readBoolean()
+            val policyFlags = input.readInt()
+            return ThreadTransaction(
+                uid, order, fee, weight, sigops, effectiveFeePerVsize, inputs, txid, firstSeen, isTruc, policyFlags
+            )
+        }
+    }
+
+    private var out = open()
+
+    fun recordMake(mempool: List<ThreadTransaction>, accelerations: List<ThreadAcceleration>) {
+        write(RECORD_MAKE) {
+            writeTransactions(mempool)
+            writeAccelerations(accelerations)
+        }
+    }
+
+    fun recordUpdate(newTxs: List<ThreadTransaction>, removeTxs: List<Int>, accelerations: List<ThreadAcceleration>) {
+        write(RECORD_UPDATE) {
+            writeTransactions(newTxs)
+            writeInt(removeTxs.size)
+            removeTxs.forEach { writeInt(it) }
+            writeAccelerations(accelerations)
+        }
+    }
+
+    fun recordCompute() = write(RECORD_COMPUTE) {}
+
+    fun recordReset(resetCounters: Boolean) = write(RECORD_RESET) { writeBoolean(resetCounters) }
+
+    fun close() = out.close()
+
+    private fun open(): DataOutputStream {
+        file.parentFile?.mkdirs()
+        val (mempool, accelerations) = baseState()
+        return DataOutputStream(BufferedOutputStream(FileOutputStream(file))).apply {
+            writeInt(MAGIC)
+            writeInt(VERSION)
+            writeConfig(this, config)
+            writeByte(RECORD_MAKE)
+            writeTransactions(mempool)
+            writeAccelerations(accelerations)
+            flush()
+        }
+    }
+
+    /**
+     * Rotation happens before a record rather than after one: records are written before the
+     * generator applies them, so only then does [baseState] include everything recorded so far.
+     */
+    private fun write(type: Int, body: DataOutputStream.() -> Unit) {
+        if (file.length() > maxBytes) rotate()
+        out.writeByte(type)
+        out.body()
+        out.flush()
+    }
+
+    private fun rotate() {
+        out.close()
+        val previous = File(file.path + ".1")
+        previous.delete()
+        if (!file.renameTo(previous)) throw IOException("Failed to rotate ${file.path}")
+        out = open()
+    }
+
+    private fun DataOutputStream.writeTransactions(txs: List<ThreadTransaction>) {
+        writeInt(txs.size)
+        txs.forEach { writeTransaction(this, it) }
+    }
+
+    private fun DataOutputStream.writeAccelerations(accelerations: List<ThreadAcceleration>) {
+        writeInt(accelerations.size)
+        accelerations.forEach {
+            writeInt(it.uid)
+            writeLong(it.delta)
+        }
+    }
+}
+
+/**
+ * Replays a [GbtRecorder] file against a fresh generator created with the recorded config.
+ * Malformed input fails with a [GbtFormatException] (or an [EOFException] for truncated
+ * files) rather than crashing.
+ */
+object GbtReplay {
+    /** Returns the result of every recorded compute, in order. [seed] fixes the randomized parts. */
+    fun replayFile(file: File, seed: Long = 0L): List<GbtResult?> {
+        val results = mutableListOf<GbtResult?>()
+        DataInputStream(file.inputStream().buffered()).use { input ->
+            if (input.readInt() != GbtRecorder.MAGIC) throw GbtFormatException("Not a GBT recording: ${file.path}")
+            val version = input.readInt()
+            if (version != GbtRecorder.VERSION) throw GbtFormatException("Unsupported recording version $version")
+            val limits = GbtRecorder.readConfig(input)
+            val generator = GbtGenerator.create(limits).apply { setSeed(seed) }
+            while (true) {
+                val type = try {
+                    input.readByte().toInt()
+                } catch (e: EOFException) {
+                    break
+                }
+                when (type) {
+                    GbtRecorder.RECORD_MAKE -> {
+                        val mempool = GbtRecorder.readTransactions(input, limits)
+                        generator.applyMake(mempool, GbtRecorder.readAccelerations(input, limits))
+                    }
+                    GbtRecorder.RECORD_UPDATE -> {
+                        val newTxs = GbtRecorder.readTransactions(input, limits)
+                        val removeTxs = GbtRecorder.readUids(input, limits)
+                        generator.applyUpdates(newTxs, removeTxs, GbtRecorder.readAccelerations(input, limits))
+                    }
+                    GbtRecorder.RECORD_COMPUTE -> results.add(generator.compute())
+                    GbtRecorder.RECORD_RESET -> generator.reset(input.readBoolean())
+                    else -> throw GbtFormatException("Unknown record type $type")
+                }
+            }
+        }
+        return results
+    }
+}
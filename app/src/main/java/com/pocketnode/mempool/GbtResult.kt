@@ -1,30 +1,629 @@
 package com.pocketnode.mempool
 
+/**
+ * Output of a [GbtGenerator] run. Transactions are identified by uid; weights are in WU,
+ * vsizes in vB (see [weightToVsize]), fees in sats and rates in sat/vB.
+ */
 data class GbtResult(
+    /**
+     * Projected blocks, best first. A run always yields at least one, empty if nothing could be
+     * packed (e.g. an empty mempool); the per-block arrays below have the same length.
+     */
     val blocks: Array<IntArray> = emptyArray(),
+    /** Total weight of each block in WU. */
     val blockWeights: IntArray = intArrayOf(),
+    /**
+     * Per-block summaries filled in while packing, parallel to [blockWeights] and part of the
+     * same detail section: transaction count and vsize in vB (the sum of each transaction's
+     * [weightToVsize]).
+     */
+    val blockTxCounts: IntArray = intArrayOf(),
+    val blockVsizes: IntArray = intArrayOf(),
+    /** Per-block vsize with sigops priced in (see [sigopAdjustedWeight]), as used for block space. */
+    val blockAdjustedVsizes: IntArray = intArrayOf(),
+    /**
+     * Per block, the package rates (sat/vB) in the order packages were added, downsampled to
+     * at most 100 values with the first and the last (the block's cutoff) kept exactly. Mostly
+     * non-increasing, but a package can score above its predecessor once an ancestor was taken
+     * by another package (CPFP).
+     */
+    val blockRateSamples: Array<DoubleArray> = emptyArray(),
+    /**
+     * Per block, CPFP links as flattened (child uid, parent uid) pairs: ancestors whose own rate
+     * was below the block's cutoff and that made it in only as part of a child's package. Each
+     * parent appears at most once.
+     */
+    val cpfpEdges: Array<IntArray> = emptyArray(),
+    /** Groups of transactions connected through in-mempool inputs; only groups of two or more. */
     val clusters: Array<IntArray> = emptyArray(),
+    /**
+     * Per-cluster totals, index-aligned with [clusters]: acceleration-adjusted fees (sats),
+     * vsize (vB), the resulting rate (sat/vB), and whether any member is accelerated.
+     */
+    val clusterFees: LongArray = longArrayOf(),
+    val clusterVsizes: IntArray = intArrayOf(),
+    val clusterRates: DoubleArray = doubleArrayOf(),
+    val clusterAccelerated: BooleanArray = booleanArrayOf(),
+    /**
+     * [clusters] flattened into parallel arrays: [clusterIds] holds the index into [clusters]
+     * for each uid in [clusterUids]. Transactions without in-mempool relatives are omitted.
+     */
+    val clusterUids: IntArray = intArrayOf(),
+    val clusterIds: IntArray = intArrayOf(),
+    /**
+     * [uid, base rate, final rate] in sat/vB for transactions whose rate was adjusted before
+     * packing. The base rate excludes acceleration deltas; the final rate is what was packed.
+     */
     val rates: Array<DoubleArray> = emptyArray(),
-    val overflow: IntArray = intArrayOf()
+    val overflow: IntArray = intArrayOf(),
+    /** Why each [overflow] entry was left out, one of the OVERFLOW_* constants. */
+    val overflowReasons: IntArray = intArrayOf(),
+    /**
+     * [overflow] totalled per reason: for each code present in [overflowReasons] (ascending),
+     * the transaction count, weight (WU) and acceleration-adjusted fees (sats). The fees of the
+     * NO_SPACE and NOT_EVALUATED buckets add up to [overflowFees].
+     */
+    val overflowReasonCodes: IntArray = intArrayOf(),
+    val overflowReasonCounts: IntArray = intArrayOf(),
+    val overflowReasonWeights: LongArray = longArrayOf(),
+    val overflowReasonFees: LongArray = longArrayOf(),
+    /** True when the generator returned its previous result because nothing changed. */
+    val fromCache: Boolean = false,
+    /** Changes in projected positions relative to the generator's previous run. */
+    val diff: GbtResultDiff? = null,
+    /** [blocks] as txids when the generator was created with txid results; "" where unknown. */
+    val blockTxids: Array<Array<String>> = emptyArray(),
+    /** [overflow] as txids when the generator was created with txid results; "" where unknown. */
+    val overflowTxids: Array<String> = emptyArray(),
+    /** Transactions rejected by validation since the previous result. */
+    val invalidTxCount: Int = 0,
+    /**
+     * Transactions since the previous result whose provided rate disagreed with the one
+     * derived from fee and weight. Only counted by generators created with deriveRates.
+     */
+    val rateDiscrepancyCount: Int = 0,
+    /** Per-transaction dependency statistics, null when not requested. */
+    val txStats: GbtTxStats? = null,
+    /**
+     * Effective fee rate (sat/vB, as packed, so acceleration-adjusted) of the last transaction
+     * that made it into block 0; 1 sat/vB when block 0 is empty.
+     */
+    val nextBlockCutoffRate: Double = 0.0,
+    /**
+     * Best package rate (sat/vB) among transactions that didn't fit, i.e. roughly the rate
+     * needed to make the projection at all; 0 when nothing overflowed for lack of space.
+     * [overflowVsize] (vB) and [overflowFees] (sats, acceleration-adjusted) total those
+     * transactions. Unscorable, TRUC-rejected and non-standard transactions are left out of all three.
+     */
+    val overflowTopRate: Double = 0.0,
+    val overflowVsize: Long = 0,
+    val overflowFees: Long = 0,
+    /**
+     * Cumulative depth curve for fee UIs: [depthCurveVsizes] holds the total vsize (vB) of
+     * packed transactions at or above each rate in [depthCurveRates] (sat/vB, log-spaced, highest
+     * first), i.e. [GbtGenerator.getDepthAtRate] at those rates when the result was made.
+     */
+    val depthCurveRates: DoubleArray = doubleArrayOf(),
+    val depthCurveVsizes: LongArray = longArrayOf(),
+    /** Digest of the ordered block contents and weights, for cheap change detection. */
+    val templateHash: Long = 0,
+    /** Largest uid present in the packed mempool, 0 if it was empty. */
+    val maxUid: Int = 0,
+    /** Block limit the run packed with after clamping, 0 if unlimited. */
+    val effectiveMaxBlocks: Int = 0,
+    /** Scoring the run selected packages with. */
+    val strategy: GbtSelectionStrategy = GbtSelectionStrategy.EFFECTIVE_RATE,
+    /** The generator's seed for randomized work when the run was made; see [GbtGenerator.setSeed]. */
+    val seed: Long = 0,
+    /** Watched transactions whose projection changed since the previous run. */
+    val watchedChanges: GbtWatchedChanges? = null,
+    /** Where the user's own transactions landed; see [GbtGenerator.setOwnTransactions]. */
+    val ownTxPositions: GbtOwnPositions? = null,
+    /**
+     * Sequence number of the run that produced this result, increasing per generator. Cached
+     * results keep their run's id; trace events and log lines of the run carry the same id.
+     */
+    val runId: Long = 0,
+    /** [GbtGenerator.getChangeCounter] at the state this result was packed from. */
+    val changeCounter: Long = 0
 ) {
+    companion object {
+        /** Block index used for transactions that didn't fit into the projected blocks. */
+        const val OVERFLOW_BLOCK = -1
+        /** Block index reported for uids that weren't part of the last run. */
+        const val UNKNOWN_UID = -2
+
+        /** Didn't fit into any of the projected blocks. */
+        const val OVERFLOW_NO_SPACE = 0
+        /** Skipped by a next-block-only run once block 0 was full. */
+        const val OVERFLOW_NOT_EVALUATED = 1
+        /** Fee rate couldn't be scored (NaN). */
+        const val OVERFLOW_UNSCORABLE = 2
+        /** TRUC transaction with more than one in-mempool parent. */
+        const val OVERFLOW_TRUC_INVALID = 3
+        /** TRUC transaction above the TRUC size limit, or a TRUC child above the child limit. */
+        const val OVERFLOW_TRUC_OVERSIZED = 4
+        /** TRUC child displaced by a better-scoring sibling spending the same TRUC parent. */
+        const val OVERFLOW_TRUC_SIBLING_EVICTED = 5
+        /** Carries policy flags outside GbtConfig.acceptedPolicyMask, or descends from such a transaction. */
+        const val OVERFLOW_NON_STANDARD = 6
+        /** Descends from a transaction left out under one of the OVERFLOW_TRUC_* reasons above. */
+        const val OVERFLOW_TRUC_ANCESTOR_EXCLUDED = 7
+
+        /** Section flags for the detail mask accepted by [GbtGenerator.compute]. */
+        const val DETAIL_BLOCKS = 1
+        const val DETAIL_WEIGHTS = 2
+        const val DETAIL_CLUSTERS = 4
+        const val DETAIL_RATES = 8
+        const val DETAIL_OVERFLOW = 16
+        const val DETAIL_TX_STATS = 32
+        const val DETAIL_RATE_SAMPLES = 64
+        const val DETAIL_CPFP_EDGES = 128
+        const val DETAIL_DEPTH_CURVE = 256
+        const val DETAIL_ALL = DETAIL_BLOCKS or DETAIL_WEIGHTS or DETAIL_CLUSTERS or DETAIL_RATES or
+            DETAIL_OVERFLOW or DETAIL_TX_STATS or DETAIL_RATE_SAMPLES or DETAIL_CPFP_EDGES or DETAIL_DEPTH_CURVE
+    }
+
+    /** Returns a copy with the sections not selected by [detail] emptied. */
+    fun withDetail(detail: Int): GbtResult {
+        if (detail and DETAIL_ALL == DETAIL_ALL) return this
+        fun has(flag: Int) = detail and flag != 0
+        return copy(
+            blocks = if (has(DETAIL_BLOCKS)) blocks else emptyArray(),
+            blockTxids = if (has(DETAIL_BLOCKS)) blockTxids else emptyArray(),
+            blockWeights = if (has(DETAIL_WEIGHTS)) blockWeights else intArrayOf(),
+            blockTxCounts = if (has(DETAIL_WEIGHTS)) blockTxCounts else intArrayOf(),
+            blockVsizes = if (has(DETAIL_WEIGHTS)) blockVsizes else intArrayOf(),
+            blockAdjustedVsizes = if (has(DETAIL_WEIGHTS)) blockAdjustedVsizes else intArrayOf(),
+            blockRateSamples = if (has(DETAIL_RATE_SAMPLES)) blockRateSamples else emptyArray(),
+            cpfpEdges = if (has(DETAIL_CPFP_EDGES)) cpfpEdges else emptyArray(),
+            clusters = if (has(DETAIL_CLUSTERS)) clusters else emptyArray(),
+            clusterFees = if (has(DETAIL_CLUSTERS)) clusterFees else longArrayOf(),
+            clusterVsizes = if (has(DETAIL_CLUSTERS)) clusterVsizes else intArrayOf(),
+            clusterRates = if (has(DETAIL_CLUSTERS)) clusterRates else doubleArrayOf(),
+            clusterAccelerated = if (has(DETAIL_CLUSTERS)) clusterAccelerated else booleanArrayOf(),
+            clusterUids = if (has(DETAIL_CLUSTERS)) clusterUids else intArrayOf(),
+            clusterIds = if (has(DETAIL_CLUSTERS)) clusterIds else intArrayOf(),
+            rates = if (has(DETAIL_RATES)) rates else emptyArray(),
+            overflow = if (has(DETAIL_OVERFLOW)) overflow else intArrayOf(),
+            overflowReasons = if (has(DETAIL_OVERFLOW)) overflowReasons else intArrayOf(),
+            overflowReasonCodes = if (has(DETAIL_OVERFLOW)) overflowReasonCodes else intArrayOf(),
+            overflowReasonCounts = if (has(DETAIL_OVERFLOW)) overflowReasonCounts else intArrayOf(),
+            overflowReasonWeights = if (has(DETAIL_OVERFLOW)) overflowReasonWeights else longArrayOf(),
+            overflowReasonFees = if (has(DETAIL_OVERFLOW)) overflowReasonFees else longArrayOf(),
+            overflowTxids = if (has(DETAIL_OVERFLOW)) overflowTxids else emptyArray(),
+            txStats = if (has(DETAIL_TX_STATS)) txStats else null,
+            depthCurveRates = if (has(DETAIL_DEPTH_CURVE)) depthCurveRates else doubleArrayOf(),
+            depthCurveVsizes = if (has(DETAIL_DEPTH_CURVE)) depthCurveVsizes else longArrayOf()
+        )
+    }
+
     override fun equals(other: Any?): Boolean {
         if (this === other) return true
         if (javaClass != other?.javaClass) return false
         other as GbtResult
         if (!blocks.contentDeepEquals(other.blocks)) return false
         if (!blockWeights.contentEquals(other.blockWeights)) return false
+        if (!blockTxCounts.contentEquals(other.blockTxCounts)) return false
+        if (!blockVsizes.contentEquals(other.blockVsizes)) return false
+        if (!blockAdjustedVsizes.contentEquals(other.blockAdjustedVsizes)) return false
+        if (!blockRateSamples.contentDeepEquals(other.blockRateSamples)) return false
+        if (!cpfpEdges.contentDeepEquals(other.cpfpEdges)) return false
         if (!clusters.contentDeepEquals(other.clusters)) return false
+        if (!clusterFees.contentEquals(other.clusterFees)) return false
+        if (!clusterVsizes.contentEquals(other.clusterVsizes)) return false
+        if (!clusterRates.contentEquals(other.clusterRates)) return false
+        if (!clusterAccelerated.contentEquals(other.clusterAccelerated)) return false
+        if (!clusterUids.contentEquals(other.clusterUids)) return false
+        if (!clusterIds.contentEquals(other.clusterIds)) return false
         if (!rates.contentDeepEquals(other.rates)) return false
         if (!overflow.contentEquals(other.overflow)) return false
+        if (!overflowReasons.contentEquals(other.overflowReasons)) return false
+        if (!overflowReasonCodes.contentEquals(other.overflowReasonCodes)) return false
+        if (!overflowReasonCounts.contentEquals(other.overflowReasonCounts)) return false
+        if (!overflowReasonWeights.contentEquals(other.overflowReasonWeights)) return false
+        if (!overflowReasonFees.contentEquals(other.overflowReasonFees)) return false
+        if (fromCache != other.fromCache) return false
+        if (diff != other.diff) return false
+        if (!blockTxids.contentDeepEquals(other.blockTxids)) return false
+        if (!overflowTxids.contentEquals(other.overflowTxids)) return false
+        if (invalidTxCount != other.invalidTxCount) return false
+        if (rateDiscrepancyCount != other.rateDiscrepancyCount) return false
+        if (txStats != other.txStats) return false
+        if (nextBlockCutoffRate != other.nextBlockCutoffRate) return false
+        if (overflowTopRate != other.overflowTopRate) return false
+        if (overflowVsize != other.overflowVsize) return false
+        if (overflowFees != other.overflowFees) return false
+        if (!depthCurveRates.contentEquals(other.depthCurveRates)) return false
+        if (!depthCurveVsizes.contentEquals(other.depthCurveVsizes)) return false
+        if (templateHash != other.templateHash) return false
+        if (maxUid != other.maxUid) return false
+        if (effectiveMaxBlocks != other.effectiveMaxBlocks) return false
+        if (strategy != other.strategy) return false
+        if (seed != other.seed) return false
+        if (watchedChanges != other.watchedChanges) return false
+        if (ownTxPositions != other.ownTxPositions) return false
+        if (runId != other.runId) return false
+        if (changeCounter != other.changeCounter) return false
         return true
     }
 
     override fun hashCode(): Int {
         var result = blocks.contentDeepHashCode()
         result = 31 * result + blockWeights.contentHashCode()
+        result = 31 * result + blockTxCounts.contentHashCode()
+        result = 31 * result + blockVsizes.contentHashCode()
+        result = 31 * result + blockAdjustedVsizes.contentHashCode()
+        result = 31 * result + blockRateSamples.contentDeepHashCode()
+        result = 31 * result + cpfpEdges.contentDeepHashCode()
         result = 31 * result + clusters.contentDeepHashCode()
+        result = 31 * result + clusterFees.contentHashCode()
+        result = 31 * result + clusterVsizes.contentHashCode()
+        result = 31 * result + clusterRates.contentHashCode()
+        result = 31 * result + clusterAccelerated.contentHashCode()
+        result = 31 * result + clusterUids.contentHashCode()
+        result = 31 * result + clusterIds.contentHashCode()
         result = 31 * result + rates.contentDeepHashCode()
         result = 31 * result + overflow.contentHashCode()
+        result = 31 * result + overflowReasons.contentHashCode()
+        result = 31 * result + overflowReasonCodes.contentHashCode()
+        result = 31 * result + overflowReasonCounts.contentHashCode()
+        result = 31 * result + overflowReasonWeights.contentHashCode()
+        result = 31 * result + overflowReasonFees.contentHashCode()
+        result = 31 * result + fromCache.hashCode()
+        result = 31 * result + (diff?.hashCode() ?: 0)
+        result = 31 * result + blockTxids.contentDeepHashCode()
+        result = 31 * result + overflowTxids.contentHashCode()
+        result = 31 * result + invalidTxCount
+        result = 31 * result + rateDiscrepancyCount
+        result = 31 * result + (txStats?.hashCode() ?: 0)
+        result = 31 * result + nextBlockCutoffRate.hashCode()
+        result = 31 * result + overflowTopRate.hashCode()
+        result = 31 * result + overflowVsize.hashCode()
+        result = 31 * result + overflowFees.hashCode()
+        result = 31 * result + depthCurveRates.contentHashCode()
+        result = 31 * result + depthCurveVsizes.contentHashCode()
+        result = 31 * result + templateHash.hashCode()
+        result = 31 * result + maxUid
+        result = 31 * result + effectiveMaxBlocks
+        result = 31 * result + strategy.hashCode()
+        result = 31 * result + seed.hashCode()
+        result = 31 * result + (watchedChanges?.hashCode() ?: 0)
+        result = 31 * result + (ownTxPositions?.hashCode() ?: 0)
+        result = 31 * result + runId.hashCode()
+        result = 31 * result + changeCounter.hashCode()
+        return result
+    }
+}
+
+/**
+ * Projection changes between two consecutive runs. Moved transactions are described by
+ * the parallel arrays [movedUids], [movedFromBlock] and [movedToBlock]; overflow counts
+ * as block [GbtResult.OVERFLOW_BLOCK].
+ */
+data class GbtResultDiff(
+    val addedUids: IntArray = intArrayOf(),
+    val removedUids: IntArray = intArrayOf(),
+    val movedUids: IntArray = intArrayOf(),
+    val movedFromBlock: IntArray = intArrayOf(),
+    val movedToBlock: IntArray = intArrayOf()
+) {
+    override fun equals(other: Any?): Boolean {
+        if (this === other) return true
+        if (javaClass != other?.javaClass) return false
+        other as GbtResultDiff
+        if (!addedUids.contentEquals(other.addedUids)) return false
+        if (!removedUids.contentEquals(other.removedUids)) return false
+        if (!movedUids.contentEquals(other.movedUids)) return false
+        if (!movedFromBlock.contentEquals(other.movedFromBlock)) return false
+        if (!movedToBlock.contentEquals(other.movedToBlock)) return false
+        return true
+    }
+
+    override fun hashCode(): Int {
+        var result = addedUids.contentHashCode()
+        result = 31 * result + removedUids.contentHashCode()
+        result = 31 * result + movedUids.contentHashCode()
+        result = 31 * result + movedFromBlock.contentHashCode()
+        result = 31 * result + movedToBlock.contentHashCode()
+        return result
+    }
+}
+
+/**
+ * Changes to watched transactions between two consecutive runs, as parallel arrays. Block
+ * indices are as in [GbtGenerator.getTxPosition]: a watched transaction that left the
+ * mempool (mined or removed) moves to [GbtResult.UNKNOWN_UID], and one that was never
+ * tracked is reported once with both sides unknown. Rates are in sat/vB, 0 when unknown.
+ */
+data class GbtWatchedChanges(
+    val uids: IntArray = intArrayOf(),
+    val oldBlocks: IntArray = intArrayOf(),
+    val newBlocks: IntArray = intArrayOf(),
+    val oldRates: DoubleArray = doubleArrayOf(),
+    val newRates: DoubleArray = doubleArrayOf()
+) {
+    override fun equals(other: Any?): Boolean {
+        if (this === other) return true
+        if (javaClass != other?.javaClass) return false
+        other as GbtWatchedChanges
+        if (!uids.contentEquals(other.uids)) return false
+        if (!oldBlocks.contentEquals(other.oldBlocks)) return false
+        if (!newBlocks.contentEquals(other.newBlocks)) return false
+        if (!oldRates.contentEquals(other.oldRates)) return false
+        if (!newRates.contentEquals(other.newRates)) return false
+        return true
+    }
+
+    override fun hashCode(): Int {
+        var result = uids.contentHashCode()
+        result = 31 * result + oldBlocks.contentHashCode()
+        result = 31 * result + newBlocks.contentHashCode()
+        result = 31 * result + oldRates.contentHashCode()
+        result = 31 * result + newRates.contentHashCode()
+        return result
+    }
+}
+
+/**
+ * The user's own transactions in a run, as parallel arrays ordered by [uids]. Block indices
+ * are as in [GbtGenerator.getTxPosition], with [GbtResult.UNKNOWN_UID] for a transaction
+ * that is no longer tracked; rates are in sat/vB, 0 when unknown. [nextBlockCount] counts
+ * those projected into block 0 and [pendingFees] sums the fees (sats) of those still tracked.
+ */
+data class GbtOwnPositions(
+    val uids: IntArray = intArrayOf(),
+    val blocks: IntArray = intArrayOf(),
+    val rates: DoubleArray = doubleArrayOf(),
+    val nextBlockCount: Int = 0,
+    val pendingFees: Long = 0
+) {
+    override fun equals(other: Any?): Boolean {
+        if (this === other) return true
+        if (javaClass != other?.javaClass) return false
+        other as GbtOwnPositions
+        if (!uids.contentEquals(other.uids)) return false
+        if (!blocks.contentEquals(other.blocks)) return false
+        if (!rates.contentEquals(other.rates)) return false
+        if (nextBlockCount != other.nextBlockCount) return false
+        if (pendingFees != other.pendingFees) return false
+        return true
+    }
+
+    override fun hashCode(): Int {
+        var result = uids.contentHashCode()
+        result = 31 * result + blocks.contentHashCode()
+        result = 31 * result + rates.contentHashCode()
+        result = 31 * result + nextBlockCount
+        result = 31 * result + pendingFees.hashCode()
+        return result
+    }
+}
+
+/**
+ * Dependency statistics for every tracked transaction, as parallel arrays ordered by [uids].
+ * Counts exclude the transaction itself; [ancestorWeights] (WU) and [ancestorFees] (sats,
+ * acceleration-adjusted) include it, like Core's ancestorsize/fees.ancestor. [ages] are
+ * seconds since each transaction was first seen.
+ */
+data class GbtTxStats(
+    val uids: IntArray = intArrayOf(),
+    val ancestorCounts: IntArray = intArrayOf(),
+    val descendantCounts: IntArray = intArrayOf(),
+    val ancestorWeights: LongArray = longArrayOf(),
+    val ancestorFees: LongArray = longArrayOf(),
+    val ages: LongArray = longArrayOf()
+) {
+    override fun equals(other: Any?): Boolean {
+        if (this === other) return true
+        if (javaClass != other?.javaClass) return false
+        other as GbtTxStats
+        if (!uids.contentEquals(other.uids)) return false
+        if (!ancestorCounts.contentEquals(other.ancestorCounts)) return false
+        if (!descendantCounts.contentEquals(other.descendantCounts)) return false
+        if (!ancestorWeights.contentEquals(other.ancestorWeights)) return false
+        if (!ancestorFees.contentEquals(other.ancestorFees)) return false
+        if (!ages.contentEquals(other.ages)) return false
+        return true
+    }
+
+    override fun hashCode(): Int {
+        var result = uids.contentHashCode()
+        result = 31 * result + ancestorCounts.contentHashCode()
+        result = 31 * result + descendantCounts.contentHashCode()
+        result = 31 * result + ancestorWeights.contentHashCode()
+        result = 31 * result + ancestorFees.contentHashCode()
+        result = 31 * result + ages.contentHashCode()
+        return result
+    }
+}
+
+/** Parallel uid / fee rate (sat/vB) arrays, ordered best first. */
+data class RankedRates(
+    val uids: IntArray = intArrayOf(),
+    val rates: DoubleArray = doubleArrayOf()
+) {
+    override fun equals(other: Any?): Boolean {
+        if (this === other) return true
+        if (javaClass != other?.javaClass) return false
+        other as RankedRates
+        if (!uids.contentEquals(other.uids)) return false
+        if (!rates.contentEquals(other.rates)) return false
+        return true
+    }
+
+    override fun hashCode(): Int {
+        var result = uids.contentHashCode()
+        result = 31 * result + rates.contentHashCode()
+        return result
+    }
+}
+
+/** Projected block indices and packing fee rates (sat/vB) for a batch of queried uids. */
+data class ConfirmationEtas(
+    val blocks: IntArray = intArrayOf(),
+    val rates: DoubleArray = doubleArrayOf()
+) {
+    override fun equals(other: Any?): Boolean {
+        if (this === other) return true
+        if (javaClass != other?.javaClass) return false
+        other as ConfirmationEtas
+        if (!blocks.contentEquals(other.blocks)) return false
+        if (!rates.contentEquals(other.rates)) return false
+        return true
+    }
+
+    override fun hashCode(): Int {
+        var result = blocks.contentHashCode()
+        result = 31 * result + rates.contentHashCode()
+        return result
+    }
+}
+
+/** Outcome of [GbtGenerator.getDepthAtRate]. */
+data class MempoolDepth(
+    /** Total vsize in vB. */
+    val vsize: Long,
+    /** Total fees in sats. */
+    val fees: Long
+)
+
+/** Outcome of [GbtGenerator.previewAcceleration]; block indices as in [GbtGenerator.getTxPosition]. */
+data class AccelerationPreview(
+    val currentBlock: Int,
+    val newBlock: Int,
+    /** Effective fee rate in sat/vB the transaction would be packed at. */
+    val newRate: Double
+)
+
+/**
+ * Outcome of [GbtGenerator.computeEnsemble]: for each uid (ascending), the fraction of the
+ * [runs] completed runs in which it landed in block 0, in the first [FIRST_BLOCKS] blocks, or
+ * in overflow. [seed] is the seed the runs drew their noise from.
+ */
+data class EnsembleProbabilities(
+    val uids: IntArray = intArrayOf(),
+    val blockZero: DoubleArray = doubleArrayOf(),
+    val firstBlocks: DoubleArray = doubleArrayOf(),
+    val overflow: DoubleArray = doubleArrayOf(),
+    val runs: Int = 0,
+    val seed: Long = 0
+) {
+    companion object {
+        const val FIRST_BLOCKS = 3
+    }
+
+    override fun equals(other: Any?): Boolean {
+        if (this === other) return true
+        if (javaClass != other?.javaClass) return false
+        other as EnsembleProbabilities
+        if (!uids.contentEquals(other.uids)) return false
+        if (!blockZero.contentEquals(other.blockZero)) return false
+        if (!firstBlocks.contentEquals(other.firstBlocks)) return false
+        if (!overflow.contentEquals(other.overflow)) return false
+        if (runs != other.runs) return false
+        if (seed != other.seed) return false
+        return true
+    }
+
+    override fun hashCode(): Int {
+        var result = uids.contentHashCode()
+        result = 31 * result + blockZero.contentHashCode()
+        result = 31 * result + firstBlocks.contentHashCode()
+        result = 31 * result + overflow.contentHashCode()
+        result = 31 * result + runs
+        result = 31 * result + seed.hashCode()
+        return result
+    }
+}
+
+/**
+ * The accelerations a [GbtGenerator] holds, as parallel arrays ordered by uid. Rates are in
+ * sat/vB: [boostedRates] is what the last run packed each transaction at (0 if it wasn't part
+ * of that run) and [unboostedRates] its own rate without the delta. [stale] marks uids that are
+ * no longer tracked, whose acceleration the caller can drop.
+ */
+data class AppliedAccelerations(
+    val uids: IntArray = intArrayOf(),
+    /** Fee deltas in sats. */
+    val deltas: LongArray = longArrayOf(),
+    val boostedRates: DoubleArray = doubleArrayOf(),
+    val unboostedRates: DoubleArray = doubleArrayOf(),
+    /** Projected block in the last run, as in [GbtGenerator.getTxPosition]. */
+    val projectedBlocks: IntArray = intArrayOf(),
+    val stale: BooleanArray = booleanArrayOf()
+) {
+    override fun equals(other: Any?): Boolean {
+        if (this === other) return true
+        if (javaClass != other?.javaClass) return false
+        other as AppliedAccelerations
+        if (!uids.contentEquals(other.uids)) return false
+        if (!deltas.contentEquals(other.deltas)) return false
+        if (!boostedRates.contentEquals(other.boostedRates)) return false
+        if (!unboostedRates.contentEquals(other.unboostedRates)) return false
+        if (!projectedBlocks.contentEquals(other.projectedBlocks)) return false
+        if (!stale.contentEquals(other.stale)) return false
+        return true
+    }
+
+    override fun hashCode(): Int {
+        var result = uids.contentHashCode()
+        result = 31 * result + deltas.contentHashCode()
+        result = 31 * result + boostedRates.contentHashCode()
+        result = 31 * result + unboostedRates.contentHashCode()
+        result = 31 * result + projectedBlocks.contentHashCode()
+        result = 31 * result + stale.contentHashCode()
+        return result
+    }
+}
+
+/** Outcome of [GbtGenerator.removeByTxid]: uids removed (leaves first) and txids that weren't tracked. */
+data class TxidRemoval(
+    val removedUids: IntArray = intArrayOf(),
+    val missingTxids: Array<String> = emptyArray()
+) {
+    override fun equals(other: Any?): Boolean {
+        if (this === other) return true
+        if (javaClass != other?.javaClass) return false
+        other as TxidRemoval
+        if (!removedUids.contentEquals(other.removedUids)) return false
+        if (!missingTxids.contentEquals(other.missingTxids)) return false
+        return true
+    }
+
+    override fun hashCode(): Int {
+        var result = removedUids.contentHashCode()
+        result = 31 * result + missingTxids.contentHashCode()
+        return result
+    }
+}
+
+/**
+ * One entry of [GbtGenerator.computeMulti]: the blocks projected under [maxBlockWeight], their
+ * weights in WU and their acceleration-adjusted fees in sats.
+ */
+data class GbtBlockProjection(
+    val maxBlockWeight: Int = 0,
+    val blocks: Array<IntArray> = emptyArray(),
+    val blockWeights: IntArray = intArrayOf(),
+    val blockFees: LongArray = longArrayOf()
+) {
+    override fun equals(other: Any?): Boolean {
+        if (this === other) return true
+        if (javaClass != other?.javaClass) return false
+        other as GbtBlockProjection
+        if (maxBlockWeight != other.maxBlockWeight) return false
+        if (!blocks.contentDeepEquals(other.blocks)) return false
+        if (!blockWeights.contentEquals(other.blockWeights)) return false
+        if (!blockFees.contentEquals(other.blockFees)) return false
+        return true
+    }
+
+    override fun hashCode(): Int {
+        var result = maxBlockWeight
+        result = 31 * result + blocks.contentDeepHashCode()
+        result = 31 * result + blockWeights.contentHashCode()
+        result = 31 * result + blockFees.contentHashCode()
         return result
     }
 }
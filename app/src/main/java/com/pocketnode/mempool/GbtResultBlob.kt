@@ -0,0 +1,240 @@
+package com.pocketnode.mempool
+
+import java.io.ByteArrayInputStream
+import java.io.ByteArrayOutputStream
+import java.io.DataInputStream
+import java.io.DataOutputStream
+import java.io.EOFException
+
+/**
+ * Compact binary form of a [GbtResult], for handing results to another process without
+ * marshalling every array as an object. A magic and version header is followed by each field
+ * in declaration order; optional sections carry a presence flag. Sections stripped by
+ * [GbtResult.withDetail] are simply empty, so the blob respects the detail mask it was made with.
+ */
+object GbtResultBlob {
+    private const val MAGIC = 0x47425242 // "GBRB"
+    private const val VERSION = 9
+
+    fun encode(result: GbtResult): ByteArray {
+        val bytes = ByteArrayOutputStream()
+        DataOutputStream(bytes).use { out ->
+            out.writeInt(MAGIC)
+            out.writeInt(VERSION)
+            out.writeIntArrays(result.blocks)
+            out.writeInts(result.blockWeights)
+            out.writeInts(result.blockTxCounts)
+            out.writeInts(result.blockVsizes)
+            out.writeInts(result.blockAdjustedVsizes)
+            out.writeDoubleArrays(result.blockRateSamples)
+            out.writeIntArrays(result.cpfpEdges)
+            out.writeIntArrays(result.clusters)
+            out.writeLongs(result.clusterFees)
+            out.writeInts(result.clusterVsizes)
+            out.writeDoubles(result.clusterRates)
+            out.writeInt(result.clusterAccelerated.size)
+            result.clusterAccelerated.forEach { out.writeBoolean(it) }
+            out.writeInts(result.clusterUids)
+            out.writeInts(result.clusterIds)
+            out.writeDoubleArrays(result.rates)
+            out.writeInts(result.overflow)
+            out.writeInts(result.overflowReasons)
+            out.writeInts(result.overflowReasonCodes)
+            out.writeInts(result.overflowReasonCounts)
+            out.writeLongs(result.overflowReasonWeights)
+            out.writeLongs(result.overflowReasonFees)
+            out.writeBoolean(result.fromCache)
+            out.writeBoolean(result.diff != null)
+            result.diff?.let {
+                out.writeInts(it.addedUids)
+                out.writeInts(it.removedUids)
+                out.writeInts(it.movedUids)
+                out.writeInts(it.movedFromBlock)
+                out.writeInts(it.movedToBlock)
+            }
+            out.writeInt(result.blockTxids.size)
+            result.blockTxids.forEach { out.writeStrings(it) }
+            out.writeStrings(result.overflowTxids)
+            out.writeInt(result.invalidTxCount)
+            out.writeInt(result.rateDiscrepancyCount)
+            out.writeBoolean(result.txStats != null)
+            result.txStats?.let {
+                out.writeInts(it.uids)
+                out.writeInts(it.ancestorCounts)
+                out.writeInts(it.descendantCounts)
+                out.writeLongs(it.ancestorWeights)
+                out.writeLongs(it.ancestorFees)
+                out.writeLongs(it.ages)
+            }
+            out.writeDouble(result.nextBlockCutoffRate)
+            out.writeDouble(result.overflowTopRate)
+            out.writeLong(result.overflowVsize)
+            out.writeLong(result.overflowFees)
+            out.writeDoubles(result.depthCurveRates)
+            out.writeLongs(result.depthCurveVsizes)
+            out.writeLong(result.templateHash)
+            out.writeInt(result.maxUid)
+            out.writeInt(result.effectiveMaxBlocks)
+            out.writeUTF(result.strategy.name)
+            out.writeLong(result.seed)
+            out.writeBoolean(result.watchedChanges != null)
+            result.watchedChanges?.let {
+                out.writeInts(it.uids)
+                out.writeInts(it.oldBlocks)
+                out.writeInts(it.newBlocks)
+                out.writeDoubles(it.oldRates)
+                out.writeDoubles(it.newRates)
+            }
+            out.writeBoolean(result.ownTxPositions != null)
+            result.ownTxPositions?.let {
+                out.writeInts(it.uids)
+                out.writeInts(it.blocks)
+                out.writeDoubles(it.rates)
+                out.writeInt(it.nextBlockCount)
+                out.writeLong(it.pendingFees)
+            }
+            out.writeLong(result.runId)
+            out.writeLong(result.changeCounter)
+        }
+        return bytes.toByteArray()
+    }
+
+    /**
+     * Decodes a blob made by [encode]. Needs no generator, so it can run in the receiving
+     * process. Malformed blobs throw [GbtFormatException], truncated ones [EOFException].
+     */
+    fun decode(blob: ByteArray): GbtResult {
+        // No length prefix can exceed the blob itself, which bounds every allocation.
+        val max = blob.size
+        DataInputStream(ByteArrayInputStream(blob)).use { input ->
+            if (input.readInt() != MAGIC) throw GbtFormatException("Not a GBT result blob")
+            val version = input.readInt()
+            if (version != VERSION) throw GbtFormatException("Unsupported result blob version $version")
+            val result = GbtResult(
+                blocks = input.readIntArrays(max),
+                blockWeights = input.readInts(max),
+                blockTxCounts = input.readInts(max),
+                blockVsizes = input.readInts(max),
+                blockAdjustedVsizes = input.readInts(max),
+                blockRateSamples = input.readDoubleArrays(max),
+                cpfpEdges = input.readIntArrays(max),
+                clusters = input.readIntArrays(max),
+                clusterFees = input.readLongs(max),
+                clusterVsizes = input.readInts(max),
+                clusterRates = input.readDoubles(max),
+                clusterAccelerated = BooleanArray(GbtRecorder.readCount(input, max)) { input.readBoolean() },
+                clusterUids = input.readInts(max),
+                clusterIds = input.readInts(max),
+                rates = input.readDoubleArrays(max),
+                overflow = input.readInts(max),
+                overflowReasons = input.readInts(max),
+                overflowReasonCodes = input.readInts(max),
+                overflowReasonCounts = input.readInts(max),
+                overflowReasonWeights = input.readLongs(max),
+                overflowReasonFees = input.readLongs(max),
+                fromCache = input.readBoolean(),
+                diff = if (input.readBoolean()) {
+                    GbtResultDiff(
+                        addedUids = input.readInts(max),
+                        removedUids = input.readInts(max),
+                        movedUids = input.readInts(max),
+                        movedFromBlock = input.readInts(max),
+                        movedToBlock = input.readInts(max)
+                    )
+                } else null,
+                blockTxids = Array(GbtRecorder.readCount(input, max)) { input.readStrings(max) },
+                overflowTxids = input.readStrings(max),
+                invalidTxCount = input.readInt(),
+                rateDiscrepancyCount = input.readInt(),
+                txStats = if (input.readBoolean()) {
+                    GbtTxStats(
+                        uids = input.readInts(max),
+                        ancestorCounts = input.readInts(max),
+                        descendantCounts = input.readInts(max),
+                        ancestorWeights = input.readLongs(max),
+                        ancestorFees = input.readLongs(max),
+                        ages = input.readLongs(max)
+                    )
+                } else null,
+                nextBlockCutoffRate = input.readDouble(),
+                overflowTopRate = input.readDouble(),
+                overflowVsize = input.readLong(),
+                overflowFees = input.readLong(),
+                depthCurveRates = input.readDoubles(max),
+                depthCurveVsizes = input.readLongs(max),
+                templateHash = input.readLong(),
+                maxUid = input.readInt(),
+                effectiveMaxBlocks = input.readInt(),
+                strategy = input.readUTF().let { name ->
+                    GbtSelectionStrategy.values().firstOrNull { it.name == name }
+                        ?: throw GbtFormatException("Unknown selection strategy $name")
+                },
+                seed = input.readLong(),
+                watchedChanges = if (input.readBoolean()) {
+                    GbtWatchedChanges(
+                        uids = input.readInts(max),
+                        oldBlocks = input.readInts(max),
+                        newBlocks = input.readInts(max),
+                        oldRates = input.readDoubles(max),
+                        newRates = input.readDoubles(max)
+                    )
+                } else null,
+                ownTxPositions = if (input.readBoolean()) {
+                    GbtOwnPositions(
+                        uids = input.readInts(max),
+                        blocks = input.readInts(max),
+                        rates = input.readDoubles(max),
+                        nextBlockCount = input.readInt(),
+                        pendingFees = input.readLong()
+                    )
+                } else null,
+                runId = input.readLong(),
+                changeCounter = input.readLong()
+            )
+            if (input.read() != -1) throw GbtFormatException("Trailing data after result blob")
+            return result
+        }
+    }
+
+    private fun DataOutputStream.writeInts(values: IntArray) {
+        writeInt(values.size)
+        values.forEach { writeInt(it) }
+    }
+
+    private fun DataOutputStream.writeLongs(values: LongArray) {
+        writeInt(values.size)
+        values.forEach { writeLong(it) }
+    }
+
+    private fun DataOutputStream.writeDoubles(values: DoubleArray) {
+        writeInt(values.size)
+        values.forEach { writeDouble(it) }
+    }
+
+    private fun DataOutputStream.writeStrings(values: Array<String>) {
+        writeInt(values.size)
+        values.forEach { writeUTF(it) }
+    }
+
+    private fun DataOutputStream.writeIntArrays(values: Array<IntArray>) {
+        writeInt(values.size)
+        values.forEach { writeInts(it) }
+    }
+
+    private fun DataOutputStream.writeDoubleArrays(values: Array<DoubleArray>) {
+        writeInt(values.size)
+        values.forEach { writeDoubles(it) }
+    }
+
+    private fun DataInputStream.readInts(max: Int) = IntArray(GbtRecorder.readCount(this, max)) { readInt() }
+
+    private fun DataInputStream.readLongs(max: Int) = LongArray(GbtRecorder.readCount(this, max)) { readLong() }
+
+    private fun DataInputStream.readDoubles(max: Int) = DoubleArray(GbtRecorder.readCount(this, max)) { readDouble() }
+
+    private fun DataInputStream.readStrings(max: Int) = Array(GbtRecorder.readCount(this, max)) { readUTF() }
+
+    private fun DataInputStream.readIntArrays(max: Int) = Array(GbtRecorder.readCount(this, max)) { readInts(max) }
+
+    private fun DataInputStream.readDoubleArrays(max: Int) = Array(GbtRecorder.readCount(this, max)) { readDoubles(max) }
+}
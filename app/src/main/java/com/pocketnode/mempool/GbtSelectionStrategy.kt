@@ -0,0 +1,58 @@
+package com.pocketnode.mempool
+
+/**
+ * How [GbtGenerator] scores candidate packages, set through [GbtConfig.strategy]. Fees are
+ * acceleration-adjusted and vsizes sigop-adjusted under every strategy; equal scores are
+ * broken by [ThreadTransaction.order], then uid.
+ */
+enum class GbtSelectionStrategy {
+    /**
+     * A transaction without unselected ancestors scores at its provided effective rate, which
+     * may already account for CPFP the way mempool.space's does; a package at its combined
+     * fee over combined vsize.
+     */
+    EFFECTIVE_RATE {
+        override fun score(
+            ownRate: Double,
+            ownFee: Long,
+            ownVsize: Long,
+            packageFee: Long,
+            packageVsize: Long,
+            hasAncestors: Boolean
+        ): Double {
+            return if (hasAncestors) packageFee.toDouble() / packageVsize else ownRate
+        }
+    },
+
+    /**
+     * Bitcoin Core's BlockAssembler, ignoring provided rates: the lower of the transaction's
+     * own fee over vsize and its ancestor package's, as in CompareTxMemPoolEntryByAncestorFee.
+     * A low-fee child of a high-fee parent is not lifted by its parent.
+     */
+    ANCESTOR_SCORE {
+        override fun score(
+            ownRate: Double,
+            ownFee: Long,
+            ownVsize: Long,
+            packageFee: Long,
+            packageVsize: Long,
+            hasAncestors: Boolean
+        ): Double {
+            return minOf(ownFee.toDouble() / ownVsize, packageFee.toDouble() / packageVsize)
+        }
+    };
+
+    /**
+     * Score in sat/vB of a transaction together with its unselected ancestors. [packageFee] and
+     * [packageVsize] include the transaction itself; [ownRate] is its adjusted effective rate,
+     * and [ownFee] and [ownVsize] the adjusted fee and vsize it was derived from.
+     */
+    internal abstract fun score(
+        ownRate: Double,
+        ownFee: Long,
+        ownVsize: Long,
+        packageFee: Long,
+        packageVsize: Long,
+        hasAncestors: Boolean
+    ): Double
+}
@@ -0,0 +1,81 @@
+package com.pocketnode.mempool
+
+/**
+ * Result of [GbtGenerator.verifyState]. Each uid list holds at most
+ * [MAX_REPORTED_UIDS] examples; the counts cover everything found.
+ */
+data class GbtStateReport(
+    val txCount: Int = 0,
+    /** Transactions with an input that isn't tracked. Expected while parents are in flight, so not a failure. */
+    val orphanCount: Int = 0,
+    val orphanUids: IntArray = intArrayOf(),
+    /** Transactions whose entries in the reverse child index are missing or extra. */
+    val childIndexMismatchCount: Int = 0,
+    val childIndexMismatchUids: IntArray = intArrayOf(),
+    /** Transactions listing the same input twice. */
+    val duplicateInputCount: Int = 0,
+    val duplicateInputUids: IntArray = intArrayOf(),
+    /**
+     * Transactions whose txid doesn't map back to them in the txid index, usually because the
+     * caller sent one txid under two uids. Reported, but like orphans not counted as a failure.
+     */
+    val txidIndexMismatchCount: Int = 0,
+    val txidIndexMismatchUids: IntArray = intArrayOf(),
+    /** Whether the running counters (estimated memory) match a recomputation. */
+    val countersMatch: Boolean = true,
+    /** Uids in the last run's positions that are no longer tracked. Only checked while that run is current. */
+    val stalePositionCount: Int = 0,
+    val stalePositionUids: IntArray = intArrayOf()
+) {
+    companion object {
+        const val MAX_REPORTED_UIDS = 20
+    }
+
+    /** True if nothing other than orphans and txid collisions was found. */
+    val isConsistent: Boolean
+        get() = childIndexMismatchCount == 0 && duplicateInputCount == 0 && countersMatch && stalePositionCount == 0
+
+    override fun equals(other: Any?): Boolean {
+        if (this === other) return true
+        if (javaClass != other?.javaClass) return false
+        other as GbtStateReport
+        if (txCount != other.txCount) return false
+        if (orphanCount != other.orphanCount) return false
+        if (!orphanUids.contentEquals(other.orphanUids)) return false
+        if (childIndexMismatchCount != other.childIndexMismatchCount) return false
+        if (!childIndexMismatchUids.contentEquals(other.childIndexMismatchUids)) return false
+        if (duplicateInputCount != other.duplicateInputCount) return false
+        if (!duplicateInputUids.contentEquals(other.duplicateInputUids)) return false
+        if (txidIndexMismatchCount != other.txidIndexMismatchCount) return false
+        if (!txidIndexMismatchUids.contentEquals(other.txidIndexMismatchUids)) return false
+        if (countersMatch != other.countersMatch) return false
+        if (stalePositionCount != other.stalePositionCount) return false
+        if (!stalePositionUids.contentEquals(other.stalePositionUids)) return false
+        return true
+    }
+
+    override fun hashCode(): Int {
+        var result = txCount
+        result = 31 * result + orphanCount
+        result = 31 * result + orphanUids.contentHashCode()
+        result = 31 * result + childIndexMismatchCount
+        result = 31 * result + childIndexMismatchUids.contentHashCode()
+        result = 31 * result + duplicateInputCount
+        result = 31 * result + duplicateInputUids.contentHashCode()
+        result = 31 * result + txidIndexMismatchCount
+        result = 31 * result + txidIndexMismatchUids.contentHashCode()
+        result = 31 * result + countersMatch.hashCode()
+        result = 31 * result + stalePositionCount
+        result = 31 * result + stalePositionUids.contentHashCode()
+        return result
+    }
+
+    override fun toString(): String {
+        return "GbtStateReport(txCount=$txCount, orphans=$orphanCount ${orphanUids.contentToString()}, " +
+            "childIndexMismatches=$childIndexMismatchCount ${childIndexMismatchUids.contentToString()}, " +
+            "duplicateInputs=$duplicateInputCount ${duplicateInputUids.contentToString()}, " +
+            "txidIndexMismatches=$txidIndexMismatchCount ${txidIndexMismatchUids.contentToString()}, " +
+            "countersMatch=$countersMatch, " +
+            "stalePositions=$stalePositionCount ${stalePositionUids.contentToString()})"
+    }
+}
@@ -0,0 +1,46 @@
+package com.pocketnode.mempool
+
+import org.json.JSONArray
+import org.json.JSONObject
+import java.io.File
+
+/**
+ * Collects [GbtGenerator] phase timings and writes them as a Chrome trace, started with
+ * [GbtGenerator.startTrace] and written by [GbtGenerator.stopTrace]. Pull the file off the
+ * device and open it in chrome://tracing or https://ui.perfetto.dev; every phase is a
+ * complete ("X") event with its tx count or block index under "args", e.g.
+ *
+ *     {"traceEvents":[{"name":"gbt.block","ph":"X","ts":1520,"dur":8411,"pid":1,"tid":42,"args":{"index":0}}]}
+ *
+ * Timestamps are microseconds since the trace started. The same phases also show up as
+ * android.os.Trace sections in system traces.
+ */
+class GbtTracer(private val file: File) {
+    companion object {
+        /** Keeps a forgotten trace from growing without bound. */
+        private const val MAX_EVENTS = 100_000
+    }
+
+    private val origin = System.nanoTime()
+    private val events = JSONArray()
+
+    fun record(name: String, startNanos: Long, endNanos: Long, args: Array<out Pair<String, Any>>) {
+        val event = JSONObject()
+            .put("name", name)
+            .put("ph", "X")
+            .put("ts", (startNanos - origin) / 1000)
+            .put("dur", (endNanos - startNanos) / 1000)
+            .put("pid", 1)
+            .put("tid", Thread.currentThread().id)
+        if (args.isNotEmpty()) event.put("args", JSONObject().apply { args.forEach { put(it.first, it.second) } })
+        synchronized(events) {
+            if (events.length() < MAX_EVENTS) events.put(event)
+        }
+    }
+
+    fun close() {
+        val json = synchronized(events) { JSONObject().put("traceEvents", events).toString() }
+        file.parentFile?.mkdirs()
+        file.writeText(json)
+    }
+}
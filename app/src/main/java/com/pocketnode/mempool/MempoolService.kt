@@ -16,7 +16,6 @@ import kotlinx.coroutines.flow.asStateFlow
 import org.json.JSONArray
 import org.json.JSONObject
 import java.util.concurrent.ConcurrentHashMap
-import java.util.concurrent.atomic.AtomicInteger
 
 class MempoolService : Service() {
     companion object {
@@ -38,9 +37,7 @@ class MempoolService : Service() {
     private var gbtGenerator: GbtGenerator? = null
 
     private val currentMempool = ConcurrentHashMap<String, MempoolEntry>()
-    private val txIdToUid = ConcurrentHashMap<String, Int>()
-    private val uidToTxId = ConcurrentHashMap<Int, String>()
-    private val uidCounter = AtomicInteger(1)
+    private val uids = MempoolUids()
 
     private val _mempoolState = MutableStateFlow(MempoolState())
     val mempoolState: StateFlow<MempoolState> = _mempoolState.asStateFlow()
@@ -66,14 +63,17 @@ class MempoolService : Service() {
     fun reinitializeRpcClient() {
         isRpcConnected = false
         _rpcStatus.value = RpcStatus.DISCONNECTED
-        currentMempool.clear()
-        txIdToUid.clear()
-        uidToTxId.clear()
-        uidCounter.set(1)
+        resetUids()
         _gbtResult.value = null
         initializeRpcClient()
     }
 
+    private fun resetUids() {
+        currentMempool.clear()
+        uids.clear()
+        gbtGenerator?.reset()
+    }
+
     inner class MempoolBinder : Binder() {
         fun getService(): MempoolService = this@MempoolService
     }
@@ -90,6 +90,11 @@ class MempoolService : Service() {
         startPolling()
     }
 
+    override fun onTrimMemory(level: Int) {
+        super.onTrimMemory(level)
+        gbtGenerator?.trimMemory(level)
+    }
+
     override fun onDestroy() {
         super.onDestroy()
         stopPolling()
@@ -154,21 +159,22 @@ class MempoolService : Service() {
             _feeEstimates.value = feeEst
 
             val newTxIds = newMempoolData.keys.toSet()
+            if (uids.nextUid > Int.MAX_VALUE - newTxIds.size) {
+                // Uids are plain ints shared with GbtGenerator; start over rather than wrap negative.
+                Log.w(TAG, "uid space exhausted, renumbering mempool")
+                resetUids()
+            }
             val currentTxIds = currentMempool.keys.toSet()
             val addedTxIds = newTxIds - currentTxIds
             val removedTxIds = currentTxIds - newTxIds
 
-            removedTxIds.forEach { txId ->
-                currentMempool.remove(txId)
-                val uid = txIdToUid.remove(txId)
-                uid?.let { uidToTxId.remove(it) }
-            }
+            removedTxIds.forEach { currentMempool.remove(it) }
+            // Released before the generator sees them, so keep the uids for its removal list.
+            val removedUids = uids.release(removedTxIds)
             addedTxIds.forEach { txId ->
                 newMempoolData[txId]?.let { entry ->
                     currentMempool[txId] = entry
-                    val uid = uidCounter.getAndIncrement()
-                    txIdToUid[txId] = uid
-                    uidToTxId[uid] = txId
+                    uids.assign(txId)
                 }
             }
 
@@ -181,9 +187,8 @@ class MempoolService : Service() {
 
             updateFeeRateHistogram()
 
-            if (currentMempool.isNotEmpty()) {
-                runGbtAlgorithm(addedTxIds, removedTxIds)
-            }
+            // Also when the mempool just emptied, so the generator drops what was mined.
+            runGbtAlgorithm(addedTxIds, removedUids)
 
             fetchLatestBlock(rpc)
             checkWatchedTransactions(rpc)
@@ -255,18 +260,17 @@ class MempoolService : Service() {
         }
     }
 
-    private fun runGbtAlgorithm(addedTxIds: Set<String>, removedTxIds: Set<String>) {
+    private fun runGbtAlgorithm(addedTxIds: Set<String>, removedUids: List<Int>) {
         try {
             val generator = gbtGenerator ?: return
-            if (addedTxIds.isEmpty() && removedTxIds.isEmpty()) return
+            if (addedTxIds.isEmpty() && removedUids.isEmpty()) return
 
-            val maxUid = uidCounter.get()
+            val maxUid = uids.nextUid
 
-            if (removedTxIds.isNotEmpty()) {
+            if (removedUids.isNotEmpty()) {
                 val newThreadTxs = addedTxIds.mapNotNull { txId ->
                     currentMempool[txId]?.let { convertToThreadTransaction(txId, it) }
                 }
-                val removedUids = removedTxIds.mapNotNull { txIdToUid[it] }
                 val result = generator.update(newTxs = newThreadTxs, removeTxs = removedUids, maxUid = maxUid)
                 _gbtResult.value = result
                 result?.let { computeProjectedBlockInfo(it) }
@@ -292,7 +296,7 @@ class MempoolService : Service() {
                 val totalWeight = gbtResult.blockWeights.getOrNull(index) ?: 0
 
                 for (uid in block) {
-                    val txId = uidToTxId[uid] ?: continue
+                    val txId = uids.txIdOf(uid) ?: continue
                     val entry = currentMempool[txId] ?: continue
                     val feeRate = entry.effectiveFee / entry.vsize.toDouble() * 100_000_000.0
                     feeRates.add(feeRate)
@@ -368,11 +372,15 @@ class MempoolService : Service() {
     }
 
     private fun convertToThreadTransaction(txId: String, entry: MempoolEntry): ThreadTransaction? {
-        val uid = txIdToUid[txId] ?: return null
-        val inputUids = entry.depends.mapNotNull { txIdToUid[it] }.toIntArray()
-        val effectiveFeePerVsize = entry.effectiveFee / entry.vsize.toDouble()
+        val uid = uids.uidOf(txId) ?: return null
+        val inputUids = entry.depends.mapNotNull { uids.uidOf(it) }.toIntArray()
+        val fee = ThreadTransaction.btcToSats(entry.effectiveFee)
+        // Zero-fee parents (ephemeral anchors) are valid and get their rate from the child's package;
+        // only a missing vsize must not turn into a NaN rate.
+        val vsize = if (entry.vsize > 0) entry.vsize else weightToVsize(entry.weight)
+        val effectiveFeePerVsize = if (vsize > 0) fee / vsize.toDouble() else 0.0
         val order = (entry.time and 0xFFFFFFFF).toInt()
-        return ThreadTransaction(uid = uid, order = order, fee = entry.effectiveFee, weight = entry.weight, sigops = 0, effectiveFeePerVsize = effectiveFeePerVsize, inputs = inputUids)
+        return ThreadTransaction(uid = uid, order = order, fee = fee, weight = entry.weight, sigops = 0, effectiveFeePerVsize = effectiveFeePerVsize, inputs = inputUids, txid = txId, firstSeen = entry.time.takeIf { it > 0 })
     }
 
     private fun updateFeeRateHistogram() {
@@ -427,7 +435,7 @@ class MempoolService : Service() {
     suspend fun searchTransaction(txid: String): TransactionSearchResult {
         return try {
             currentMempool[txid]?.let { entry ->
-                val uid = txIdToUid[txid]
+                val uid = uids.uidOf(txid)
                 val blockPosition = findTransactionInProjectedBlocks(uid)
                 return TransactionSearchResult.InMempool(txid, entry, blockPosition)
             }
@@ -449,9 +457,7 @@ class MempoolService : Service() {
 
     private fun findTransactionInProjectedBlocks(uid: Int?): Int? {
         if (uid == null) return null
-        val currentGbt = _gbtResult.value ?: return null
-        currentGbt.blocks.forEachIndexed { index, block -> if (block.contains(uid)) return index }
-        return null
+        return gbtGenerator?.getTxPosition(uid)?.takeIf { it >= 0 }
     }
 }
 
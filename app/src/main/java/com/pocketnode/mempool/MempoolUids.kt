@@ -0,0 +1,42 @@
+package com.pocketnode.mempool
+
+import java.util.concurrent.ConcurrentHashMap
+import java.util.concurrent.atomic.AtomicInteger
+
+/**
+ * Txid to uid bookkeeping for [GbtGenerator], which only knows uids. Uids are handed out
+ * counting up from 1 and aren't reused until [clear].
+ */
+internal class MempoolUids {
+    private val txIdToUid = ConcurrentHashMap<String, Int>()
+    private val uidToTxId = ConcurrentHashMap<Int, String>()
+    private val uidCounter = AtomicInteger(1)
+
+    /** The uid [assign] hands out next; every assigned uid is below it. */
+    val nextUid: Int get() = uidCounter.get()
+
+    fun uidOf(txId: String): Int? = txIdToUid[txId]
+
+    fun txIdOf(uid: Int): String? = uidToTxId[uid]
+
+    fun assign(txId: String): Int {
+        val uid = uidCounter.getAndIncrement()
+        txIdToUid[txId] = uid
+        uidToTxId[uid] = txId
+        return uid
+    }
+
+    /**
+     * Forgets [txIds] and returns the uids they had, so the caller can still remove them from
+     * the generator. Unknown txids are skipped.
+     */
+    fun release(txIds: Collection<String>): List<Int> {
+        return txIds.mapNotNull { txId -> txIdToUid.remove(txId)?.also { uidToTxId.remove(it) } }
+    }
+
+    fun clear() {
+        txIdToUid.clear()
+        uidToTxId.clear()
+        uidCounter.set(1)
+    }
+}
@@ -1,6 +1,7 @@
 package com.pocketnode.mempool
 
+/** Out-of-band fee bump for [uid]; [delta] is in satoshis. */
 data class ThreadAcceleration(
     val uid: Int,
-    val delta: Double
+    val delta: Long
 )
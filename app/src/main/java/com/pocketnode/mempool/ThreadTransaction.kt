@@ -1,13 +1,27 @@
 package com.pocketnode.mempool
 
+/**
+ * A mempool transaction as fed to [GbtGenerator].
+ *
+ * [fee] is in whole satoshis so fee sums are exact; a Long holds the entire 21M BTC
+ * supply (2.1e15 sats) thousands of times over, so summing a whole mempool can't overflow.
+ * [effectiveFeePerVsize] is in sat/vB. [txid] is optional and only needed for
+ * txid-keyed results. [firstSeen] is in epoch seconds; when absent [GbtGenerator]
+ * stamps the insertion time. [isTruc] marks a version 3 (TRUC, BIP 431) transaction.
+ * [policyFlags] is a mask of POLICY_* non-standard traits some miners still accept.
+ */
 data class ThreadTransaction(
     val uid: Int,
     val order: Int,
-    val fee: Double,
+    val fee: Long,
     val weight: Int,
     val sigops: Int,
     val effectiveFeePerVsize: Double,
-    val inputs: IntArray
+    val inputs: IntArray,
+    val txid: String? = null,
+    val firstSeen: Long? = null,
+    val isTruc: Boolean = false,
+    val policyFlags: Int = 0
 ) {
     override fun equals(other: Any?): Boolean {
         if (this === other) return true
@@ -20,6 +34,10 @@ data class ThreadTransaction(
         if (sigops != other.sigops) return false
         if (effectiveFeePerVsize != other.effectiveFeePerVsize) return false
         if (!inputs.contentEquals(other.inputs)) return false
+        if (txid != other.txid) return false
+        if (firstSeen != other.firstSeen) return false
+        if (isTruc != other.isTruc) return false
+        if (policyFlags != other.policyFlags) return false
         return true
     }
 
@@ -31,6 +49,20 @@ data class ThreadTransaction(
         result = 31 * result + sigops
         result = 31 * result + effectiveFeePerVsize.hashCode()
         result = 31 * result + inputs.contentHashCode()
+        result = 31 * result + (txid?.hashCode() ?: 0)
+        result = 31 * result + (firstSeen?.hashCode() ?: 0)
+        result = 31 * result + isTruc.hashCode()
+        result = 31 * result + policyFlags
         return result
     }
+
+    companion object {
+        /** OP_RETURN output above the default datacarrier size. */
+        const val POLICY_LARGE_DATACARRIER = 1
+        /** Bare multisig output. */
+        const val POLICY_BARE_MULTISIG = 2
+
+        /** Converts a BTC amount as reported by RPC into satoshis, rounding to the nearest sat. */
+        fun btcToSats(btc: Double): Long = Math.round(btc * 100_000_000.0)
+    }
 }
@@ -0,0 +1,20 @@
+package com.pocketnode.mempool
+
+/**
+ * Virtual size in vB for a weight in WU, rounded up like Core's GetVirtualTransactionSize.
+ * Every vsize and every per-vB rate the mempool code reports goes through this, so a 401 WU
+ * transaction is 101 vB everywhere.
+ */
+fun weightToVsize(weight: Int): Int = (weight + 3) / 4
+
+/** [weightToVsize] for summed weights. */
+fun weightToVsize(weight: Long): Long = (weight + 3) / 4
+
+/**
+ * Weight a transaction costs a miner once sigops are priced in, like Core's
+ * GetVirtualTransactionSize: max(weight, sigops * bytesPerSigop). 0 disables the adjustment.
+ */
+fun sigopAdjustedWeight(weight: Int, sigops: Int, bytesPerSigop: Int): Int {
+    if (bytesPerSigop <= 0) return weight
+    return maxOf(weight.toLong(), sigops.toLong() * bytesPerSigop).coerceAtMost(Int.MAX_VALUE.toLong()).toInt()
+}
@@ -0,0 +1,61 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertNull
+import org.junit.Assert.assertTrue
+import org.junit.Test
+import java.util.concurrent.CountDownLatch
+import java.util.concurrent.TimeUnit
+import kotlin.concurrent.thread
+
+class GbtAccelerationPreviewTest {
+    /** Twelve 5 sat/vB transactions fill a 5,000 WU block; 13 at 1 sat/vB waits for the next. */
+    private val mempool = (1..12).map { tx(it, fee = 500) } + tx(13, fee = 100)
+
+    private fun packed() = generator(maxBlockWeight = GbtGenerator.MIN_BLOCK_WEIGHT).apply { make(mempool, maxUid = 13) }
+
+    @Test
+    fun previewMovesTheTransactionWithoutTouchingState() {
+        val generator = packed()
+
+        val preview = generator.previewAcceleration(13, 900)!!
+
+        assertEquals(1, preview.currentBlock)
+        assertEquals(0, preview.newBlock)
+        assertEquals(10.0, preview.newRate, 0.0)
+        assertEquals(1, generator.getTxPosition(13))
+        assertTrue(generator.compute()!!.fromCache)
+    }
+
+    @Test
+    fun untrackedUidHasNoPreview() {
+        assertNull(packed().previewAcceleration(99, 900))
+    }
+
+    @Test(timeout = 10_000)
+    fun previewWaitsForARunInFlightAndReadsOneState() {
+        val generator = packed()
+        generator.applyUpdates(newTxs = listOf(tx(13, fee = 5_000)))
+        val inRun = CountDownLatch(1)
+        val released = CountDownLatch(1)
+        generator.afterSnapshot = {
+            generator.afterSnapshot = null
+            inRun.countDown()
+            released.await()
+        }
+        val runner = thread { generator.compute() }
+        assertTrue(inRun.await(5, TimeUnit.SECONDS))
+
+        var preview: AccelerationPreview? = null
+        val previewer = thread { preview = generator.previewAcceleration(13, 0) }
+        previewer.join(100)
+        assertTrue(previewer.isAlive)
+        released.countDown()
+        runner.join()
+        previewer.join()
+
+        // Both sides describe the replaced transaction the run packed, not the position before it.
+        assertEquals(0, preview!!.currentBlock)
+        assertEquals(0, preview!!.newBlock)
+    }
+}
@@ -0,0 +1,45 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertFalse
+import org.junit.Test
+
+class GbtAncestorScoreTest {
+    private fun rows(result: GbtResult) = result.rates.associate { it[0].toInt() to it.drop(1) }
+
+    @Test
+    fun tinyChildDoesNotDragAHugeParentIntoBlockZero() {
+        // 19 fillers at 5 sat/vB leave 20,000 WU of block 0: room for the 150 vB child at
+        // 200 sat/vB, not for it together with its 50 kvB parent at 1 sat/vB.
+        val fillers = (1..19).map { tx(it, fee = 25_000, weight = 20_000) }
+        val parent = tx(20, fee = 50_000, weight = 200_000)
+        val child = tx(21, fee = 30_000, weight = 600, inputs = intArrayOf(20))
+
+        val result = generator(maxBlockWeight = 400_000).make(fillers + parent + child, maxUid = 21)!!
+
+        val positions = positions(result)
+        (1..19).forEach { assertEquals(0, positions[it]) }
+        assertEquals(1, positions[20])
+        assertEquals(1, positions[21])
+        // Both packed at the ancestor-set rate, (50,000 + 30,000) / 50,150 vB.
+        assertEquals(listOf(1.0, 80_000.0 / 50_150), rows(result)[20])
+        assertEquals(listOf(200.0, 80_000.0 / 50_150), rows(result)[21])
+    }
+
+    @Test
+    fun remainingChildIsRescoredOnceItsParentIsSelectedElsewhere() {
+        // 3 carries the parent at 10 sat/vB; 2 is then left with only itself, at 200 sat/vB.
+        val parent = tx(1, fee = 50_000, weight = 200_000)
+        val small = tx(2, fee = 30_000, weight = 600, inputs = intArrayOf(1))
+        val rich = tx(3, fee = 451_500, weight = 600, inputs = intArrayOf(1))
+
+        val result = generator().make(listOf(parent, small, rich), maxUid = 3)!!
+
+        val rows = rows(result)
+        assertEquals(listOf(1.0, 10.0), rows[1])
+        assertEquals(listOf(3_010.0, 10.0), rows[3])
+        // Packed at its own rate rather than the 1.6 sat/vB of its original ancestor set.
+        assertFalse(2 in rows)
+        assertEquals(0, positions(result)[2])
+    }
+}
@@ -0,0 +1,62 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertNull
+import org.junit.Assert.assertSame
+import org.junit.Test
+
+class GbtAuditStatusTest {
+    @Test
+    fun statusValuesAreFixed() {
+        assertEquals(0, BlockAudit.STATUS_NOT_AUDITED)
+        assertEquals(1, BlockAudit.STATUS_MATCHED)
+        assertEquals(2, BlockAudit.STATUS_MISSING)
+        assertEquals(3, BlockAudit.STATUS_ADDED)
+        assertEquals(4, BlockAudit.STATUS_FRESH)
+        assertEquals(5, BlockAudit.STATUS_UNKNOWN)
+        assertEquals(6, BlockAudit.STATUS_PRIORITIZED)
+        assertEquals(7, BlockAudit.STATUS_ACCELERATED)
+    }
+
+    @Test
+    fun statusesAndSummaryComeFromTheLastAudit() {
+        val generator = generator()
+        generator.make(listOf(tx(1, fee = 1_000, firstSeen = 0), tx(2, fee = 2_000, firstSeen = 0)), maxUid = 2)
+        assertNull(generator.getLastAuditSummary())
+        assertEquals(BlockAudit.STATUS_NOT_AUDITED, generator.getAuditStatus(1))
+
+        val audit = generator.auditBlock(intArrayOf(2, 7), blockTime = 10_000)
+
+        assertSame(audit, generator.getLastAuditSummary())
+        assertEquals(BlockAudit.STATUS_MISSING, generator.getAuditStatus(1))
+        assertEquals(BlockAudit.STATUS_MATCHED, generator.getAuditStatus(2))
+        assertEquals(BlockAudit.STATUS_UNKNOWN, generator.getAuditStatus(7))
+        assertEquals(BlockAudit.STATUS_NOT_AUDITED, generator.getAuditStatus(3))
+    }
+
+    @Test
+    fun nextBlockReplacesTheStoredAudit() {
+        val generator = generator()
+        generator.make(listOf(tx(1, fee = 1_000, firstSeen = 0), tx(2, fee = 2_000, firstSeen = 0)), maxUid = 2)
+        generator.auditBlock(intArrayOf(2, 7), blockTime = 10_000)
+        generator.update(removeTxs = listOf(2), maxUid = 2)
+
+        val next = generator.auditBlock(intArrayOf(1), blockTime = 10_600)
+
+        assertSame(next, generator.getLastAuditSummary())
+        assertEquals(BlockAudit.STATUS_MATCHED, generator.getAuditStatus(1))
+        assertEquals(BlockAudit.STATUS_NOT_AUDITED, generator.getAuditStatus(7))
+    }
+
+    @Test
+    fun resetDropsTheStoredAudit() {
+        val generator = generator()
+        generator.make(listOf(tx(1, fee = 1_000)), maxUid = 1)
+        generator.auditBlock(intArrayOf(1))
+
+        generator.reset()
+
+        assertNull(generator.getLastAuditSummary())
+        assertEquals(BlockAudit.STATUS_NOT_AUDITED, generator.getAuditStatus(1))
+    }
+}
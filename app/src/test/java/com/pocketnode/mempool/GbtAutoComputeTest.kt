@@ -0,0 +1,71 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertTrue
+import org.junit.Test
+import java.util.concurrent.CountDownLatch
+import java.util.concurrent.LinkedBlockingQueue
+import java.util.concurrent.TimeUnit
+
+class GbtAutoComputeTest {
+    @Test(timeout = 10_000)
+    fun coalescesUpdatesIntoOneRun() {
+        val generator = generator()
+        generator.make(listOf(tx(1, fee = 1_000)), maxUid = 1)
+        val results = LinkedBlockingQueue<GbtResult>()
+        // Long enough that all three batches land before the first check.
+        generator.startAutoCompute(500) { results.add(it) }
+
+        generator.applyUpdates(newTxs = listOf(tx(2, fee = 1_000)))
+        generator.applyUpdates(newTxs = listOf(tx(2, fee = 1_000), tx(3, fee = 1_000)))
+        generator.applyUpdates(newTxs = listOf(tx(2, fee = 1_000), tx(3, fee = 1_000), tx(4, fee = 1_000)))
+
+        val result = results.poll(5, TimeUnit.SECONDS)!!
+        assertEquals(4, result.blocks[0].size)
+        assertEquals(generator.getChangeCounter(), result.changeCounter)
+        generator.stopAutoCompute()
+        assertTrue(results.isEmpty())
+    }
+
+    @Test(timeout = 10_000)
+    fun restartWhileTheListenerStopsTheSchedulerDoesNotDeadlock() {
+        val generator = generator()
+        generator.make(listOf(tx(1, fee = 1_000)), maxUid = 1)
+        val inListener = CountDownLatch(1)
+        val release = CountDownLatch(1)
+        generator.startAutoCompute(10) {
+            inListener.countDown()
+            // Not interruptible, so the restart's interrupt doesn't cut this short.
+            while (release.count > 0) Thread.onSpinWait()
+            generator.stopAutoCompute()
+        }
+        generator.applyUpdates(newTxs = listOf(tx(2, fee = 1_000)))
+        assertTrue(inListener.await(5, TimeUnit.SECONDS))
+
+        // Joins the old worker, whose listener can only finish stopAutoCompute without the lock held.
+        val restarter = Thread { generator.startAutoCompute(10) { } }
+        restarter.start()
+        while (restarter.state != Thread.State.WAITING) Thread.sleep(1)
+        release.countDown()
+        restarter.join()
+
+        generator.stopAutoCompute()
+    }
+
+    @Test(timeout = 10_000)
+    fun stopFromTheListenerEndsTheWorker() {
+        val generator = generator()
+        generator.make(listOf(tx(1, fee = 1_000)), maxUid = 1)
+        val stopped = CountDownLatch(1)
+        generator.startAutoCompute(10) {
+            generator.stopAutoCompute()
+            stopped.countDown()
+        }
+        generator.applyUpdates(newTxs = listOf(tx(2, fee = 1_000)))
+
+        assertTrue(stopped.await(5, TimeUnit.SECONDS))
+        generator.applyUpdates(newTxs = listOf(tx(3, fee = 1_000)))
+        Thread.sleep(100)
+        assertEquals(1L, generator.pendingChangeCount())
+    }
+}
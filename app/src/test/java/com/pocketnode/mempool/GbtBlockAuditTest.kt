@@ -0,0 +1,105 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Test
+
+class GbtBlockAuditTest {
+    private val blockTime = 1_700_000_000L
+
+    /**
+     * Uids 1..20 at 10 sat/vB per uid, so block 0 is 20..9 with a 90 sat/vB cutoff, and 21,
+     * seen just before the block, at 85 sat/vB.
+     */
+    private fun projected(accelerations: List<ThreadAcceleration> = emptyList()): GbtGenerator =
+        generator(maxBlockWeight = GbtGenerator.MIN_BLOCK_WEIGHT).apply {
+            make(
+                (1..20).map { tx(it, fee = 1_000L * it, firstSeen = blockTime - 3_600) } +
+                    tx(21, fee = 8_500, firstSeen = blockTime - 10),
+                accelerations,
+                maxUid = 21
+            )
+        }
+
+    @Test
+    fun swappedTransactionsLandInEveryCategory() {
+        val generator = projected()
+        // 9 and 10 swapped out for 8, the fresh 21, and 50 which we never saw.
+        val mined = (20 downTo 11).toList().toIntArray() + intArrayOf(8, 21, 50)
+
+        val audit = generator.auditBlock(mined, blockTime = blockTime, freshWindowSeconds = 60)
+
+        assertArrayEquals(intArrayOf(9, 10), audit.missingUids.sortedArray())
+        assertArrayEquals(intArrayOf(8, 21, 50), audit.addedUids)
+        assertArrayEquals(intArrayOf(21), audit.freshUids)
+        assertArrayEquals(intArrayOf(50), audit.unknownUids)
+        // 10 of the 12 known mined transactions, all 400 WU, were projected.
+        assertEquals(4_000.0 * 100 / 4_800, audit.matchRate, 1e-9)
+    }
+
+    @Test
+    fun acceleratedLowFeeTransactionIsNotCalledAdded() {
+        // 1 stays far below block 0 even with its bump.
+        val generator = projected(listOf(ThreadAcceleration(1, 2_000)))
+        val mined = (20 downTo 10).toList().toIntArray() + intArrayOf(1)
+
+        val audit = generator.auditBlock(mined, blockTime = blockTime)
+
+        assertArrayEquals(intArrayOf(1), audit.acceleratedUids)
+        assertArrayEquals(intArrayOf(), audit.addedUids)
+        assertArrayEquals(intArrayOf(), audit.prioritizedUids)
+        assertEquals(BlockAudit.STATUS_ACCELERATED, generator.getAuditStatus(1))
+    }
+
+    @Test
+    fun minedFarBelowTheCutoffIsPrioritized() {
+        val generator = projected()
+        // 2 pays 20 sat/vB against a 90 sat/vB cutoff; 8 at 80 sat/vB is close enough to count as added.
+        val mined = (20 downTo 11).toList().toIntArray() + intArrayOf(8, 2)
+
+        val audit = generator.auditBlock(mined, blockTime = blockTime)
+
+        assertArrayEquals(intArrayOf(2), audit.prioritizedUids)
+        assertArrayEquals(intArrayOf(8), audit.addedUids)
+        assertArrayEquals(intArrayOf(), audit.acceleratedUids)
+        assertEquals(BlockAudit.STATUS_PRIORITIZED, generator.getAuditStatus(2))
+    }
+
+    @Test
+    fun totalsMatchHandComputedValues() {
+        val generator = generator()
+        generator.make(
+            listOf(tx(1, fee = 1_000, weight = 400), tx(2, fee = 2_500, weight = 800), tx(3, fee = 300, weight = 1_200)),
+            // Template fees are the transactions' own, without the bump.
+            listOf(ThreadAcceleration(3, 5_000)),
+            maxUid = 3
+        )
+
+        val audit = generator.auditBlock(intArrayOf(1, 3, 9), blockTime = blockTime)
+
+        assertEquals(3_800L, audit.expectedFees)
+        assertEquals(2_400L, audit.expectedWeight)
+        assertEquals(1_300L, audit.actualKnownFees)
+        assertEquals(1_600L, audit.actualKnownWeight)
+        assertEquals(1, audit.unknownTxCount)
+    }
+
+    @Test
+    fun exactMatchHasNothingToReport() {
+        val generator = projected()
+        val block0 = generator.compute()!!.blocks[0]
+
+        val audit = generator.auditBlock(block0, blockTime = blockTime)
+
+        assertEquals(100.0, audit.matchRate, 0.0)
+        assertEquals(0, audit.missingUids.size + audit.addedUids.size + audit.unknownUids.size)
+    }
+
+    @Test
+    fun emptyBlockMissesTheWholeProjection() {
+        val audit = projected().auditBlock(intArrayOf(), blockTime = blockTime)
+
+        assertEquals(0.0, audit.matchRate, 0.0)
+        assertEquals(12, audit.missingUids.size)
+    }
+}
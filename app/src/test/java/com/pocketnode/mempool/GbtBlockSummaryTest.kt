@@ -0,0 +1,56 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertTrue
+import org.junit.Test
+
+class GbtBlockSummaryTest {
+    /** Odd weights, so per-transaction vsize rounding shows up in the totals. */
+    private val mempool = (1..40).map { uid ->
+        tx(uid, fee = 100L * uid, weight = 301 + 7 * uid, inputs = if (uid % 6 == 0) intArrayOf(uid - 2) else intArrayOf())
+    }
+
+    private fun checkSummaries(result: GbtResult, byUid: Map<Int, ThreadTransaction>) {
+        assertEquals(result.blocks.size, result.blockTxCounts.size)
+        assertEquals(result.blocks.size, result.blockVsizes.size)
+        for ((i, block) in result.blocks.withIndex()) {
+            assertEquals(block.size, result.blockTxCounts[i])
+            assertEquals(block.sumOf { byUid.getValue(it).weight }, result.blockWeights[i])
+            // Summed per transaction, each rounded up, like Core's vsize.
+            assertEquals(block.sumOf { (byUid.getValue(it).weight + 3) / 4 }, result.blockVsizes[i])
+            assertTrue(result.blockVsizes[i] * 4 >= result.blockWeights[i])
+        }
+    }
+
+    @Test
+    fun summariesMatchTheBlocks() {
+        val result = generator(maxBlockWeight = 10_000).make(mempool, maxUid = 40)!!
+
+        checkSummaries(result, mempool.associateBy { it.uid })
+    }
+
+    @Test
+    fun summariesDontNeedTheOtherSections() {
+        val generator = generator(maxBlockWeight = 10_000)
+        generator.make(mempool, maxUid = 40)
+        generator.applyUpdates(newTxs = listOf(tx(41, fee = 9_000, weight = 561)))
+
+        val slim = generator.compute(detail = GbtResult.DETAIL_BLOCKS or GbtResult.DETAIL_WEIGHTS)!!
+
+        checkSummaries(slim, (mempool + tx(41, fee = 9_000, weight = 561)).associateBy { it.uid })
+        assertEquals(0, slim.clusters.size)
+        assertEquals(0, slim.rates.size)
+    }
+
+    @Test
+    fun emptyBlockZero() {
+        val generator = generator()
+        generator.make(listOf(tx(1, fee = 100)), maxUid = 1)
+
+        val result = generator.update(removeTxs = listOf(1), maxUid = 1)!!
+
+        assertArrayEquals(intArrayOf(0), result.blockTxCounts)
+        assertArrayEquals(intArrayOf(0), result.blockVsizes)
+    }
+}
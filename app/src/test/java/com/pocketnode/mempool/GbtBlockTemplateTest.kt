@@ -0,0 +1,114 @@
+package com.pocketnode.mempool
+
+import android.content.ComponentCallbacks2
+import org.json.JSONObject
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertNull
+import org.junit.Assert.assertTrue
+import org.junit.Assert.fail
+import org.junit.Test
+
+class GbtBlockTemplateTest {
+    private fun txid(uid: Int) = "%064x".format(uid)
+
+    /**
+     * Checks [json] against the fields of Bitcoin Core's getblocktemplate "transactions" entries
+     * that the generator can fill: 64-hex txid, depends as 1-based positions of earlier entries,
+     * and integer fee, sigops and weight, with the top-level totals matching them.
+     */
+    private fun assertTemplateSchema(json: JSONObject) {
+        val transactions = json.getJSONArray("transactions")
+        var fees = 0L
+        var sigops = 0
+        var weight = 0
+        for (i in 0 until transactions.length()) {
+            val entry = transactions.getJSONObject(i)
+            assertEquals(setOf("txid", "depends", "fee", "sigops", "weight"), entry.keySet())
+            assertTrue(entry.getString("txid").matches(Regex("[0-9a-f]{64}")))
+            val depends = entry.getJSONArray("depends")
+            for (j in 0 until depends.length()) assertTrue(depends.getInt(j) in 1..i)
+            fees += entry.getLong("fee")
+            sigops += entry.getInt("sigops")
+            weight += entry.getInt("weight")
+        }
+        assertEquals(fees, json.getLong("fees"))
+        assertEquals(sigops, json.getInt("sigops"))
+        assertEquals(weight, json.getInt("weight"))
+    }
+
+    @Test
+    fun blockZeroInGetblocktemplateShape() {
+        val generator = generator()
+        generator.make(
+            listOf(
+                tx(1, fee = 100, sigops = 1, txid = txid(1)),
+                tx(2, fee = 5_000, weight = 600, sigops = 2, inputs = intArrayOf(1), txid = txid(2)),
+                tx(3, fee = 3_000, txid = txid(3)),
+                tx(4, fee = 900, inputs = intArrayOf(1, 3, 77), txid = txid(4))
+            ),
+            // Template fees are the transactions' own.
+            listOf(ThreadAcceleration(3, 10_000)),
+            maxUid = 4
+        )
+
+        val json = JSONObject(generator.getBlockTemplate()!!)
+
+        assertTemplateSchema(json)
+        val transactions = json.getJSONArray("transactions")
+        val order = (0 until transactions.length()).map { transactions.getJSONObject(it).getString("txid") }
+        assertEquals(listOf(txid(3), txid(1), txid(2), txid(4)), order)
+        // 4 depends on 1 and 3 (positions 2 and 1); 77 isn't in the mempool.
+        val depends = transactions.getJSONObject(3).getJSONArray("depends")
+        assertArrayEquals(intArrayOf(1, 2), IntArray(depends.length()) { depends.getInt(it) })
+        assertEquals(3_000L, transactions.getJSONObject(0).getLong("fee"))
+        assertEquals(9_000L, json.getLong("fees"))
+        assertEquals(1_800, json.getInt("weight"))
+    }
+
+    @Test
+    fun missingTxidFailsWithTheUid() {
+        val generator = generator()
+        generator.make(listOf(tx(1, fee = 100, txid = txid(1)), tx(2, fee = 200)), maxUid = 2)
+
+        try {
+            generator.getBlockTemplate()
+            fail()
+        } catch (e: IllegalStateException) {
+            assertTrue(e.message!!.contains("[2]"))
+        }
+    }
+
+    @Test
+    fun templateSurvivesDroppingTheRemovedCache() {
+        val generator = generator()
+        val mempool = listOf(tx(1, fee = 1_000, txid = txid(1)), tx(2, fee = 500, txid = txid(2), inputs = intArrayOf(1)))
+        generator.make(mempool, maxUid = 2)
+        val before = generator.getBlockTemplate()
+
+        // Mined after the run, and the removed cache that still described them is dropped.
+        generator.applyUpdates(removeTxs = listOf(1, 2))
+        generator.trimMemory(ComponentCallbacks2.TRIM_MEMORY_RUNNING_LOW)
+
+        val after = generator.getBlockTemplate()!!
+        assertEquals(before, after)
+        assertEquals(2, JSONObject(after).getJSONArray("transactions").length())
+        assertEquals(1_500L, JSONObject(after).getLong("fees"))
+    }
+
+    @Test
+    fun templateIsUnaffectedByAZeroSizedRemovedCache() {
+        val generator = GbtGenerator.create(GbtConfig(removedCacheSize = 0))
+        generator.make(listOf(tx(1, fee = 1_000, txid = txid(1))), maxUid = 1)
+
+        generator.applyUpdates(removeTxs = listOf(1))
+
+        val transactions = JSONObject(generator.getBlockTemplate()!!).getJSONArray("transactions")
+        assertEquals(txid(1), transactions.getJSONObject(0).getString("txid"))
+    }
+
+    @Test
+    fun nullBeforeTheFirstRun() {
+        assertNull(generator().getBlockTemplate())
+    }
+}
@@ -0,0 +1,69 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertFalse
+import org.junit.Assert.assertTrue
+import org.junit.Test
+
+class GbtBlockWeightLimitTest {
+    private val limit = GbtGenerator.MIN_BLOCK_WEIGHT
+
+    /**
+     * 1 fills 4,000 WU of block 0; the CPFP package of 2 (500 WU, 0.8 sat/vB) and 3 (paying
+     * for both) comes next with [childWeight], so the package totals 500 + [childWeight] WU.
+     */
+    private fun packed(childWeight: Int): GbtResult {
+        val mempool = listOf(
+            tx(1, fee = 1_000_000, weight = 4_000),
+            tx(2, fee = 100, weight = 500),
+            tx(3, fee = 50_000, weight = childWeight, inputs = intArrayOf(2))
+        )
+        val result = generator(maxBlockWeight = limit).make(mempool, maxUid = 3)!!
+        val byUid = mempool.associateBy { it.uid }
+        for ((i, block) in result.blocks.withIndex()) {
+            assertEquals(block.sumOf { byUid.getValue(it).weight }, result.blockWeights[i])
+            assertTrue(result.blockWeights[i] <= limit)
+        }
+        return result
+    }
+
+    @Test
+    fun packageLandingExactlyOnTheLimitFits() {
+        val result = packed(childWeight = 500)
+
+        assertArrayEquals(intArrayOf(1, 2, 3), result.blocks[0])
+        assertEquals(limit, result.blockWeights[0])
+    }
+
+    @Test
+    fun packageOneUnderTheLimitFits() {
+        val result = packed(childWeight = 499)
+
+        assertArrayEquals(intArrayOf(1, 2, 3), result.blocks[0])
+        assertEquals(limit - 1, result.blockWeights[0])
+    }
+
+    @Test
+    fun packageOneOverTheLimitMovesToTheNextBlock() {
+        val result = packed(childWeight = 501)
+
+        // The child isn't squeezed in; the parent may still go on its own merit.
+        assertFalse(3 in result.blocks[0])
+        assertTrue(3 in result.blocks[1])
+        val parentBlock = result.blocks.indexOfFirst { 2 in it }
+        assertTrue(parentBlock == 0 || result.blocks[1].indexOf(2) < result.blocks[1].indexOf(3))
+    }
+
+    @Test
+    fun transactionsAtTheLimitEachGetTheirOwnBlock() {
+        val result = generator(maxBlockWeight = limit).make(
+            listOf(tx(1, fee = 3_000, weight = limit), tx(2, fee = 2_000, weight = limit), tx(3, fee = 1, weight = 4)),
+            maxUid = 3
+        )!!
+
+        assertArrayEquals(intArrayOf(1), result.blocks[0])
+        assertArrayEquals(intArrayOf(2), result.blocks[1])
+        assertArrayEquals(intArrayOf(limit, limit, 4), result.blockWeights)
+    }
+}
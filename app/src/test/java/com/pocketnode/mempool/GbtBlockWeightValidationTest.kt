@@ -0,0 +1,61 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertNotNull
+import org.junit.Assert.fail
+import org.junit.Test
+
+class GbtBlockWeightValidationTest {
+    private fun assertRejected(weight: Int, call: () -> Unit) {
+        try {
+            call()
+            fail("$weight accepted")
+        } catch (e: IllegalArgumentException) {
+            assertEquals("maxBlockWeight $weight WU outside 5000..4000000", e.message)
+        }
+    }
+
+    private fun tracked() = generator().apply { make((1..3).map { tx(it, fee = 1_000) }, maxUid = 3) }
+
+    @Test
+    fun creationBoundaries() {
+        assertRejected(4_999) { GbtGenerator.create(4_999, 1) }
+        assertEquals(GbtGenerator.MIN_BLOCK_WEIGHT, GbtGenerator.create(5_000, 1).getConfig().maxBlockWeight)
+        assertEquals(GbtGenerator.MAX_BLOCK_WEIGHT, GbtGenerator.create(4_000_000, 1).getConfig().maxBlockWeight)
+        assertRejected(4_000_001) { GbtGenerator.create(4_000_001, 1) }
+        assertRejected(-1) { GbtGenerator.create(-1, 1) }
+    }
+
+    @Test
+    fun vbytesLookalikeIsAcceptedWithAWarning() {
+        // 1,000,000 is a full block in vbytes; accepted, only logged.
+        assertEquals(1_000_000, GbtGenerator.create(1_000_000, 1).getConfig().maxBlockWeight)
+    }
+
+    @Test
+    fun overrideBoundaries() {
+        val generator = tracked()
+
+        assertRejected(4_999) { generator.compute(maxBlockWeightOverride = 4_999) }
+        assertRejected(4_000_001) { generator.compute(maxBlockWeightOverride = 4_000_001) }
+        assertNotNull(generator.compute(maxBlockWeightOverride = 5_000))
+        assertNotNull(generator.compute(maxBlockWeightOverride = 4_000_000))
+    }
+
+    @Test
+    fun simulateBoundaries() {
+        val generator = tracked()
+
+        assertRejected(4_999) { generator.simulate(4_999, 1) }
+        assertRejected(4_000_001) { generator.simulate(4_000_001, 1) }
+        assertEquals(3, generator.simulate(5_000, 1)!!.blocks[0].size)
+    }
+
+    @Test
+    fun computeMultiChecksEveryWeight() {
+        val generator = tracked()
+
+        assertRejected(4_000) { generator.computeMulti(intArrayOf(4_000_000, 4_000)) }
+        assertEquals(2, generator.computeMulti(intArrayOf(5_000, 4_000_000)).size)
+    }
+}
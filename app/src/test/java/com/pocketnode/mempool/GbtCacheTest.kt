@@ -0,0 +1,114 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertFalse
+import org.junit.Assert.assertTrue
+import org.junit.Before
+import org.junit.Test
+
+class GbtCacheTest {
+    private lateinit var generator: GbtGenerator
+
+    @Before
+    fun setUp() {
+        generator = generator()
+        generator.make(listOf(tx(1, fee = 1_000), tx(2, fee = 2_000, inputs = intArrayOf(1)), tx(3, fee = 500)), maxUid = 3)
+    }
+
+    private fun assertRecomputed() {
+        assertTrue("counter not bumped", generator.pendingChangeCount() > 0)
+        assertFalse(generator.compute()!!.fromCache)
+        assertTrue(generator.compute()!!.fromCache)
+    }
+
+    @Test
+    fun unchangedStateIsServedFromCache() {
+        assertTrue(generator.compute()!!.fromCache)
+        assertTrue(generator.update(maxUid = 3)!!.fromCache)
+    }
+
+    @Test
+    fun identicalReinsertKeepsTheCache() {
+        generator.applyUpdates(newTxs = listOf(tx(3, fee = 500)))
+        assertTrue(generator.compute()!!.fromCache)
+    }
+
+    @Test
+    fun insertInvalidates() {
+        generator.applyUpdates(newTxs = listOf(tx(4, fee = 100)))
+        assertRecomputed()
+    }
+
+    @Test
+    fun replacementInvalidates() {
+        generator.applyUpdates(newTxs = listOf(tx(3, fee = 600)))
+        assertRecomputed()
+    }
+
+    @Test
+    fun removalInvalidates() {
+        generator.applyUpdates(removeTxs = listOf(3))
+        assertRecomputed()
+    }
+
+    @Test
+    fun removingAnUnknownUidKeepsTheCache() {
+        generator.applyUpdates(removeTxs = listOf(99))
+        assertTrue(generator.compute()!!.fromCache)
+    }
+
+    @Test
+    fun accelerationChangeInvalidates() {
+        generator.applyUpdates(accelerations = listOf(ThreadAcceleration(3, 10_000)))
+        assertRecomputed()
+        generator.applyUpdates(accelerations = listOf(ThreadAcceleration(3, 10_000)))
+        assertTrue(generator.compute()!!.fromCache)
+        generator.applyUpdates()
+        assertRecomputed()
+    }
+
+    @Test
+    fun removeWithDescendantsInvalidates() {
+        generator.removeWithDescendants(intArrayOf(1))
+        assertRecomputed()
+    }
+
+    @Test
+    fun removeByTxidInvalidates() {
+        generator.make(listOf(tx(1, fee = 1_000, txid = "aa"), tx(2, fee = 500, txid = "bb")), maxUid = 2)
+        generator.compute()
+        generator.removeByTxid(listOf("bb"))
+        assertRecomputed()
+    }
+
+    @Test
+    fun reinstateInvalidates() {
+        generator.update(removeTxs = listOf(3), maxUid = 3)
+        generator.reinstate(intArrayOf(3))
+        assertRecomputed()
+    }
+
+    @Test
+    fun resetInvalidates() {
+        generator.reset()
+        assertRecomputed()
+    }
+
+    @Test
+    fun makeInvalidatesEvenWithTheSameMempool() {
+        generator.applyMake(listOf(tx(1, fee = 1_000), tx(3, fee = 500)), emptyList())
+        assertRecomputed()
+    }
+
+    @Test
+    fun sizeCapEvictionInvalidates() {
+        val capped = GbtGenerator.create(GbtConfig(maxBlockWeight = 4_000_000, maxBlocks = 0, maxTxCount = 2))
+        capped.make(listOf(tx(1, fee = 1_000), tx(2, fee = 2_000)), maxUid = 2)
+        val before = capped.getChangeCounter()
+        capped.applyUpdates(newTxs = listOf(tx(3, fee = 3_000)))
+        assertTrue(capped.getEvictedSinceLastCall().contentEquals(intArrayOf(1)))
+        // One bump for the insert, one for the eviction.
+        assertEquals(before + 2, capped.getChangeCounter())
+        assertFalse(capped.compute()!!.fromCache)
+    }
+}
@@ -0,0 +1,177 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Assert.fail
+import org.junit.Test
+import java.io.ByteArrayOutputStream
+import java.io.DataOutputStream
+import java.io.File
+
+class GbtCallLimitsTest {
+    private val limits = GbtConfig(maxTxsPerCall = 3, maxInputsPerTx = 2, maxAccelerations = 1)
+
+    private fun limited() = GbtGenerator.create(limits).apply { setSeed(1L) }
+
+    private fun mempool(count: Int) = (1..count).map { tx(it, fee = 100L * it) }
+
+    private inline fun assertRejected(message: String, block: () -> Unit) {
+        try {
+            block()
+            fail()
+        } catch (e: IllegalArgumentException) {
+            assertEquals(message, e.message)
+        }
+    }
+
+    /** Header and [limits], followed by [body]. */
+    private fun recording(body: DataOutputStream.() -> Unit): File {
+        val bytes = ByteArrayOutputStream()
+        DataOutputStream(bytes).apply {
+            writeInt(GbtRecorder.MAGIC)
+            writeInt(GbtRecorder.VERSION)
+            GbtRecorder.writeConfig(this, limits)
+            body()
+        }
+        return File.createTempFile("gbt", ".rec").apply {
+            deleteOnExit()
+            writeBytes(bytes.toByteArray())
+        }
+    }
+
+    private fun DataOutputStream.writeMake(txs: List<ThreadTransaction>, accelerations: List<ThreadAcceleration>) {
+        writeByte(GbtRecorder.RECORD_MAKE)
+        writeInt(txs.size)
+        txs.forEach { GbtRecorder.writeTransaction(this, it) }
+        writeInt(accelerations.size)
+        accelerations.forEach {
+            writeInt(it.uid)
+            writeLong(it.delta)
+        }
+    }
+
+    private fun assertReplayRejected(message: String, file: File) {
+        try {
+            GbtReplay.replayFile(file)
+            fail()
+        } catch (e: GbtFormatException) {
+            assertEquals(message, e.message)
+        }
+    }
+
+    @Test
+    fun capsAreReadBackFromTheConfig() {
+        val config = limited().getConfig()
+        assertEquals(3, config.maxTxsPerCall)
+        assertEquals(2, config.maxInputsPerTx)
+        assertEquals(1, config.maxAccelerations)
+
+        val defaults = generator().getConfig()
+        assertEquals(GbtConfig.DEFAULT_MAX_TXS_PER_CALL, defaults.maxTxsPerCall)
+        assertEquals(GbtConfig.DEFAULT_MAX_INPUTS_PER_TX, defaults.maxInputsPerTx)
+        assertEquals(GbtConfig.DEFAULT_MAX_ACCELERATIONS, defaults.maxAccelerations)
+    }
+
+    @Test
+    fun nonPositiveCapsAreRejected() {
+        assertRejected("maxTxsPerCall must be positive, got 0") { GbtGenerator.create(GbtConfig(maxTxsPerCall = 0)) }
+        assertRejected("maxInputsPerTx must be positive, got 0") { GbtGenerator.create(GbtConfig(maxInputsPerTx = 0)) }
+        assertRejected("maxAccelerations must be positive, got -1") { GbtGenerator.create(GbtConfig(maxAccelerations = -1)) }
+    }
+
+    @Test
+    fun callsAtEachCapAreAccepted() {
+        val generator = limited()
+        val parents = listOf(tx(1, fee = 100), tx(2, fee = 200), tx(3, fee = 3_000, inputs = intArrayOf(1, 2)))
+        generator.make(parents, listOf(ThreadAcceleration(1, 1_000)), maxUid = 3)!!
+
+        val result = generator.update(newTxs = mempool(6).drop(3), removeTxs = listOf(1, 2, 3), maxUid = 6)!!
+        assertEquals(setOf(4, 5, 6), positions(result).keys)
+    }
+
+    @Test
+    fun callsOverACapAreRejectedWhole() {
+        val generator = limited()
+        assertRejected("4 transactions exceed maxTxsPerCall 3") { generator.make(mempool(4), maxUid = 4) }
+
+        generator.make(mempool(3), maxUid = 3)!!
+        assertRejected("4 transactions exceed maxTxsPerCall 3") { generator.update(newTxs = mempool(8).drop(4), maxUid = 8) }
+        assertRejected("4 removals exceed maxTxsPerCall 3") { generator.update(removeTxs = listOf(1, 2, 3, 4), maxUid = 3) }
+        val accelerations = listOf(ThreadAcceleration(1, 500), ThreadAcceleration(2, 500))
+        assertRejected("2 accelerations exceed maxAccelerations 1") { generator.update(accelerations = accelerations, maxUid = 3) }
+
+        assertEquals(setOf(1, 2, 3), generator.trackedTransactions().keys)
+    }
+
+    @Test
+    fun reinstatingOverTheCapIsRejected() {
+        val generator = limited()
+        generator.make(mempool(3), maxUid = 3)!!
+        generator.update(removeTxs = listOf(1, 2, 3), maxUid = 3)
+
+        assertRejected("4 uids exceed maxTxsPerCall 3") { generator.reinstate(intArrayOf(1, 2, 3, 4)) }
+        assertEquals(emptySet<Int>(), generator.trackedTransactions().keys)
+        assertArrayEquals(IntArray(0), generator.reinstate(intArrayOf(1, 2, 3)))
+        assertEquals(setOf(1, 2, 3), generator.trackedTransactions().keys)
+    }
+
+    @Test
+    fun removingTxidsOverTheCapIsRejected() {
+        val generator = limited()
+        generator.make((1..3).map { tx(it, fee = 100, txid = "t$it") }, maxUid = 3)!!
+
+        assertRejected("4 txids exceed maxTxsPerCall 3") { generator.removeByTxid(listOf("t1", "t2", "t3", "t4")) }
+        assertEquals(setOf(1, 2, 3), generator.trackedTransactions().keys)
+        assertEquals(3, generator.removeByTxid(listOf("t1", "t2", "t3")).removedUids.size)
+    }
+
+    @Test
+    fun transactionWithTooManyInputsIsNamed() {
+        val generator = limited()
+        generator.make(mempool(3), maxUid = 3)!!
+        try {
+            generator.update(newTxs = listOf(tx(4, fee = 100, inputs = intArrayOf(1, 2, 3))), maxUid = 4)
+            fail()
+        } catch (e: GbtInvalidInputException) {
+            assertEquals(4, e.uid)
+            assertEquals("3 inputs exceed maxInputsPerTx 2", e.reason)
+        }
+        assertEquals(setOf(1, 2, 3), generator.trackedTransactions().keys)
+    }
+
+    @Test
+    fun recordingAtEachCapReplays() {
+        val file = recording {
+            writeMake(
+                listOf(tx(1, fee = 100), tx(2, fee = 200), tx(3, fee = 3_000, inputs = intArrayOf(1, 2))),
+                listOf(ThreadAcceleration(1, 1_000))
+            )
+            writeByte(GbtRecorder.RECORD_COMPUTE)
+        }
+
+        val results = GbtReplay.replayFile(file)
+        assertEquals(1, results.size)
+        assertEquals(setOf(1, 2, 3), positions(results[0]!!).keys)
+    }
+
+    @Test
+    fun recordedLengthsOverACapAreRejected() {
+        assertReplayRejected("Corrupt data: length 4 outside 0..3", recording { writeMake(mempool(4), emptyList()) })
+        assertReplayRejected(
+            "Corrupt data: length 3 outside 0..2",
+            recording { writeMake(listOf(tx(1, fee = 100, inputs = intArrayOf(5, 6, 7))), emptyList()) }
+        )
+        assertReplayRejected(
+            "Corrupt data: length 2 outside 0..1",
+            recording { writeMake(mempool(2), listOf(ThreadAcceleration(1, 500), ThreadAcceleration(2, 500))) }
+        )
+        assertReplayRejected("Corrupt data: length 4 outside 0..3", recording {
+            writeMake(mempool(3), emptyList())
+            writeByte(GbtRecorder.RECORD_UPDATE)
+            writeInt(0)
+            writeInt(4)
+            repeat(4) { writeInt(it + 1) }
+            writeInt(0)
+        })
+    }
+}
@@ -0,0 +1,109 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertNotEquals
+import org.junit.Assert.assertTrue
+import org.junit.Test
+
+class GbtChangeCounterTest {
+    @Test
+    fun updateBumpsAndComputeEmbedsTheCounter() {
+        val generator = generator()
+        val made = generator.make(listOf(tx(1, fee = 1_000)), maxUid = 1)!!
+        assertEquals(generator.getChangeCounter(), made.changeCounter)
+
+        generator.applyUpdates(newTxs = listOf(tx(2, fee = 1_000), tx(3, fee = 1_000)))
+
+        assertEquals(made.changeCounter + 2, generator.getChangeCounter())
+        val computed = generator.compute()!!
+        // Two inserts, then the completed run itself.
+        assertEquals(made.changeCounter + 3, computed.changeCounter)
+        assertEquals(generator.getChangeCounter(), computed.changeCounter)
+        val cached = generator.compute()!!
+        assertTrue(cached.fromCache)
+        assertEquals(computed.changeCounter, cached.changeCounter)
+    }
+
+    @Test
+    fun completedRunTellsOtherPollersAboutIt() {
+        val generator = generator()
+        val seen = generator.make(listOf(tx(1, fee = 1_000)), maxUid = 1)!!.changeCounter
+
+        // Someone else changes the state and computes; a poller that only saw the make notices.
+        generator.applyUpdates(newTxs = listOf(tx(2, fee = 1_000)))
+        generator.compute()
+
+        assertEquals(seen + 2, generator.getChangeCounter())
+        assertEquals(0L, generator.pendingChangeCount())
+        val fetched = generator.compute()!!
+        assertTrue(fetched.fromCache)
+        assertEquals(generator.getChangeCounter(), fetched.changeCounter)
+    }
+
+    @Test
+    fun runOvertakenByAChangeLeavesItAlone() {
+        val generator = generator()
+        generator.make(listOf(tx(1, fee = 1_000)), maxUid = 1)
+        generator.applyUpdates(newTxs = listOf(tx(2, fee = 1_000)))
+        val before = generator.getChangeCounter()
+        generator.afterSnapshot = {
+            generator.afterSnapshot = null
+            generator.applyMake(listOf(tx(3, fee = 1_000)), emptyList())
+        }
+
+        val stale = generator.compute()!!
+
+        assertEquals(before, stale.changeCounter)
+        assertEquals(before + 1, generator.getChangeCounter())
+        assertEquals(1L, generator.pendingChangeCount())
+        assertEquals(setOf(3), positions(generator.compute()!!).keys)
+    }
+
+    @Test
+    fun cachedComputesAndSettingsLeaveItAlone() {
+        val generator = generator()
+        generator.make(listOf(tx(1, fee = 1_000)), maxUid = 1)
+        val counter = generator.getChangeCounter()
+
+        generator.compute()
+        generator.compute(maxBlocksOverride = 1)
+        generator.setWatched(intArrayOf(1))
+        generator.setSeed(9L)
+        generator.applyUpdates(removeTxs = listOf(42))
+
+        assertEquals(counter, generator.getChangeCounter())
+    }
+
+    @Test
+    fun removalsReinstatesAccelerationsAndResetBumpIt() {
+        val generator = generator()
+        generator.make(listOf(tx(1, fee = 1_000), tx(2, fee = 1_000, inputs = intArrayOf(1))), maxUid = 2)
+        var counter = generator.getChangeCounter()
+
+        fun assertBumped(by: Long? = null) {
+            val now = generator.getChangeCounter()
+            if (by != null) assertEquals(counter + by, now) else assertTrue(now > counter)
+            counter = now
+        }
+
+        generator.removeWithDescendants(intArrayOf(1))
+        assertBumped(by = 2)
+        generator.reinstate(intArrayOf(1, 2))
+        assertBumped(by = 2)
+        generator.applyUpdates(accelerations = listOf(ThreadAcceleration(2, 500)))
+        assertBumped()
+        generator.reset()
+        assertBumped()
+    }
+
+    @Test
+    fun staleResultIsDetectedWithoutComputing() {
+        val generator = generator()
+        val last = generator.make(listOf(tx(1, fee = 1_000)), maxUid = 1)!!
+
+        assertEquals(last.changeCounter, generator.getChangeCounter())
+        generator.applyUpdates(newTxs = listOf(tx(2, fee = 1_000)))
+        assertNotEquals(last.changeCounter, generator.getChangeCounter())
+        assertEquals(1L, generator.pendingChangeCount())
+    }
+}
@@ -0,0 +1,83 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertNull
+import org.junit.Assert.assertTrue
+import org.junit.Test
+
+class GbtChildIndexTest {
+    /** Chain 1 <- 2 <- 3 <- 4. */
+    private fun chain(): GbtGenerator = generator().apply {
+        make((1..4).map { tx(it, fee = 1_000, inputs = if (it == 1) intArrayOf() else intArrayOf(it - 1)) }, maxUid = 4)
+    }
+
+    private fun GbtGenerator.descendants(uid: Int) = getDescendants(uid)?.sorted()
+
+    private fun assertIndexMatchesRebuild(generator: GbtGenerator) {
+        val report = generator.verifyState()
+        assertEquals(0, report.childIndexMismatchCount)
+        assertTrue(report.isConsistent)
+    }
+
+    @Test
+    fun removingAMidChainTransactionSplitsTheChain() {
+        val generator = chain()
+
+        generator.applyUpdates(removeTxs = listOf(2))
+
+        assertEquals(listOf<Int>(), generator.descendants(1))
+        assertNull(generator.getDescendants(2))
+        assertEquals(listOf(4), generator.descendants(3))
+        assertIndexMatchesRebuild(generator)
+    }
+
+    @Test
+    fun reinsertingAMidChainTransactionRejoinsTheChain() {
+        val generator = chain()
+        generator.applyUpdates(removeTxs = listOf(2))
+
+        generator.applyUpdates(newTxs = listOf(tx(2, fee = 1_000, inputs = intArrayOf(1))))
+
+        assertEquals(listOf(2, 3, 4), generator.descendants(1))
+        assertEquals(listOf(1, 2, 3), generator.getAncestors(4)!!.sorted())
+        assertIndexMatchesRebuild(generator)
+    }
+
+    @Test
+    fun replacingAMidChainTransactionMovesItsParentLink() {
+        val generator = chain()
+
+        // 3 now spends 1 directly instead of 2.
+        generator.applyUpdates(newTxs = listOf(tx(3, fee = 1_000, inputs = intArrayOf(1))))
+
+        assertEquals(listOf<Int>(), generator.descendants(2))
+        assertEquals(listOf(2, 3, 4), generator.descendants(1))
+        assertEquals(listOf(1), generator.getAncestors(3)!!.toList())
+        assertIndexMatchesRebuild(generator)
+    }
+
+    @Test
+    fun removeAndReinsertInOneBatch() {
+        val generator = chain()
+
+        generator.applyUpdates(newTxs = listOf(tx(3, fee = 2_000, inputs = intArrayOf(2))), removeTxs = listOf(3))
+
+        assertEquals(listOf(3, 4), generator.descendants(2))
+        assertIndexMatchesRebuild(generator)
+    }
+
+    @Test
+    fun makeAndResetRebuildTheIndex() {
+        val generator = chain()
+
+        generator.make(listOf(tx(10, fee = 1_000), tx(11, fee = 1_000, inputs = intArrayOf(10))), maxUid = 11)
+        assertNull(generator.getDescendants(1))
+        assertEquals(listOf(11), generator.descendants(10))
+        assertIndexMatchesRebuild(generator)
+
+        generator.reset()
+        assertIndexMatchesRebuild(generator)
+        generator.applyUpdates(newTxs = listOf(tx(10, fee = 1_000)))
+        assertEquals(listOf<Int>(), generator.descendants(10))
+    }
+}
@@ -0,0 +1,47 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertFalse
+import org.junit.Test
+
+class GbtClusterMappingTest {
+    @Test
+    fun flatMappingMatchesTheJaggedClusters() {
+        val mempool = listOf(
+            // chain 1 <- 2 <- 3
+            tx(1, fee = 1_000), tx(2, fee = 1_000, inputs = intArrayOf(1)), tx(3, fee = 1_000, inputs = intArrayOf(2)),
+            // 4 and 5 joined by a common child 6
+            tx(4, fee = 2_000), tx(5, fee = 2_000), tx(6, fee = 500, inputs = intArrayOf(4, 5)),
+            // singletons
+            tx(7, fee = 3_000), tx(8, fee = 3_000),
+            // parent 9 with two children
+            tx(9, fee = 800), tx(10, fee = 900, inputs = intArrayOf(9)), tx(11, fee = 900, inputs = intArrayOf(9))
+        )
+
+        val result = generator().make(mempool, maxUid = 11)!!
+
+        assertEquals(
+            listOf(listOf(1, 2, 3), listOf(4, 5, 6), listOf(9, 10, 11)),
+            result.clusters.map { it.toList() }
+        )
+        assertEquals(result.clusters.sumOf { it.size }, result.clusterUids.size)
+        assertEquals(result.clusterUids.size, result.clusterIds.size)
+        val inverted = result.clusterUids.indices.groupBy({ result.clusterIds[it] }, { result.clusterUids[it] })
+        for ((id, members) in result.clusters.withIndex()) {
+            assertArrayEquals(members, inverted.getValue(id).sorted().toIntArray())
+        }
+        // Singletons are omitted rather than given a sentinel id.
+        assertFalse(7 in result.clusterUids)
+        assertFalse(8 in result.clusterUids)
+    }
+
+    @Test
+    fun noMultiTxClustersGivesEmptyMapping() {
+        val result = generator().make((1..5).map { tx(it, fee = 100L * it) }, maxUid = 5)!!
+
+        assertEquals(0, result.clusters.size)
+        assertEquals(0, result.clusterUids.size)
+        assertEquals(0, result.clusterIds.size)
+    }
+}
@@ -0,0 +1,51 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Test
+
+class GbtClusterSummaryTest {
+    /** Chain 1 <- 2 <- 3 with 3 accelerated by 1,000 sats; pair 4 <- 5; singleton 6. */
+    private val mempool = listOf(
+        tx(1, fee = 1_000),
+        tx(2, fee = 2_000, weight = 600, inputs = intArrayOf(1)),
+        tx(3, fee = 500, weight = 401, inputs = intArrayOf(2)),
+        tx(4, fee = 300),
+        tx(5, fee = 700, inputs = intArrayOf(4)),
+        tx(6, fee = 9_000)
+    )
+
+    @Test
+    fun summariesAlignWithClusters() {
+        val result = generator().make(mempool, listOf(ThreadAcceleration(3, 1_000)), maxUid = 6)!!
+
+        assertEquals(listOf(listOf(1, 2, 3), listOf(4, 5)), result.clusters.map { it.toList() })
+        // 1,000 + 2,000 + 500 + 1,000 accelerated, over 100 + 150 + 101 vB.
+        assertArrayEquals(longArrayOf(4_500, 1_000), result.clusterFees)
+        assertArrayEquals(intArrayOf(351, 200), result.clusterVsizes)
+        assertArrayEquals(doubleArrayOf(4_500.0 / 351, 5.0), result.clusterRates, 0.0)
+        assertArrayEquals(booleanArrayOf(true, false), result.clusterAccelerated)
+    }
+
+    @Test
+    fun singletonsOnlyGiveEmptySummaries() {
+        val result = generator().make(listOf(tx(1, fee = 1_000), tx(2, fee = 2_000)), listOf(ThreadAcceleration(1, 500)), maxUid = 2)!!
+
+        assertEquals(0, result.clusters.size)
+        assertEquals(0, result.clusterFees.size)
+        assertEquals(0, result.clusterVsizes.size)
+        assertEquals(0, result.clusterRates.size)
+        assertEquals(0, result.clusterAccelerated.size)
+    }
+
+    @Test
+    fun summariesFollowTheClustersDetailFlag() {
+        val generator = generator()
+        generator.make(mempool, maxUid = 6)
+
+        val result = generator.compute(detail = GbtResult.DETAIL_BLOCKS)!!
+
+        assertEquals(0, result.clusterFees.size)
+        assertEquals(0, result.clusterAccelerated.size)
+    }
+}
@@ -0,0 +1,43 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertTrue
+import org.junit.Test
+import java.util.concurrent.CountDownLatch
+import java.util.concurrent.TimeUnit
+import kotlin.concurrent.thread
+
+class GbtConcurrentQueryTest {
+    @Test(timeout = 30_000)
+    fun queriesReturnPromptlyWhileALargeRunIsInFlight() {
+        val count = 50_000
+        val generator = generator()
+        // Chains of four, so the run has real ancestor work to do.
+        val txs = (1..count).map { tx(it, fee = 100L + it % 997, inputs = if (it % 4 == 1) intArrayOf() else intArrayOf(it - 1)) }
+        generator.make(txs, maxUid = count)
+        generator.applyUpdates(newTxs = listOf(tx(count + 1, fee = 1_000)))
+        val inRun = CountDownLatch(1)
+        val released = CountDownLatch(1)
+        generator.afterSnapshot = {
+            generator.afterSnapshot = null
+            inRun.countDown()
+            released.await()
+        }
+        val runner = thread { generator.compute() }
+        assertTrue(inRun.await(10, TimeUnit.SECONDS))
+
+        val started = System.nanoTime()
+        val size = generator.size()
+        val ancestors = generator.getAncestors(4)
+        val counter = generator.getChangeCounter()
+        val elapsedMillis = (System.nanoTime() - started) / 1_000_000
+
+        released.countDown()
+        runner.join()
+        assertEquals(count + 1, size)
+        assertEquals(listOf(1, 2, 3), ancestors!!.sorted())
+        // The only change since is the finished run's own bump.
+        assertEquals(counter + 1, generator.getChangeCounter())
+        assertTrue("queries took $elapsedMillis ms", elapsedMillis < 100)
+    }
+}
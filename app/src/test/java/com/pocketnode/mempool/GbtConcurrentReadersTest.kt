@@ -0,0 +1,71 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertNull
+import org.junit.Assert.assertTrue
+import org.junit.Test
+import java.util.concurrent.atomic.AtomicBoolean
+import java.util.concurrent.atomic.AtomicReference
+import kotlin.concurrent.thread
+
+class GbtConcurrentReadersTest {
+    /** Chain [k] is root 3k+1, spent by 3k+2, spent by 3k+3. */
+    private fun chain(k: Int) = listOf(
+        tx(3 * k + 1, fee = 1_000),
+        tx(3 * k + 2, fee = 2_000, inputs = intArrayOf(3 * k + 1)),
+        tx(3 * k + 3, fee = 3_000, inputs = intArrayOf(3 * k + 2))
+    )
+
+    @Test(timeout = 60_000)
+    fun readersSeeWholeBatchesWhileAWriterApplies() {
+        val live = 10
+        val batches = 300
+        val generator = generator()
+        generator.make((0 until live).flatMap { chain(it) }, maxUid = 3 * live)
+        val done = AtomicBoolean(false)
+        val failure = AtomicReference<Throwable>()
+
+        // Each batch adds one chain and removes the oldest, so a consistent view holds exactly `live` chains.
+        val writer = thread {
+            try {
+                for (k in live until live + batches) {
+                    val oldest = k - live
+                    generator.applyUpdates(newTxs = chain(k), removeTxs = listOf(3 * oldest + 1, 3 * oldest + 2, 3 * oldest + 3))
+                    if (k % 25 == 0) generator.compute()
+                }
+            } catch (t: Throwable) {
+                failure.compareAndSet(null, t)
+            } finally {
+                done.set(true)
+            }
+        }
+        val readers = (0 until 4).map { r ->
+            thread {
+                try {
+                    var k = r
+                    while (!done.get()) {
+                        assertEquals(3 * live, generator.size())
+                        // A chain is either fully tracked or fully gone, never torn.
+                        val ancestors = generator.getAncestors(3 * k + 3)
+                        val descendants = generator.getDescendants(3 * k + 1)
+                        if (ancestors == null) assertNull(descendants)
+                        else {
+                            assertEquals(listOf(3 * k + 2, 3 * k + 1), ancestors.asList())
+                            assertEquals(listOf(3 * k + 2, 3 * k + 3), descendants!!.asList())
+                        }
+                        if (k % 50 == 0) assertEquals(0, generator.verifyState().childIndexMismatchCount)
+                        k = (k + 1) % (live + batches)
+                    }
+                } catch (t: Throwable) {
+                    failure.compareAndSet(null, t)
+                }
+            }
+        }
+
+        writer.join()
+        readers.forEach { it.join() }
+        failure.get()?.let { throw it }
+        assertEquals(3 * live, generator.size())
+        assertTrue(generator.verifyState().isConsistent)
+    }
+}
@@ -0,0 +1,100 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertEquals
+import org.junit.Assert.fail
+import org.junit.Test
+
+class GbtConfigTest {
+    private fun assertRejected(config: GbtConfig, message: String) {
+        try {
+            GbtGenerator.create(config)
+            fail()
+        } catch (e: IllegalArgumentException) {
+            assertEquals(message, e.message)
+        }
+    }
+
+    @Test
+    fun defaultsAreReadBack() {
+        val config = GbtGenerator.create(GbtConfig()).getConfig()
+
+        assertEquals(GbtConfig(), config)
+        assertEquals(GbtConfig.DEFAULT_MAX_BLOCK_WEIGHT, config.maxBlockWeight)
+        assertEquals(GbtConfig.DEFAULT_MAX_BLOCKS, config.maxBlocks)
+        assertEquals(GbtGenerator.DEFAULT_BYTES_PER_SIGOP, config.bytesPerSigop)
+        assertEquals(GbtConfig.ACCEPT_ALL_POLICIES, config.acceptedPolicyMask)
+    }
+
+    @Test
+    fun zeroWeightMeansDefaultAndLargeBlockCountsAreClamped() {
+        val config = GbtGenerator.create(GbtConfig(maxBlockWeight = 0, maxBlocks = 500)).getConfig()
+
+        assertEquals(GbtConfig.DEFAULT_MAX_BLOCK_WEIGHT, config.maxBlockWeight)
+        assertEquals(GbtGenerator.MAX_BLOCKS_LIMIT, config.maxBlocks)
+    }
+
+    @Test
+    fun builderSetsEveryFieldAndReadsBack() {
+        val config = GbtConfig.Builder()
+            .maxBlockWeight(GbtGenerator.MIN_BLOCK_WEIGHT)
+            .maxBlocks(3)
+            .includeTxids(true)
+            .strict(true)
+            .removedCacheSize(10)
+            .maxTxCount(100)
+            .maxMemoryBytes(1_000_000)
+            .deriveRates(true)
+            .bytesPerSigop(0)
+            .acceptedPolicyMask(0)
+            .strategy(GbtSelectionStrategy.values().last())
+            .maxTxsPerCall(50)
+            .maxInputsPerTx(5)
+            .maxAccelerations(7)
+            .build()
+
+        assertEquals(
+            GbtConfig(
+                maxBlockWeight = GbtGenerator.MIN_BLOCK_WEIGHT, maxBlocks = 3, includeTxids = true, strict = true,
+                removedCacheSize = 10, maxTxCount = 100, maxMemoryBytes = 1_000_000, deriveRates = true,
+                bytesPerSigop = 0, acceptedPolicyMask = 0, strategy = GbtSelectionStrategy.values().last(),
+                maxTxsPerCall = 50, maxInputsPerTx = 5, maxAccelerations = 7
+            ),
+            config
+        )
+        assertEquals(config, GbtGenerator.create(config).getConfig())
+    }
+
+    @Test
+    fun shorthandCreateMatchesTheConfig() {
+        val generator = GbtGenerator.create(GbtGenerator.MIN_BLOCK_WEIGHT, 2, strict = true, bytesPerSigop = 0)
+
+        assertEquals(GbtConfig(maxBlockWeight = GbtGenerator.MIN_BLOCK_WEIGHT, maxBlocks = 2, strict = true, bytesPerSigop = 0), generator.getConfig())
+    }
+
+    @Test
+    fun invalidFieldsAreNamed() {
+        assertRejected(GbtConfig(maxBlockWeight = 4_999), "maxBlockWeight 4999 WU outside 5000..4000000")
+        assertRejected(GbtConfig(maxBlockWeight = 4_000_001), "maxBlockWeight 4000001 WU outside 5000..4000000")
+        assertRejected(GbtConfig(maxBlocks = -1), "maxBlocks must not be negative, got -1")
+        assertRejected(GbtConfig(removedCacheSize = -1), "removedCacheSize must not be negative, got -1")
+        assertRejected(GbtConfig(maxTxCount = -1), "maxTxCount must not be negative, got -1")
+        assertRejected(GbtConfig(maxMemoryBytes = -1), "maxMemoryBytes must not be negative, got -1")
+        assertRejected(GbtConfig(bytesPerSigop = -1), "bytesPerSigop must not be negative, got -1")
+        assertRejected(GbtConfig(maxTxsPerCall = 0), "maxTxsPerCall must be positive, got 0")
+        assertRejected(GbtConfig(maxInputsPerTx = 0), "maxInputsPerTx must be positive, got 0")
+        assertRejected(GbtConfig(maxAccelerations = 0), "maxAccelerations must be positive, got 0")
+    }
+
+    @Test
+    fun callCapsAreEnforced() {
+        val generator = GbtGenerator.create(GbtConfig(maxTxsPerCall = 2))
+
+        try {
+            generator.applyUpdates(newTxs = (1..3).map { tx(it, fee = 1_000) })
+            fail()
+        } catch (e: IllegalArgumentException) {
+            assertEquals("3 transactions exceed maxTxsPerCall 2", e.message)
+        }
+        assertEquals(0, generator.size())
+    }
+}
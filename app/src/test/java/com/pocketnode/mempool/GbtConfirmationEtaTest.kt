@@ -0,0 +1,50 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Test
+
+class GbtConfirmationEtaTest {
+    /** 30 transactions, 12 per block, two blocks: uids 30..19 in block 0, 18..7 in block 1, the rest overflow. */
+    private fun started(): GbtGenerator = generator(maxBlockWeight = GbtGenerator.MIN_BLOCK_WEIGHT, maxBlocks = 2).apply {
+        make((1..30).map { tx(it, fee = 100L * it) }, maxUid = 30)
+    }
+
+    @Test
+    fun coversEveryKindOfPosition() {
+        val generator = started()
+
+        val etas = generator.getConfirmationEtas(intArrayOf(30, 10, 3, 99))
+
+        assertArrayEquals(intArrayOf(0, 1, GbtResult.OVERFLOW_BLOCK, GbtResult.UNKNOWN_UID), etas.blocks)
+        assertArrayEquals(doubleArrayOf(30.0, 10.0, 3.0, 0.0), etas.rates, 0.0)
+    }
+
+    @Test
+    fun matchesSingleUidPositions() {
+        val generator = started()
+        val uids = (0..32).toList().toIntArray()
+
+        val etas = generator.getConfirmationEtas(uids)
+
+        for ((i, uid) in uids.withIndex()) assertEquals(generator.getTxPosition(uid), etas.blocks[i])
+    }
+
+    @Test
+    fun reflectsTheLastRunOnly() {
+        val generator = started()
+        generator.applyUpdates(newTxs = listOf(tx(31, fee = 10_000)))
+
+        // Not part of a run yet.
+        assertEquals(GbtResult.UNKNOWN_UID, generator.getConfirmationEtas(intArrayOf(31)).blocks[0])
+
+        generator.compute()
+        assertEquals(0, generator.getConfirmationEtas(intArrayOf(31)).blocks[0])
+    }
+
+    @Test
+    fun emptyQueryAndNoRunYet() {
+        assertEquals(0, started().getConfirmationEtas(intArrayOf()).blocks.size)
+        assertArrayEquals(intArrayOf(GbtResult.UNKNOWN_UID), generator().getConfirmationEtas(intArrayOf(1)).blocks)
+    }
+}
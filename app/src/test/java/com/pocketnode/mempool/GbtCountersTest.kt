@@ -0,0 +1,64 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertTrue
+import org.junit.Test
+
+class GbtCountersTest {
+    private fun GbtGenerator.counter(index: Int) = getCounters()[index]
+
+    @Test
+    fun countersAfterAScriptedSequence() {
+        val generator = generator()
+        val before = System.currentTimeMillis()
+
+        generator.make((1..5).map { tx(it, fee = 1_000L * it) }, maxUid = 5)
+        // Removes one (and an unknown uid), adds three: 4, then 7 tracked.
+        generator.update(newTxs = (6..8).map { tx(it, fee = 500) }, removeTxs = listOf(2, 99), maxUid = 8)
+        // One rejected for its weight, one replacing uid 1 with a new fee.
+        generator.update(newTxs = listOf(tx(9, fee = 500, weight = 0), tx(1, fee = 7_000)), maxUid = 9)
+        // Served from the cache, still counted.
+        generator.compute()
+
+        val counters = generator.getCounters()
+        assertEquals(GbtGenerator.COUNTER_COUNT, counters.size)
+        assertEquals(4L, counters[GbtGenerator.COUNTER_COMPUTES])
+        assertEquals(2L, counters[GbtGenerator.COUNTER_UPDATES])
+        assertEquals(9L, counters[GbtGenerator.COUNTER_TXS_INSERTED])
+        assertEquals(1L, counters[GbtGenerator.COUNTER_TXS_REMOVED])
+        assertEquals(1L, counters[GbtGenerator.COUNTER_TXS_REJECTED])
+        assertEquals(1L, counters[GbtGenerator.COUNTER_TXS_REPLACED])
+        assertEquals(7L, counters[GbtGenerator.COUNTER_MAP_SIZE])
+        assertEquals(7L, counters[GbtGenerator.COUNTER_PEAK_MAP_SIZE])
+        assertTrue(counters[GbtGenerator.COUNTER_COMPUTE_MILLIS] >= 0)
+        assertTrue(counters[GbtGenerator.COUNTER_LAST_COMPUTE_TIME] in before..System.currentTimeMillis())
+    }
+
+    @Test
+    fun peakSurvivesRemovals() {
+        val generator = generator()
+        generator.make((1..6).map { tx(it, fee = 1_000) }, maxUid = 6)
+
+        generator.update(removeTxs = listOf(1, 2, 3, 4), maxUid = 6)
+
+        assertEquals(2L, generator.counter(GbtGenerator.COUNTER_MAP_SIZE))
+        assertEquals(6L, generator.counter(GbtGenerator.COUNTER_PEAK_MAP_SIZE))
+        assertEquals(4L, generator.counter(GbtGenerator.COUNTER_TXS_REMOVED))
+    }
+
+    @Test
+    fun resetKeepsCountersUnlessAsked() {
+        val generator = generator()
+        generator.make((1..3).map { tx(it, fee = 1_000) }, maxUid = 3)
+
+        generator.reset()
+
+        assertEquals(1L, generator.counter(GbtGenerator.COUNTER_COMPUTES))
+        assertEquals(3L, generator.counter(GbtGenerator.COUNTER_TXS_INSERTED))
+        assertEquals(0L, generator.counter(GbtGenerator.COUNTER_MAP_SIZE))
+
+        generator.reset(resetCounters = true)
+
+        assertTrue(generator.getCounters().all { it == 0L })
+    }
+}
@@ -0,0 +1,58 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Test
+
+class GbtCpfpEdgesTest {
+    /** 9 at 5 sat/vB sets block 0's cutoff. */
+    private val filler = tx(9, fee = 500)
+
+    @Test
+    fun lowFeeParentWithHighFeeChildGivesOneEdge() {
+        // Parent 1 at 1 sat/vB, child 2 at 19: in together at 10 sat/vB.
+        val result = generator().make(listOf(tx(1, fee = 100), tx(2, fee = 1_900, inputs = intArrayOf(1)), filler), maxUid = 9)!!
+
+        assertEquals(1, result.cpfpEdges.size)
+        assertArrayEquals(intArrayOf(2, 1), result.cpfpEdges[0])
+    }
+
+    @Test
+    fun parentAboveTheCutoffOnItsOwnGivesNone() {
+        // Parent 1 at 20 sat/vB would have made the block anyway.
+        val result = generator().make(listOf(tx(1, fee = 2_000), tx(2, fee = 3_000, inputs = intArrayOf(1)), filler), maxUid = 9)!!
+
+        assertArrayEquals(intArrayOf(), result.cpfpEdges[0])
+    }
+
+    @Test
+    fun sharedParentIsLinkedOnce() {
+        // Both children would lift parent 1; 2 takes it in, 3 then goes in alone.
+        val mempool = listOf(tx(1, fee = 100), tx(2, fee = 1_900, inputs = intArrayOf(1)), tx(3, fee = 1_500, inputs = intArrayOf(1)), filler)
+
+        val result = generator().make(mempool, maxUid = 9)!!
+
+        assertArrayEquals(intArrayOf(2, 1), result.cpfpEdges[0])
+    }
+
+    @Test
+    fun edgesArePerBlock() {
+        // Block 0 fills with 12 at 50 sat/vB; the pair lands in block 1 with a 3 sat/vB filler.
+        val full = (11..22).map { tx(it, fee = 5_000) }
+        val mempool = full + listOf(tx(1, fee = 100), tx(2, fee = 1_900, inputs = intArrayOf(1)), tx(9, fee = 300))
+
+        val result = generator(maxBlockWeight = GbtGenerator.MIN_BLOCK_WEIGHT).make(mempool, maxUid = 22)!!
+
+        assertEquals(2, result.cpfpEdges.size)
+        assertArrayEquals(intArrayOf(), result.cpfpEdges[0])
+        assertArrayEquals(intArrayOf(2, 1), result.cpfpEdges[1])
+    }
+
+    @Test
+    fun leftOutWithoutTheDetailFlag() {
+        val generator = generator()
+        generator.make(listOf(tx(1, fee = 100), tx(2, fee = 1_900, inputs = intArrayOf(1)), filler), maxUid = 9)
+
+        assertEquals(0, generator.compute(detail = GbtResult.DETAIL_BLOCKS)!!.cpfpEdges.size)
+    }
+}
@@ -0,0 +1,75 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertTrue
+import org.junit.Test
+
+class GbtDepthCurveTest {
+    /** Rates from 1 to 200 sat/vB, a CPFP pair packed at 10 sat/vB and a zero-fee transaction. */
+    private val mempool = (1..200).map { tx(it, fee = 100L * it) } +
+        listOf(tx(201, fee = 100), tx(202, fee = 1_900, inputs = intArrayOf(201)), tx(203, fee = 0))
+
+    @Test
+    fun curveIsMonotonicAndSpansTheRateRange() {
+        val result = generator().make(mempool, maxUid = 203)!!
+        val rates = result.depthCurveRates
+        val vsizes = result.depthCurveVsizes
+
+        // 256 log-spaced points plus the zero-fee point.
+        assertEquals(257, rates.size)
+        assertEquals(rates.size, vsizes.size)
+        assertEquals(200.0, rates.first(), 1e-9)
+        assertEquals(1.0, rates[255], 0.0)
+        assertEquals(0.0, rates.last(), 0.0)
+        for (i in 1 until rates.size) {
+            assertTrue(rates[i] < rates[i - 1])
+            assertTrue(vsizes[i] >= vsizes[i - 1])
+        }
+        assertEquals(mempool.sumOf { weightToVsize(it.weight).toLong() }, vsizes.last())
+    }
+
+    @Test
+    fun sampledPointsAgreeWithTheExactQuery() {
+        val generator = generator()
+        val result = generator.make(mempool, maxUid = 203)!!
+
+        for (i in listOf(0, 1, 37, 128, 200, 255, 256)) {
+            assertEquals("rate ${result.depthCurveRates[i]}", generator.getDepthAtRate(result.depthCurveRates[i]).vsize, result.depthCurveVsizes[i])
+        }
+    }
+
+    @Test
+    fun overflowAboveTheRateIsNotCounted() {
+        val generator = generator(maxBlockWeight = GbtGenerator.MIN_BLOCK_WEIGHT, maxBlocks = 1)
+        // 2 pays 50 sat/vB but can't fit next to 1, so it overflows above the 10 sat/vB filler.
+        val mempool = listOf(tx(1, fee = 10_000), tx(2, fee = 60_000, weight = 4_800), tx(3, fee = 1_000), tx(4, fee = 1_000))
+
+        val result = generator.make(mempool, maxUid = 4)!!
+
+        assertEquals(listOf(2), result.overflow.asList())
+        assertEquals(MempoolDepth(100, 10_000), generator.getDepthAtRate(20.0))
+        assertEquals(300L, generator.getDepthAtRate(1.0).vsize)
+        assertEquals(100.0, result.depthCurveRates.first(), 0.0)
+        assertEquals(100L, result.depthCurveVsizes.first())
+        assertEquals(300L, result.depthCurveVsizes.last())
+    }
+
+    @Test
+    fun singleRateGivesOnePoint() {
+        val result = generator().make((1..3).map { tx(it, fee = 1_000) }, maxUid = 3)!!
+
+        assertEquals(listOf(10.0), result.depthCurveRates.asList())
+        assertEquals(listOf(300L), result.depthCurveVsizes.asList())
+    }
+
+    @Test
+    fun leftOutWithoutTheDetailFlag() {
+        val generator = generator()
+        generator.make(mempool, maxUid = 203)
+
+        val result = generator.compute(detail = GbtResult.DETAIL_BLOCKS)!!
+
+        assertEquals(0, result.depthCurveRates.size)
+        assertEquals(0, result.depthCurveVsizes.size)
+    }
+}
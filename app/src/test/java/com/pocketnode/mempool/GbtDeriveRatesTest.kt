@@ -0,0 +1,57 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Test
+
+class GbtDeriveRatesTest {
+    private fun deriving(derive: Boolean): GbtGenerator =
+        GbtGenerator.create(4_000_000, 0, deriveRates = derive).apply { setSeed(1L) }
+
+    /** 1 really pays 50 sat/vB but claims 1; 2 pays 10 and claims it. */
+    private val mempool = listOf(tx(1, fee = 5_000).copy(effectiveFeePerVsize = 1.0), tx(2, fee = 1_000))
+
+    @Test
+    fun wrongProvidedRateIsIgnoredWhenDeriving() {
+        val result = deriving(true).make(mempool, maxUid = 2)!!
+
+        assertArrayEquals(intArrayOf(1, 2), result.blocks[0])
+        assertEquals(1, result.rateDiscrepancyCount)
+        // Packed at the derived rate, so no rates row for it.
+        assertEquals(0, result.rates.size)
+    }
+
+    @Test
+    fun providedRateIsTrustedOtherwise() {
+        val result = deriving(false).make(mempool, maxUid = 2)!!
+
+        assertArrayEquals(intArrayOf(2, 1), result.blocks[0])
+        assertEquals(0, result.rateDiscrepancyCount)
+    }
+
+    @Test
+    fun derivedRateFeedsCpfpAndAccelerations() {
+        // The child's claimed rate is off too; the package is priced from fees alone.
+        val txs = listOf(
+            tx(1, fee = 100).copy(effectiveFeePerVsize = 80.0),
+            tx(2, fee = 1_900, inputs = intArrayOf(1)).copy(effectiveFeePerVsize = 0.5),
+            tx(3, fee = 1_500)
+        )
+
+        val result = deriving(true).make(txs, listOf(ThreadAcceleration(3, 1_000)), maxUid = 3)!!
+
+        // 3 at (1,500 + 1,000) / 100 = 25 ahead of the 1 + 2 package at 2,000 / 200 = 10.
+        assertArrayEquals(intArrayOf(3, 1, 2), result.blocks[0])
+        assertEquals(2, result.rateDiscrepancyCount)
+    }
+
+    @Test
+    fun discrepanciesAreCountedPerRun() {
+        val generator = deriving(true)
+        generator.make(mempool, maxUid = 2)
+
+        val next = generator.update(newTxs = listOf(tx(3, fee = 300)), maxUid = 3)!!
+
+        assertEquals(0, next.rateDiscrepancyCount)
+    }
+}
@@ -0,0 +1,73 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertNull
+import org.junit.Assert.assertTrue
+import org.junit.Test
+
+class GbtDetailTest {
+    private val mempool = (1..30).map { uid ->
+        tx(uid, fee = 500L + 37L * uid % 400, inputs = if (uid % 3 == 0) intArrayOf(uid - 1) else intArrayOf())
+    }
+
+    private val optional = listOf(
+        GbtResult.DETAIL_CLUSTERS, GbtResult.DETAIL_RATES, GbtResult.DETAIL_OVERFLOW, GbtResult.DETAIL_TX_STATS,
+        GbtResult.DETAIL_RATE_SAMPLES, GbtResult.DETAIL_CPFP_EDGES, GbtResult.DETAIL_DEPTH_CURVE
+    )
+
+    /** A fresh run (not served from the cache) with [detail]. */
+    private fun run(detail: Int): GbtResult {
+        val generator = generator(maxBlockWeight = GbtGenerator.MIN_BLOCK_WEIGHT, maxBlocks = 2)
+        generator.applyMake(mempool, listOf(ThreadAcceleration(4, 2_000)))
+        return generator.compute(detail = detail)!!
+    }
+
+    @Test
+    fun blocksAreIdenticalWhicheverSectionsAreRequested() {
+        val full = run(GbtResult.DETAIL_ALL)
+        val base = GbtResult.DETAIL_BLOCKS or GbtResult.DETAIL_WEIGHTS
+
+        // Every subset of the optional sections.
+        for (subset in 0 until (1 shl optional.size)) {
+            var detail = base
+            optional.forEachIndexed { i, flag -> if (subset and (1 shl i) != 0) detail = detail or flag }
+
+            val result = run(detail)
+
+            assertEquals(full.blocks.size, result.blocks.size)
+            for (i in full.blocks.indices) assertArrayEquals("detail $detail", full.blocks[i], result.blocks[i])
+            assertArrayEquals(full.blockWeights, result.blockWeights)
+            assertEquals(full.templateHash, result.templateHash)
+        }
+    }
+
+    @Test
+    fun omittedSectionsAreEmpty() {
+        val full = run(GbtResult.DETAIL_ALL)
+        val result = run(GbtResult.DETAIL_BLOCKS)
+
+        assertTrue(full.clusters.isNotEmpty() && full.rates.isNotEmpty() && full.overflow.isNotEmpty())
+        assertTrue(result.blocks.isNotEmpty())
+        assertTrue(result.blockWeights.isEmpty())
+        assertTrue(result.clusters.isEmpty() && result.clusterUids.isEmpty())
+        assertTrue(result.rates.isEmpty())
+        assertTrue(result.overflow.isEmpty() && result.overflowReasons.isEmpty())
+        assertNull(result.txStats)
+        assertTrue(result.blockRateSamples.isEmpty() && result.cpfpEdges.isEmpty())
+        assertTrue(result.depthCurveRates.isEmpty())
+    }
+
+    @Test
+    fun cachedResultIsTrimmedPerCall() {
+        val generator = generator()
+        generator.make(mempool, maxUid = 30)
+
+        val slim = generator.compute(detail = GbtResult.DETAIL_BLOCKS)!!
+        val full = generator.compute()!!
+
+        assertTrue(slim.fromCache && full.fromCache)
+        assertTrue(slim.clusters.isEmpty())
+        assertTrue(full.clusters.isNotEmpty())
+    }
+}
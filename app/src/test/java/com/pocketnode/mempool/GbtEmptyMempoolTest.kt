@@ -0,0 +1,87 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertTrue
+import org.junit.Test
+
+class GbtEmptyMempoolTest {
+    private fun assertParallel(result: GbtResult) {
+        assertTrue(result.blocks.isNotEmpty())
+        assertEquals(result.blocks.size, result.blockWeights.size)
+        assertEquals(result.blocks.size, result.blockVsizes.size)
+        assertEquals(result.blocks.size, result.blockAdjustedVsizes.size)
+        assertEquals(result.blocks.size, result.blockRateSamples.size)
+        assertEquals(result.overflow.size, result.overflowReasons.size)
+    }
+
+    @Test
+    fun emptyMempoolYieldsOneEmptyBlock() {
+        val result = generator().make(emptyList(), maxUid = 0)!!
+
+        assertParallel(result)
+        assertEquals(1, result.blocks.size)
+        assertEquals(0, result.blocks[0].size)
+        assertArrayEquals(intArrayOf(0), result.blockWeights)
+        assertEquals(0, result.blockRateSamples[0].size)
+        assertEquals(0, result.rates.size)
+        assertEquals(0, result.overflow.size)
+        assertEquals(0, result.maxUid)
+        // Fee suggestions fall back to the relay floor.
+        assertEquals(1.0, result.nextBlockCutoffRate, 0.0)
+    }
+
+    @Test
+    fun everythingRemovedYieldsOneEmptyBlock() {
+        val generator = generator()
+        generator.make(listOf(tx(1, fee = 1_000)), maxUid = 1)
+
+        val result = generator.update(removeTxs = listOf(1), maxUid = 1)!!
+
+        assertParallel(result)
+        assertEquals(0, result.blocks[0].size)
+        assertEquals(listOf(1), result.diff!!.removedUids.asList())
+    }
+
+    @Test
+    fun singleTransaction() {
+        val result = generator().make(listOf(tx(1, fee = 1_000)), maxUid = 1)!!
+
+        assertParallel(result)
+        assertArrayEquals(intArrayOf(1), result.blocks[0])
+        assertArrayEquals(intArrayOf(400), result.blockWeights)
+        assertArrayEquals(doubleArrayOf(10.0), result.blockRateSamples[0], 0.0)
+        assertEquals(10.0, result.nextBlockCutoffRate, 0.0)
+        assertEquals(0, result.rates.size)
+    }
+
+    @Test
+    fun twoUnrelatedTransactions() {
+        val result = generator().make(listOf(tx(1, fee = 1_000), tx(2, fee = 2_000)), maxUid = 2)!!
+
+        assertParallel(result)
+        assertArrayEquals(intArrayOf(2, 1), result.blocks[0])
+        assertEquals(10.0, result.nextBlockCutoffRate, 0.0)
+    }
+
+    @Test
+    fun parentChildPair() {
+        val result = generator().make(listOf(tx(1, fee = 100), tx(2, fee = 1_900, inputs = intArrayOf(1))), maxUid = 2)!!
+
+        assertParallel(result)
+        assertArrayEquals(intArrayOf(1, 2), result.blocks[0])
+        assertEquals(2, result.rates.size)
+        assertEquals(10.0, result.nextBlockCutoffRate, 0.0)
+    }
+
+    @Test
+    fun emptyResultSurvivesTheBlob() {
+        val result = generator().make(emptyList(), maxUid = 0)!!
+
+        val decoded = GbtResultBlob.decode(GbtResultBlob.encode(result))
+
+        assertParallel(decoded)
+        assertEquals(0, decoded.blocks[0].size)
+        assertEquals(0, decoded.rates.size)
+    }
+}
@@ -0,0 +1,60 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertTrue
+import org.junit.Test
+
+class GbtEnsembleTest {
+    /**
+     * Twelve 400 WU slots in a 5,000 WU block: eleven clear winners, two equal transactions
+     * (12, 13) competing for the last slot, and one (14) that never makes it.
+     */
+    private fun contested(seed: Long = 1L): GbtGenerator {
+        val txs = (1..11).map { tx(it, fee = 10_000) } + tx(12, fee = 1_000) + tx(13, fee = 1_000) + tx(14, fee = 100)
+        return generator(maxBlockWeight = GbtGenerator.MIN_BLOCK_WEIGHT).apply {
+            setSeed(seed)
+            make(txs, maxUid = 14)
+        }
+    }
+
+    private fun EnsembleProbabilities.of(uid: Int, values: DoubleArray) = values[uids.indexOf(uid)]
+
+    @Test
+    fun transactionsAtTheCutoffSplitBlockZero() {
+        val ensemble = contested().computeEnsemble(200)
+
+        assertEquals(200, ensemble.runs)
+        for (uid in intArrayOf(12, 13)) {
+            val p = ensemble.of(uid, ensemble.blockZero)
+            assertTrue("uid $uid: $p", p > 0.0 && p < 1.0)
+            assertEquals(1.0, ensemble.of(uid, ensemble.firstBlocks), 0.0)
+        }
+        assertEquals(1.0, ensemble.of(12, ensemble.blockZero) + ensemble.of(13, ensemble.blockZero), 1e-9)
+    }
+
+    @Test
+    fun transactionsFarFromTheCutoffAlwaysLandInTheSamePlace() {
+        val ensemble = contested().computeEnsemble(50)
+
+        for (uid in 1..11) assertEquals(1.0, ensemble.of(uid, ensemble.blockZero), 0.0)
+        assertEquals(0.0, ensemble.of(14, ensemble.blockZero), 0.0)
+        assertEquals(1.0, ensemble.of(14, ensemble.firstBlocks), 0.0)
+        assertTrue(ensemble.overflow.all { it == 0.0 })
+    }
+
+    @Test
+    fun sameSeedGivesTheSameProbabilities() {
+        assertEquals(contested(seed = 7L).computeEnsemble(64), contested(seed = 7L).computeEnsemble(64))
+    }
+
+    @Test
+    fun transactionsThatNeverFitCountAsOverflow() {
+        val generator = generator(maxBlockWeight = GbtGenerator.MIN_BLOCK_WEIGHT, maxBlocks = 1)
+        generator.make((1..13).map { tx(it, fee = 1_000 + it) }, maxUid = 13)
+
+        val ensemble = generator.computeEnsemble(20)
+
+        val overflow = ensemble.uids.indices.sumOf { ensemble.overflow[it] }
+        assertEquals(1.0, overflow, 1e-9)
+    }
+}
@@ -0,0 +1,48 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertTrue
+import org.junit.Assert.fail
+import org.junit.Test
+import java.io.File
+import java.io.IOException
+
+class GbtExceptionsTest {
+    @Test
+    fun invalidTransactionNamesItsUidAndReason() {
+        try {
+            generator(strict = true).make(listOf(tx(1, fee = 1_000), tx(2, fee = -5)), maxUid = 2)
+            fail()
+        } catch (e: GbtInvalidInputException) {
+            assertEquals(2, e.uid)
+            assertEquals("negative fee -5", e.reason)
+        }
+    }
+
+    @Test
+    fun invalidInputIsStillAnIllegalArgument() {
+        val generator = generator(strict = true)
+        generator.make(listOf(tx(1, fee = 1_000)), maxUid = 1)
+
+        try {
+            generator.applyUpdates(newTxs = listOf(tx(1, fee = 2_000)))
+            fail()
+        } catch (e: IllegalArgumentException) {
+            assertTrue(e is GbtInvalidInputException)
+            assertEquals(1, (e as GbtInvalidInputException).uid)
+        }
+    }
+
+    @Test
+    fun badRecordingIsAFormatErrorAndAnIoError() {
+        val file = File.createTempFile("gbt", ".rec").apply { deleteOnExit() }
+        file.writeBytes(ByteArray(16))
+
+        try {
+            GbtReplay.replayFile(file)
+            fail()
+        } catch (e: IOException) {
+            assertTrue(e is GbtFormatException)
+        }
+    }
+}
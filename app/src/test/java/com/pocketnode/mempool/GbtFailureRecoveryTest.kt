@@ -0,0 +1,58 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertFalse
+import org.junit.Assert.assertTrue
+import org.junit.Assert.fail
+import org.junit.Test
+import kotlin.concurrent.thread
+
+class GbtFailureRecoveryTest {
+    @Test
+    fun throwingMidUpdateLeavesTheGeneratorUsable() {
+        val generator = generator(strict = true)
+        generator.make(listOf(tx(1, fee = 1_000), tx(2, fee = 2_000, inputs = intArrayOf(1))), maxUid = 2)
+
+        try {
+            generator.applyUpdates(newTxs = listOf(tx(2, fee = 5_000, inputs = intArrayOf(1))))
+            fail()
+        } catch (e: GbtInvalidInputException) {
+            // expected: strict re-insert conflict, thrown with the write lock held
+        }
+
+        // Another thread can take the lock straight away.
+        var size = -1
+        thread { size = generator.size() }.join()
+        assertEquals(2, size)
+        val result = generator.update(newTxs = listOf(tx(3, fee = 3_000)), maxUid = 3)!!
+        assertArrayEquals(intArrayOf(3, 1, 2), result.blocks[0])
+        assertTrue(generator.verifyState().isConsistent)
+    }
+
+    @Test
+    fun throwingMidRunLeavesTheGeneratorUsable() {
+        val generator = generator()
+        generator.make(listOf(tx(1, fee = 1_000)), maxUid = 1)
+        generator.applyUpdates(newTxs = listOf(tx(2, fee = 2_000)))
+        generator.afterSnapshot = {
+            generator.afterSnapshot = null
+            throw IllegalStateException("boom")
+        }
+
+        try {
+            generator.compute()
+            fail()
+        } catch (e: IllegalStateException) {
+            assertEquals("boom", e.message)
+        }
+
+        // Not left marked as in flight: the next batch applies right away.
+        generator.applyUpdates(newTxs = listOf(tx(3, fee = 3_000)))
+        assertEquals(0, generator.getPendingUpdateCount())
+        val result = generator.compute()!!
+        assertFalse(result.fromCache)
+        assertArrayEquals(intArrayOf(3, 2, 1), result.blocks[0])
+        assertTrue(generator.verifyState().isConsistent)
+    }
+}
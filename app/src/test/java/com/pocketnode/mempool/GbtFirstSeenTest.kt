@@ -0,0 +1,52 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertTrue
+import org.junit.Test
+
+class GbtFirstSeenTest {
+    @Test
+    fun missingFirstSeenIsStampedOnInsert() {
+        val generator = generator()
+        val before = System.currentTimeMillis() / 1000
+
+        generator.make(listOf(tx(1, fee = 1_000)), maxUid = 1)
+
+        val stamped = generator.trackedTransactions().getValue(1).firstSeen!!
+        assertTrue(stamped in before..System.currentTimeMillis() / 1000)
+    }
+
+    @Test
+    fun explicitFirstSeenIsKeptAndReportedAsAge() {
+        val generator = generator()
+        val now = System.currentTimeMillis() / 1000
+
+        val stats = generator.make(listOf(tx(1, fee = 1_000, firstSeen = now - 600)), maxUid = 1)!!.txStats!!
+
+        assertEquals(now - 600, generator.trackedTransactions().getValue(1).firstSeen)
+        assertTrue(stats.ages[0] in 600L..610L)
+    }
+
+    @Test
+    fun explicitValuesDecideTheAuditFreshness() {
+        val blockTime = 1_700_000_000L
+        // Block 0 holds the twelve best, 20..9 (200..90 sat/vB). 1 and 2 fall just outside it,
+        // close enough to the cutoff not to count as prioritized.
+        val generator = generator(maxBlockWeight = GbtGenerator.MIN_BLOCK_WEIGHT)
+        generator.make(
+            (3..20).map { tx(it, fee = 1_000L * it, firstSeen = blockTime - 3_600) } +
+                tx(1, fee = 7_500, firstSeen = blockTime - 30) +
+                tx(2, fee = 8_000, firstSeen = blockTime - 3_600),
+            maxUid = 20
+        )
+
+        val audit = generator.auditBlock(intArrayOf(20, 19, 1, 2), blockTime = blockTime, freshWindowSeconds = 60)
+
+        assertArrayEquals(intArrayOf(1, 2), audit.addedUids)
+        assertArrayEquals(intArrayOf(1), audit.freshUids)
+        assertEquals(BlockAudit.STATUS_FRESH, generator.getAuditStatus(1))
+        assertEquals(BlockAudit.STATUS_ADDED, generator.getAuditStatus(2))
+        assertEquals(BlockAudit.STATUS_MATCHED, generator.getAuditStatus(20))
+    }
+}
@@ -0,0 +1,47 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Test
+import kotlin.random.Random
+
+class GbtInsertionOrderTest {
+    /** Many exact rate ties, and chains crossing them, so only tie-breaking decides the order. */
+    private val mempool = (1..120).map { uid ->
+        tx(uid, fee = 100L * (uid % 7 + 1), firstSeen = 1_000, inputs = if (uid % 9 == 0) intArrayOf(uid - 4) else intArrayOf())
+    }
+
+    private fun assertSameTemplate(expected: GbtResult, actual: GbtResult) {
+        assertEquals(expected.blocks.size, actual.blocks.size)
+        for (i in expected.blocks.indices) assertArrayEquals(expected.blocks[i], actual.blocks[i])
+        assertArrayEquals(expected.overflow, actual.overflow)
+        assertEquals(expected.clusters.map { it.toList() }, actual.clusters.map { it.toList() })
+        assertEquals(expected.rates.map { it.toList() }, actual.rates.map { it.toList() })
+        assertEquals(expected.cpfpEdges.map { it.toList() }, actual.cpfpEdges.map { it.toList() })
+        assertEquals(expected.templateHash, actual.templateHash)
+    }
+
+    private fun packed(txs: List<ThreadTransaction>): GbtResult =
+        generator(maxBlockWeight = GbtGenerator.MIN_BLOCK_WEIGHT, maxBlocks = 4).make(txs, maxUid = 120)!!
+
+    @Test
+    fun shuffledInsertionOrderGivesTheSameTemplate() {
+        val expected = packed(mempool)
+
+        for (seed in 1L..10L) assertSameTemplate(expected, packed(mempool.shuffled(Random(seed))))
+        assertSameTemplate(expected, packed(mempool.reversed()))
+    }
+
+    @Test
+    fun incrementalHistoryGivesTheSameTemplate() {
+        val expected = packed(mempool)
+        val generator = generator(maxBlockWeight = GbtGenerator.MIN_BLOCK_WEIGHT, maxBlocks = 4)
+        // A different history: unrelated transactions come and go, and the map grows past what it ends up holding.
+        generator.make((1_000..6_000).map { tx(it, fee = 100) }, maxUid = 6_000)
+        val shuffled = mempool.shuffled(Random(7))
+        generator.update(newTxs = shuffled.take(60), maxUid = 6_000)
+        generator.update(newTxs = shuffled.drop(60), removeTxs = (1_000..6_000).toList(), maxUid = 6_000)
+
+        assertSameTemplate(expected, generator.compute()!!)
+    }
+}
@@ -0,0 +1,108 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertTrue
+import org.junit.Assert.fail
+import org.junit.Test
+import kotlin.random.Random
+
+/**
+ * Seeded random mempools checked against the packing invariants. A failing case is shrunk by
+ * dropping transactions one at a time while it still fails, and reported with its seed.
+ */
+class GbtInvariantsTest {
+    private class Case(val maxBlockWeight: Int, val maxBlocks: Int, val accelerations: List<ThreadAcceleration>)
+
+    /** Random DAG: each transaction spends up to three earlier ones, some of them not tracked. */
+    private fun randomMempool(random: Random, count: Int): List<ThreadTransaction> = (1..count).map { uid ->
+        val parents = if (uid == 1) intArrayOf() else IntArray(random.nextInt(4)) { random.nextInt(1, uid + 5) }
+            .filter { it < uid || it > count }
+            .distinct()
+            .toIntArray()
+        tx(uid, fee = random.nextLong(0, 20_000), weight = random.nextInt(100, 4_000), inputs = parents)
+    }
+
+    private fun randomCase(random: Random, mempool: List<ThreadTransaction>) = Case(
+        maxBlockWeight = listOf(GbtGenerator.MIN_BLOCK_WEIGHT, 12_000, 40_000).random(random),
+        maxBlocks = random.nextInt(0, 4),
+        accelerations = mempool.filter { random.nextInt(10) == 0 }.map { ThreadAcceleration(it.uid, random.nextLong(1, 50_000)) }
+    )
+
+    /** The first invariant [result] breaks for [mempool], or null. */
+    private fun violation(mempool: List<ThreadTransaction>, case: Case, result: GbtResult?): String? {
+        result ?: return "no result"
+        val byUid = mempool.associateBy { it.uid }
+        val seen = HashSet<Int>()
+        for ((index, block) in result.blocks.withIndex()) {
+            val weight = block.sumOf { byUid[it]?.weight ?: return "block $index holds unknown uid $it" }
+            if (weight > case.maxBlockWeight) return "block $index weighs $weight WU"
+            if (case.maxBlocks > 0 && index >= case.maxBlocks) return "${result.blocks.size} blocks"
+            for (uid in block) {
+                for (parent in byUid.getValue(uid).inputs) {
+                    if (parent in byUid && parent !in seen) return "uid $uid in block $index before its parent $parent"
+                }
+                if (!seen.add(uid)) return "uid $uid selected twice"
+            }
+        }
+        for (uid in result.overflow) {
+            if (!seen.add(uid)) return "uid $uid both selected and in overflow, or twice in overflow"
+        }
+        if (seen != byUid.keys) return "blocks and overflow cover ${seen.size} of ${byUid.size} uids"
+        return null
+    }
+
+    private fun run(mempool: List<ThreadTransaction>, case: Case): String? {
+        val generator = generator(case.maxBlockWeight, case.maxBlocks)
+        return violation(mempool, case, generator.make(mempool, case.accelerations, maxUid = mempool.size + 5))
+    }
+
+    /** Greedily drops transactions while [case] still fails, for a small counterexample. */
+    private fun shrink(mempool: List<ThreadTransaction>, case: Case): List<ThreadTransaction> {
+        var current = mempool
+        var i = 0
+        while (i < current.size) {
+            val smaller = current.filterIndexed { j, _ -> j != i }
+            if (run(smaller, case) != null) current = smaller else i++
+        }
+        return current
+    }
+
+    @Test
+    fun randomMempoolsKeepThePackingInvariants() {
+        for (seed in 1L..200L) {
+            val random = Random(seed)
+            val mempool = randomMempool(random, random.nextInt(1, 80))
+            val case = randomCase(random, mempool)
+            val violation = run(mempool, case) ?: continue
+            val minimal = shrink(mempool, case)
+            fail(
+                "seed $seed: $violation (${case.maxBlockWeight} WU x ${case.maxBlocks}); minimal case: " +
+                    minimal.joinToString { "${it.uid}(fee=${it.fee}, weight=${it.weight}, inputs=${it.inputs.toList()})" }
+            )
+        }
+    }
+
+    @Test
+    fun incrementalUpdatesKeepThePackingInvariants() {
+        for (seed in 1L..50L) {
+            val random = Random(seed)
+            val case = Case(listOf(GbtGenerator.MIN_BLOCK_WEIGHT, 40_000).random(random), random.nextInt(0, 3), emptyList())
+            val generator = generator(case.maxBlockWeight, case.maxBlocks)
+            generator.make(randomMempool(random, 40), maxUid = 45)
+            var nextUid = 41
+            repeat(10) { step ->
+                val tracked = generator.trackedTransactions().keys.toList()
+                val removals = tracked.filter { random.nextInt(6) == 0 }
+                val additions = (0 until random.nextInt(8)).map {
+                    val uid = nextUid++
+                    val parent = tracked.randomOrNull(random)?.takeIf { random.nextBoolean() }
+                    tx(uid, fee = random.nextLong(0, 20_000), weight = random.nextInt(100, 4_000), inputs = listOfNotNull(parent).toIntArray())
+                }
+                val result = generator.update(additions, removals, maxUid = nextUid)
+
+                val violation = violation(generator.trackedTransactions().values.toList(), case, result)
+                assertTrue("seed $seed step $step: $violation", violation == null)
+                assertTrue("seed $seed step $step", generator.verifyState().isConsistent)
+            }
+        }
+    }
+}
@@ -0,0 +1,90 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertTrue
+import org.junit.Test
+
+class GbtMaxBlocksTest {
+    /** 100 transactions, 12 per 5,000 WU block: nine blocks' worth. */
+    private val mempool = (1..100).map { tx(it, fee = 1_000L + it) }
+
+    private fun packed(maxBlocks: Int) =
+        generator(maxBlockWeight = GbtGenerator.MIN_BLOCK_WEIGHT, maxBlocks = maxBlocks).make(mempool, maxUid = 100)!!
+
+    @Test
+    fun zeroProjectsEverything() {
+        val result = packed(0)
+
+        assertEquals(9, result.blocks.size)
+        assertEquals(100, result.blocks.sumOf { it.size })
+        assertEquals(0, result.overflow.size)
+        assertEquals(0, result.effectiveMaxBlocks)
+    }
+
+    @Test
+    fun oneBlockLeavesTheRestInOverflow() {
+        val result = packed(1)
+
+        assertEquals(1, result.blocks.size)
+        assertEquals(12, result.blocks[0].size)
+        assertEquals(88, result.overflow.size)
+        assertEquals(1, result.effectiveMaxBlocks)
+    }
+
+    @Test
+    fun limitIsKeptAndAnythingAboveIsClamped() {
+        assertEquals(GbtGenerator.MAX_BLOCKS_LIMIT, packed(GbtGenerator.MAX_BLOCKS_LIMIT).effectiveMaxBlocks)
+        assertEquals(GbtGenerator.MAX_BLOCKS_LIMIT, packed(GbtGenerator.MAX_BLOCKS_LIMIT + 1).effectiveMaxBlocks)
+        assertEquals(GbtGenerator.MAX_BLOCKS_LIMIT, packed(1_000).effectiveMaxBlocks)
+        assertEquals(9, packed(1_000).blocks.size)
+    }
+
+    @Test
+    fun overrideIsClampedToo() {
+        val generator = generator(maxBlockWeight = GbtGenerator.MIN_BLOCK_WEIGHT, maxBlocks = 2).apply { make(mempool, maxUid = 100) }
+
+        assertEquals(GbtGenerator.MAX_BLOCKS_LIMIT, generator.compute(maxBlocksOverride = 1_000)!!.effectiveMaxBlocks)
+    }
+
+    @Test
+    fun zeroOverrideAsksForUnlimited() {
+        val generator = generator(maxBlockWeight = GbtGenerator.MIN_BLOCK_WEIGHT, maxBlocks = 2).apply { make(mempool, maxUid = 100) }
+
+        val result = generator.compute(maxBlocksOverride = 0)!!
+
+        assertEquals(9, result.blocks.size)
+        assertEquals(0, result.overflow.size)
+        assertEquals(0, result.effectiveMaxBlocks)
+        assertEquals(2, generator.compute()!!.blocks.size)
+    }
+
+    @Test(expected = IllegalArgumentException::class)
+    fun negativeIsRejected() {
+        generator(maxBlocks = -1)
+    }
+
+    @Test(timeout = 30_000)
+    fun unlimitedModeScalesToThousandsOfBlocks() {
+        val count = 50_000
+        val generator = generator(maxBlockWeight = GbtGenerator.MIN_BLOCK_WEIGHT, maxBlocks = 0)
+
+        val result = generator.make((1..count).map { tx(it, fee = 100L + it % 997) }, maxUid = count)!!
+
+        assertEquals((count + 11) / 12, result.blocks.size)
+        assertEquals(0, result.overflow.size)
+    }
+
+    @Test(timeout = 10_000)
+    fun minimumWeightBlocksStayCheapForALargeMempool() {
+        val count = 200_000
+        // Mixed sizes: once a block is full, only a few light packages are left to try.
+        val mempool = (1..count).map { tx(it, fee = 100L + it % 997, weight = if (it % 5 == 0) 1_600 else 400) }
+        val generator = generator(maxBlockWeight = GbtGenerator.MIN_BLOCK_WEIGHT, maxBlocks = 0)
+
+        val result = generator.make(mempool, maxUid = count)!!
+
+        assertEquals(count, result.blocks.sumOf { it.size })
+        assertEquals(0, result.overflow.size)
+        assertTrue(result.blocks.all { block -> block.sumOf { mempool[it - 1].weight } <= GbtGenerator.MIN_BLOCK_WEIGHT })
+    }
+}
@@ -0,0 +1,43 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Test
+
+class GbtMaxUidTest {
+    private val mempool = listOf(tx(3, fee = 3_000), tx(70, fee = 7_000), tx(500, fee = 5_000, inputs = intArrayOf(3)))
+
+    @Test
+    fun underReportedMaxUidStillPacksEverything() {
+        val honest = generator().make(mempool, maxUid = 500)!!
+
+        // Warns, but nothing is sized by it.
+        val wrong = generator().make(mempool, maxUid = 10)!!
+
+        assertArrayEquals(honest.blocks[0], wrong.blocks[0])
+        assertArrayEquals(intArrayOf(70, 3, 500), wrong.blocks[0])
+        assertEquals(500, wrong.maxUid)
+    }
+
+    @Test
+    fun zeroMaxUidWithTransactions() {
+        val generator = generator()
+
+        val result = generator.make(mempool, maxUid = 0)!!
+
+        assertEquals(3, result.blocks[0].size)
+        assertEquals(500, result.maxUid)
+    }
+
+    @Test
+    fun resultReportsTheActualMaxAfterUpdates() {
+        val generator = generator()
+        generator.make(mempool, maxUid = 500)
+
+        val grown = generator.update(newTxs = listOf(tx(900, fee = 100)), maxUid = 499)!!
+        assertEquals(900, grown.maxUid)
+
+        val shrunk = generator.update(removeTxs = listOf(900, 500), maxUid = 900)!!
+        assertEquals(70, shrunk.maxUid)
+    }
+}
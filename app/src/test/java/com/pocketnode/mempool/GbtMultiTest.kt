@@ -0,0 +1,93 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertTrue
+import org.junit.Test
+import java.util.concurrent.CountDownLatch
+import java.util.concurrent.TimeUnit
+import kotlin.concurrent.thread
+
+class GbtMultiTest {
+    /** Mixed mempool: independent txs, a CPFP pair, a chain, a sigop-heavy tx and an accelerated one. */
+    private fun loaded(): GbtGenerator {
+        val txs = mutableListOf<ThreadTransaction>()
+        for (uid in 1..40) txs.add(tx(uid, fee = 200L + uid * 37 % 900))
+        txs.add(tx(41, fee = 0))
+        txs.add(tx(42, fee = 5_000, inputs = intArrayOf(41)))
+        txs.add(tx(43, fee = 800, weight = 1_200))
+        txs.add(tx(44, fee = 300, inputs = intArrayOf(43)))
+        txs.add(tx(45, fee = 2_000, inputs = intArrayOf(44)))
+        txs.add(tx(46, fee = 3_000, sigops = 400))
+        txs.add(tx(47, fee = 100))
+        return generator().apply {
+            make(txs, accelerations = listOf(ThreadAcceleration(47, 10_000)), maxUid = 47)
+        }
+    }
+
+    @Test
+    fun eachLimitMatchesAnIndependentRun() {
+        val generator = loaded()
+        val weights = intArrayOf(5_000, 8_000, 4_000_000, 12_345)
+
+        val projections = generator.computeMulti(weights)
+
+        assertEquals(weights.size, projections.size)
+        for ((i, weight) in weights.withIndex()) {
+            val projection = projections[i]
+            val independent = generator.simulate(weight, 0)!!
+            assertEquals(weight, projection.maxBlockWeight)
+            assertEquals(independent.blocks.size, projection.blocks.size)
+            for (b in independent.blocks.indices) assertArrayEquals(independent.blocks[b], projection.blocks[b])
+            assertArrayEquals(independent.blockWeights, projection.blockWeights)
+        }
+    }
+
+    @Test(timeout = 10_000)
+    fun waitsForARunInFlightAndPacksEveryLimitFromTheStateAfterIt() {
+        val generator = loaded()
+        val inRun = CountDownLatch(1)
+        val released = CountDownLatch(1)
+        generator.afterSnapshot = {
+            generator.afterSnapshot = null
+            inRun.countDown()
+            released.await()
+        }
+        val runner = thread { generator.compute() }
+        assertTrue(inRun.await(5, TimeUnit.SECONDS))
+        // Queued behind the run, and applied before it lets go of the generator.
+        generator.applyUpdates(newTxs = listOf(tx(48, fee = 50_000)))
+
+        var projections: Array<GbtBlockProjection> = emptyArray()
+        val projector = thread { projections = generator.computeMulti(intArrayOf(5_000, 4_000_000)) }
+        projector.join(100)
+        assertTrue(projector.isAlive)
+        released.countDown()
+        runner.join()
+        projector.join()
+
+        for (projection in projections) {
+            assertEquals(48, projection.blocks[0][0])
+            val independent = generator.simulate(projection.maxBlockWeight, 0)!!
+            for (b in independent.blocks.indices) assertArrayEquals(independent.blocks[b], projection.blocks[b])
+        }
+    }
+
+    @Test
+    fun repeatedLimitsDoNotShareState() {
+        val projections = loaded().computeMulti(intArrayOf(5_000, 5_000))
+
+        assertEquals(projections[0], projections[1])
+    }
+
+    @Test
+    fun blockFeesIncludeAccelerations() {
+        val generator = loaded()
+
+        val projection = generator.computeMulti(intArrayOf(4_000_000)).single()
+
+        assertEquals(1, projection.blocks.size)
+        val baseFees = (1..40).sumOf { 200L + it * 37 % 900 } + 5_000 + 800 + 300 + 2_000 + 3_000 + 100
+        assertEquals(baseFees + 10_000, projection.blockFees[0])
+    }
+}
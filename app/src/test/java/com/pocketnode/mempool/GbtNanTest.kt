@@ -0,0 +1,48 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertTrue
+import org.junit.Test
+
+class GbtNanTest {
+    private val unrelated = listOf(tx(1, fee = 3_000), tx(2, fee = 1_000), tx(3, fee = 2_000, inputs = intArrayOf(2)))
+
+    private fun nan(uid: Int, inputs: IntArray = intArrayOf()) =
+        tx(uid, fee = 0, inputs = inputs).copy(effectiveFeePerVsize = Double.NaN)
+
+    @Test
+    fun nanRateIsKeptOutWithoutReorderingTheRest() {
+        val clean = generator().make(unrelated, maxUid = 3)!!
+
+        val generator = generator()
+        val result = generator.make(unrelated + nan(4) + nan(5, inputs = intArrayOf(2)), maxUid = 5)!!
+
+        assertEquals(clean.blocks.size, result.blocks.size)
+        for (i in clean.blocks.indices) assertArrayEquals(clean.blocks[i], result.blocks[i])
+        assertEquals(2, result.invalidTxCount)
+        assertTrue(generator.getAncestors(4) == null)
+    }
+
+    @Test
+    fun nanRateNeverReachesTheHeapThroughAnUpdate() {
+        val generator = generator()
+        generator.make(unrelated, maxUid = 3)
+
+        val result = generator.update(newTxs = listOf(nan(4)), maxUid = 4)!!
+
+        assertArrayEquals(intArrayOf(1, 2, 3), result.blocks[0])
+        assertEquals(1, result.invalidTxCount)
+    }
+
+    @Test
+    fun derivedRatesReplaceAProvidedNan() {
+        val generator = GbtGenerator.create(GbtConfig(maxBlocks = 0, deriveRates = true))
+
+        val result = generator.make(unrelated + tx(4, fee = 500).copy(effectiveFeePerVsize = Double.NaN), maxUid = 4)!!
+
+        assertArrayEquals(intArrayOf(1, 2, 3, 4), result.blocks[0])
+        assertEquals(1, result.rateDiscrepancyCount)
+        assertEquals(0, result.invalidTxCount)
+    }
+}
@@ -0,0 +1,44 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertEquals
+import org.junit.Test
+
+class GbtOverflowPressureTest {
+    private fun oneBlock() = generator(maxBlockWeight = GbtGenerator.MIN_BLOCK_WEIGHT, maxBlocks = 1)
+
+    @Test
+    fun bestOverflowRateAndTotals() {
+        // 15 transactions at 10.1..11.5 sat/vB; the block takes the top 12.
+        val result = oneBlock().make((1..15).map { tx(it, fee = 1_000L + 10 * it) }, maxUid = 15)!!
+
+        assertEquals(listOf(1, 2, 3), result.overflow.sorted())
+        assertEquals(10.3, result.overflowTopRate, 0.0)
+        assertEquals(300L, result.overflowVsize)
+        assertEquals(1_010L + 1_020 + 1_030, result.overflowFees)
+    }
+
+    @Test
+    fun overflowRateIsThePackageRate() {
+        // Parent 13 at 1 sat/vB is worth 10 sat/vB with its child 14; both miss the block.
+        val full = (1..12).map { tx(it, fee = 2_000) }
+        val parent = tx(13, fee = 100)
+        val child = tx(14, fee = 1_900, inputs = intArrayOf(13))
+
+        val result = oneBlock().make(full + parent + child, maxUid = 14)!!
+
+        assertEquals(listOf(13, 14), result.overflow.sorted())
+        assertEquals(10.0, result.overflowTopRate, 0.0)
+        assertEquals(200L, result.overflowVsize)
+        assertEquals(2_000L, result.overflowFees)
+    }
+
+    @Test
+    fun noOverflowReportsZero() {
+        val result = oneBlock().make((1..5).map { tx(it, fee = 1_000) }, maxUid = 5)!!
+
+        assertEquals(0, result.overflow.size)
+        assertEquals(0.0, result.overflowTopRate, 0.0)
+        assertEquals(0L, result.overflowVsize)
+        assertEquals(0L, result.overflowFees)
+    }
+}
@@ -0,0 +1,73 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Test
+
+class GbtOverflowReasonTotalsTest {
+    private data class Bucket(val count: Int, val weight: Long, val fees: Long)
+
+    private fun buckets(result: GbtResult) = result.overflowReasonCodes.indices.associate {
+        result.overflowReasonCodes[it] to
+            Bucket(result.overflowReasonCounts[it], result.overflowReasonWeights[it], result.overflowReasonFees[it])
+    }
+
+    @Test
+    fun oneBucketPerReason() {
+        // 1..12 fill the only block at 100 sat/vB.
+        val fillers = (1..12).map { tx(it, fee = 10_000) }
+        val mempool = fillers + listOf(
+            tx(13, fee = 1_000),
+            tx(14, fee = 700, policyFlags = ThreadTransaction.POLICY_BARE_MULTISIG),
+            tx(15, fee = 500, inputs = intArrayOf(1, 2), isTruc = true),
+            tx(16, fee = 600, inputs = intArrayOf(15)),
+            tx(17, fee = 500_000, weight = 40_004, isTruc = true),
+            // TRUC parent 18 keeps only its better child 20; both then miss the full block, like 13.
+            tx(18, fee = 100, isTruc = true),
+            tx(19, fee = 2_000, inputs = intArrayOf(18), isTruc = true),
+            tx(20, fee = 3_000, inputs = intArrayOf(18), isTruc = true)
+        )
+        val generator = GbtGenerator.create(
+            GbtConfig(maxBlockWeight = GbtGenerator.MIN_BLOCK_WEIGHT, maxBlocks = 1, acceptedPolicyMask = 0)
+        ).apply { setSeed(1L) }
+
+        val result = generator.make(mempool, maxUid = 20)!!
+
+        assertEquals(
+            mapOf(
+                GbtResult.OVERFLOW_NO_SPACE to Bucket(3, 1_200, 4_100),
+                GbtResult.OVERFLOW_TRUC_INVALID to Bucket(1, 400, 500),
+                GbtResult.OVERFLOW_TRUC_OVERSIZED to Bucket(1, 40_004, 500_000),
+                GbtResult.OVERFLOW_TRUC_SIBLING_EVICTED to Bucket(1, 400, 2_000),
+                GbtResult.OVERFLOW_NON_STANDARD to Bucket(1, 400, 700),
+                GbtResult.OVERFLOW_TRUC_ANCESTOR_EXCLUDED to Bucket(1, 400, 600)
+            ),
+            buckets(result)
+        )
+        // Ascending codes, and the totals reconcile with the overflow itself.
+        assertArrayEquals(result.overflowReasonCodes.sortedArray(), result.overflowReasonCodes)
+        assertEquals(result.overflow.size, result.overflowReasonCounts.sum())
+        assertEquals(4_100L, result.overflowFees)
+        assertEquals(300L, result.overflowVsize)
+    }
+
+    @Test
+    fun notEvaluatedBucket() {
+        val generator = generator(maxBlockWeight = GbtGenerator.MIN_BLOCK_WEIGHT, maxBlocks = 2)
+        generator.make((1..14).map { tx(it, fee = 1_000L + it) }, maxUid = 14)
+
+        val result = generator.computeNextBlock(GbtResult.DETAIL_ALL)!!
+
+        assertEquals(mapOf(GbtResult.OVERFLOW_NOT_EVALUATED to Bucket(2, 800, 1_001L + 1_002)), buckets(result))
+        assertEquals(result.overflowFees, result.overflowReasonFees.sum())
+    }
+
+    @Test
+    fun accelerationsCountInTheFees() {
+        val generator = generator(maxBlockWeight = GbtGenerator.MIN_BLOCK_WEIGHT, maxBlocks = 1)
+
+        val result = generator.make((1..13).map { tx(it, fee = 10_000) } + tx(14, fee = 100), listOf(ThreadAcceleration(14, 400)), maxUid = 14)!!
+
+        assertEquals(mapOf(GbtResult.OVERFLOW_NO_SPACE to Bucket(2, 800, 10_000L + 500)), buckets(result))
+    }
+}
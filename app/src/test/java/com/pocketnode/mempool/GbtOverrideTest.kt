@@ -0,0 +1,62 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertNull
+import org.junit.Assert.assertTrue
+import org.junit.Test
+
+class GbtOverrideTest {
+    /** 40 transactions, 12 per 5,000 WU block: four blocks' worth. */
+    private val mempool = (1..40).map { tx(it, fee = 1_000L + 10 * it) }
+
+    private fun configured(maxBlocks: Int) =
+        generator(maxBlockWeight = GbtGenerator.MIN_BLOCK_WEIGHT, maxBlocks = maxBlocks).apply { make(mempool, maxUid = 40) }
+
+    private fun assertSameBlocks(expected: GbtResult, actual: GbtResult) {
+        assertEquals(expected.blocks.size, actual.blocks.size)
+        for (i in expected.blocks.indices) assertArrayEquals(expected.blocks[i], actual.blocks[i])
+        assertArrayEquals(expected.overflow, actual.overflow)
+    }
+
+    @Test
+    fun maxBlocksOneThenEightEachMatchTheirOwnConfiguration() {
+        val generator = configured(maxBlocks = 2)
+
+        val one = generator.compute(maxBlocksOverride = 1)!!
+        val eight = generator.compute(maxBlocksOverride = 8)!!
+
+        assertEquals(1, one.blocks.size)
+        assertEquals(28, one.overflow.size)
+        assertEquals(4, eight.blocks.size)
+        assertEquals(0, eight.overflow.size)
+        assertSameBlocks(configured(maxBlocks = 1).compute()!!, one)
+        assertSameBlocks(configured(maxBlocks = 8).compute()!!, eight)
+    }
+
+    @Test
+    fun overridesLeaveTheCachedResultAlone() {
+        val generator = configured(maxBlocks = 2)
+        val normal = generator.compute()!!
+        val positionBefore = generator.getTxPosition(1)
+
+        generator.compute(maxBlockWeightOverride = GbtGenerator.MAX_BLOCK_WEIGHT, maxBlocksOverride = 1)
+        val again = generator.compute()!!
+
+        assertTrue(again.fromCache)
+        assertSameBlocks(normal, again)
+        assertEquals(normal.templateHash, generator.getTemplateHash())
+        assertEquals(positionBefore, generator.getTxPosition(1))
+        assertEquals(GbtResult.OVERFLOW_BLOCK, positionBefore)
+    }
+
+    @Test
+    fun blockWeightOverrideRepacks() {
+        val generator = configured(maxBlocks = 0)
+
+        val big = generator.compute(maxBlockWeightOverride = 10_000)!!
+
+        assertEquals(listOf(25, 15), big.blocks.map { it.size })
+        assertNull(big.diff)
+    }
+}
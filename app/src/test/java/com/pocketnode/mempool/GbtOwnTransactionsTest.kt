@@ -0,0 +1,72 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertTrue
+import org.junit.Test
+
+class GbtOwnTransactionsTest {
+    /** 1..12 fill block 0 at 20 sat/vB; own 13 waits at 5 sat/vB. */
+    private val mempool = (1..12).map { tx(it, fee = 2_000) } + tx(13, fee = 500)
+
+    private fun tracked() = generator(maxBlockWeight = GbtGenerator.MIN_BLOCK_WEIGHT).apply {
+        setOwnTransactions(intArrayOf(13))
+        make(mempool, maxUid = 13)
+    }
+
+    @Test
+    fun accelerationMovesOwnTransactionIntoBlockZero() {
+        val generator = tracked()
+        val before = generator.compute()!!.ownTxPositions!!
+        assertArrayEquals(intArrayOf(13), before.uids)
+        assertArrayEquals(intArrayOf(1), before.blocks)
+        assertArrayEquals(doubleArrayOf(5.0), before.rates, 0.0)
+        assertEquals(0, before.nextBlockCount)
+        assertEquals(500L, before.pendingFees)
+
+        val after = generator.update(accelerations = listOf(ThreadAcceleration(13, 2_500)), maxUid = 13)!!.ownTxPositions!!
+
+        assertArrayEquals(intArrayOf(0), after.blocks)
+        assertArrayEquals(doubleArrayOf(30.0), after.rates, 0.0)
+        assertEquals(1, after.nextBlockCount)
+    }
+
+    @Test
+    fun minedOwnTransactionIsReportedGoneOnce() {
+        val generator = tracked()
+
+        val mined = generator.update(removeTxs = listOf(13), maxUid = 13)!!.ownTxPositions!!
+
+        assertArrayEquals(intArrayOf(13), mined.uids)
+        assertArrayEquals(intArrayOf(GbtResult.UNKNOWN_UID), mined.blocks)
+        assertArrayEquals(doubleArrayOf(0.0), mined.rates, 0.0)
+        assertEquals(0L, mined.pendingFees)
+
+        val next = generator.update(newTxs = listOf(tx(14, fee = 1_000)), maxUid = 14)!!.ownTxPositions!!
+
+        assertEquals(0, next.uids.size)
+    }
+
+    @Test
+    fun ownSetSurvivesRebuildingTheMempool() {
+        val generator = tracked()
+
+        val remade = generator.make(mempool.reversed(), maxUid = 13)!!.ownTxPositions!!
+
+        assertArrayEquals(intArrayOf(13), remade.uids)
+        assertArrayEquals(intArrayOf(1), remade.blocks)
+    }
+
+    @Test
+    fun cachedResultsReportOwnPositionsToo() {
+        val generator = tracked()
+        generator.setOwnTransactions(intArrayOf(1, 13))
+
+        val cached = generator.compute()!!
+
+        assertTrue(cached.fromCache)
+        assertArrayEquals(intArrayOf(1, 13), cached.ownTxPositions!!.uids)
+        assertArrayEquals(intArrayOf(0, 1), cached.ownTxPositions!!.blocks)
+        assertEquals(2_500L, cached.ownTxPositions!!.pendingFees)
+    }
+}
@@ -0,0 +1,73 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertNull
+import org.junit.Assert.assertTrue
+import org.junit.Test
+
+class GbtPagingTest {
+    /** 25 parent/child pairs, each child paying for its parent: 50 rate rows, 25 clusters. */
+    private val mempool = (1..25).flatMap { i ->
+        listOf(tx(2 * i - 1, fee = 100L + i), tx(2 * i, fee = 2_000L + 10 * i, inputs = intArrayOf(2 * i - 1)))
+    }
+
+    private fun computedSlim(): GbtGenerator = generator().apply {
+        applyMake(mempool, emptyList())
+        compute(detail = GbtResult.DETAIL_BLOCKS)
+    }
+
+    @Test
+    fun pagesReassembleToTheFullSections() {
+        val generator = computedSlim()
+        val full = generator.compute()!!
+        assertEquals(50, full.rates.size)
+
+        assertEquals(full.rates.size, generator.getRatesCount())
+        val rows = generator.getRatesCount().let { count ->
+            (0 until count step 7).flatMap { generator.getRatesPage(it, 7).asList() }
+        }
+        assertEquals(full.rates.size, rows.size)
+        full.rates.forEachIndexed { i, row -> assertArrayEquals(row, rows[i], 0.0) }
+
+        assertEquals(full.clusters.size, generator.getClusterCount())
+        for (i in full.clusters.indices) assertArrayEquals(full.clusters[i], generator.getCluster(i))
+    }
+
+    @Test
+    fun outOfRangePagesAreEmpty() {
+        val generator = computedSlim()
+
+        assertEquals(0, generator.getRatesPage(50, 10).size)
+        assertEquals(3, generator.getRatesPage(47, 10).size)
+        assertEquals(0, generator.getRatesPage(-1, 10).size)
+        assertEquals(0, generator.getRatesPage(0, 0).size)
+        assertNull(generator.getCluster(generator.getClusterCount()))
+    }
+
+    @Test
+    fun pagesFollowTheLatestRunAndRejectAStaleRunId() {
+        val generator = computedSlim()
+        val oldRun = generator.getResultRunId()
+
+        generator.update(newTxs = listOf(tx(51, fee = 100), tx(52, fee = 5_000, inputs = intArrayOf(51))), maxUid = 52)
+
+        assertTrue(generator.getResultRunId() != oldRun)
+        assertEquals(52, generator.getRatesCount())
+        assertEquals(0, generator.getRatesPage(0, 10, runId = oldRun).size)
+        assertNull(generator.getCluster(0, runId = oldRun))
+        assertEquals(10, generator.getRatesPage(0, 10, runId = generator.getResultRunId()).size)
+    }
+
+    @Test
+    fun resetDropsTheRetainedResult() {
+        val generator = computedSlim()
+
+        generator.reset()
+
+        assertEquals(0, generator.getRatesCount())
+        assertEquals(0, generator.getClusterCount())
+        assertEquals(0, generator.getRatesPage(0, 10).size)
+        assertEquals(0L, generator.getResultRunId())
+    }
+}
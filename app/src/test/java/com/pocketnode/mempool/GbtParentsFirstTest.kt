@@ -0,0 +1,64 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertTrue
+import org.junit.Test
+import kotlin.random.Random
+
+class GbtParentsFirstTest {
+    /** Every transaction comes after its in-mempool parents, in its own block or an earlier one. */
+    private fun assertParentsFirst(result: GbtResult, txs: List<ThreadTransaction>) {
+        val byUid = txs.associateBy { it.uid }
+        val seen = HashSet<Int>()
+        for (block in result.blocks) {
+            for (uid in block) {
+                for (parent in byUid.getValue(uid).inputs) {
+                    if (parent in byUid) assertTrue("$uid before its parent $parent", parent in seen)
+                }
+                seen.add(uid)
+            }
+        }
+    }
+
+    @Test
+    fun chainWithDescendingUidsIsEmittedParentsFirst() {
+        // 1 spends 3, which spends 5: uid order is the reverse of dependency order.
+        val txs = listOf(tx(5, fee = 100), tx(3, fee = 100, inputs = intArrayOf(5)), tx(1, fee = 10_000, inputs = intArrayOf(3)))
+
+        val result = generator().make(txs, maxUid = 5)!!
+
+        assertArrayEquals(intArrayOf(5, 3, 1), result.blocks[0])
+    }
+
+    @Test
+    fun diamondWithSharedAncestorIsEmittedParentsFirst() {
+        val txs = listOf(
+            tx(9, fee = 100),
+            tx(4, fee = 100, inputs = intArrayOf(9)),
+            tx(6, fee = 100, inputs = intArrayOf(9)),
+            tx(2, fee = 20_000, inputs = intArrayOf(6, 4))
+        )
+
+        val result = generator().make(txs, maxUid = 9)!!
+
+        assertEquals(9, result.blocks[0].first())
+        assertEquals(2, result.blocks[0].last())
+        assertParentsFirst(result, txs)
+    }
+
+    @Test
+    fun randomDagWithParentsAboveChildrenIsEmittedParentsFirst() {
+        val random = Random(3)
+        // Parents with higher uids than their children throughout.
+        val txs = (1..200).map { uid ->
+            val parents = if (uid < 190) intArrayOf(uid + 1 + random.nextInt(10)).filter { it <= 200 }.toIntArray() else intArrayOf()
+            tx(uid, fee = random.nextLong(100, 10_000), inputs = parents)
+        }
+
+        val result = generator(maxBlockWeight = 20_000).make(txs, maxUid = 200)!!
+
+        assertParentsFirst(result, txs)
+        assertEquals(200, result.blocks.sumOf { it.size } + result.overflow.size)
+    }
+}
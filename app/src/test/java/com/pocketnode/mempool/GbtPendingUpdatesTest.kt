@@ -0,0 +1,242 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Assert.fail
+import org.junit.Test
+import java.util.concurrent.CountDownLatch
+import java.util.concurrent.TimeUnit
+import java.util.concurrent.atomic.AtomicBoolean
+import kotlin.concurrent.thread
+
+class GbtPendingUpdatesTest {
+    /** Fixed first-seen times, so two generators fed the same batches track equal transactions. */
+    private fun seen(uid: Int, fee: Long, inputs: IntArray = intArrayOf()) = tx(uid, fee = fee, inputs = inputs, firstSeen = 1L)
+
+    private val base = (1..5).map { seen(it, fee = 1_000L * it) }
+
+    private class Batch(
+        val newTxs: List<ThreadTransaction> = emptyList(),
+        val removeTxs: List<Int> = emptyList(),
+        val accelerations: List<ThreadAcceleration> = emptyList(),
+        val removeDescendants: Boolean = false
+    )
+
+    private fun GbtGenerator.submit(batch: Batch) =
+        applyUpdates(batch.newTxs, batch.removeTxs, batch.accelerations, batch.removeDescendants)
+
+    /** Blocks the next [GbtGenerator.compute] right after its snapshot until [release] is called. */
+    private class HeldRun(private val generator: GbtGenerator) {
+        private val inRun = CountDownLatch(1)
+        private val released = CountDownLatch(1)
+        var result: GbtResult? = null
+        private val runner: Thread
+
+        init {
+            generator.afterSnapshot = {
+                generator.afterSnapshot = null
+                inRun.countDown()
+                released.await()
+            }
+            runner = thread { result = generator.compute() }
+            check(inRun.await(5, TimeUnit.SECONDS))
+        }
+
+        fun release(): GbtResult? {
+            released.countDown()
+            runner.join()
+            return result
+        }
+    }
+
+    private fun sequential(batches: List<Batch>, strict: Boolean = false): GbtGenerator {
+        return generator(strict = strict).apply {
+            make(base, maxUid = 5)
+            for (batch in batches) {
+                try {
+                    submit(batch)
+                } catch (e: GbtInvalidInputException) {
+                    // Dropped whole, like a queued batch that conflicts when applied.
+                }
+            }
+        }
+    }
+
+    private fun assertSameState(expected: GbtGenerator, actual: GbtGenerator) {
+        assertEquals(expected.trackedTransactions(), actual.trackedTransactions())
+        assertArrayEquals(expected.getAccelerations().uids, actual.getAccelerations().uids)
+        assertArrayEquals(expected.getAccelerations().deltas, actual.getAccelerations().deltas)
+        assertEquals(expected.compute()!!.templateHash, actual.compute()!!.templateHash)
+    }
+
+    @Test(timeout = 10_000)
+    fun batchesQueuedDuringARunApplyInOrderBeforeTheNextOne() {
+        val batches = listOf(
+            Batch(newTxs = listOf(seen(6, fee = 500), seen(7, fee = 700))),
+            Batch(newTxs = listOf(seen(8, fee = 9_000, inputs = intArrayOf(6))), removeTxs = listOf(1)),
+            Batch(newTxs = listOf(seen(1, fee = 4_000)), accelerations = listOf(ThreadAcceleration(8, 1_000))),
+            Batch(removeTxs = listOf(6), removeDescendants = true)
+        )
+        val generator = generator()
+        generator.make(base, maxUid = 5)
+        val run = HeldRun(generator)
+
+        batches.forEach { generator.submit(it) }
+
+        assertEquals(6, generator.getPendingUpdateCount())
+        assertEquals((1..5).toSet(), generator.trackedTransactions().keys)
+        val first = run.release()!!
+        assertEquals((1..5).toSet(), first.blocks[0].toSet())
+        assertEquals(0, generator.getPendingUpdateCount())
+        assertSameState(sequential(batches), generator)
+    }
+
+    @Test(timeout = 10_000)
+    fun updateWhileARunIsInFlightPacksItsOwnBatch() {
+        val generator = generator()
+        generator.make(base, maxUid = 5)
+        val run = HeldRun(generator)
+
+        val updater = thread { generator.update(newTxs = listOf(seen(6, fee = 9_000)), maxUid = 6) }
+        while (generator.getPendingUpdateCount() == 0) Thread.sleep(1)
+        run.release()
+        updater.join()
+
+        assertEquals(6, generator.compute()!!.blocks[0].size)
+        assertEquals(0L, generator.pendingChangeCount())
+    }
+
+    @Test(timeout = 10_000)
+    fun queueBeyondTheLimitThrowsAndKeepsEarlierBatches() {
+        val generator = generator()
+        generator.make(base, maxUid = 5)
+        val run = HeldRun(generator)
+        val full = (10 until 10 + GbtGenerator.MAX_PENDING_UPDATES).map { seen(it, fee = 1_000) }
+
+        generator.applyUpdates(newTxs = full)
+        try {
+            generator.applyUpdates(removeTxs = listOf(1))
+            fail()
+        } catch (e: GbtBackpressureException) {
+            assertEquals(GbtGenerator.MAX_PENDING_UPDATES, e.pending)
+        }
+
+        assertEquals(GbtGenerator.MAX_PENDING_UPDATES, generator.getPendingUpdateCount())
+        run.release()
+        assertEquals(5 + GbtGenerator.MAX_PENDING_UPDATES, generator.size())
+        // Room again once drained.
+        generator.applyUpdates(removeTxs = listOf(1))
+        assertEquals(4 + GbtGenerator.MAX_PENDING_UPDATES, generator.size())
+    }
+
+    @Test(timeout = 10_000)
+    fun strictConflictDropsOnlyThatQueuedBatch() {
+        val batches = listOf(
+            Batch(newTxs = listOf(seen(6, fee = 500))),
+            Batch(newTxs = listOf(seen(7, fee = 700), seen(1, fee = 9_999))),
+            Batch(newTxs = listOf(seen(8, fee = 800)))
+        )
+        val generator = generator(strict = true)
+        generator.make(base, maxUid = 5)
+        val run = HeldRun(generator)
+
+        batches.forEach { generator.submit(it) }
+        try {
+            generator.applyUpdates(newTxs = listOf(seen(9, fee = 100, inputs = intArrayOf(9))))
+            fail()
+        } catch (e: GbtInvalidInputException) {
+            assertEquals(9, e.uid)
+        }
+        assertEquals(4, generator.getPendingUpdateCount())
+        run.release()
+
+        assertEquals((1..6).toSet() + 8, generator.trackedTransactions().keys)
+        assertEquals(1_000L, generator.trackedTransactions().getValue(1).fee)
+        assertSameState(sequential(batches, strict = true), generator)
+    }
+
+    @Test(timeout = 10_000)
+    fun removalQueuedAfterAnInsertRemovesIt() {
+        val generator = generator()
+        generator.make(base, maxUid = 5)
+        val run = HeldRun(generator)
+        generator.applyUpdates(newTxs = listOf(seen(6, fee = 500), seen(7, fee = 700, inputs = intArrayOf(6))))
+
+        assertArrayEquals(IntArray(0), generator.removeWithDescendants(intArrayOf(6)))
+
+        assertEquals(3, generator.getPendingUpdateCount())
+        assertEquals((1..5).toSet(), generator.trackedTransactions().keys)
+        run.release()
+        assertEquals((1..5).toSet(), generator.trackedTransactions().keys)
+        assertEquals(0, generator.getPendingUpdateCount())
+    }
+
+    @Test(timeout = 10_000)
+    fun txidRemovalQueuedAfterAnInsertResolvesItWhenApplied() {
+        val generator = generator()
+        generator.make(base, maxUid = 5)
+        val run = HeldRun(generator)
+        generator.applyUpdates(newTxs = listOf(tx(6, fee = 500, txid = "f6", firstSeen = 1L)))
+
+        assertEquals(TxidRemoval(), generator.removeByTxid(listOf("f6")))
+
+        run.release()
+        assertEquals((1..5).toSet(), generator.trackedTransactions().keys)
+    }
+
+    @Test(timeout = 10_000)
+    fun reinstateQueuedAfterARemovalBringsTheTransactionBack() {
+        val generator = generator()
+        generator.make(base, maxUid = 5)
+        val run = HeldRun(generator)
+        generator.applyUpdates(removeTxs = listOf(3))
+
+        assertArrayEquals(IntArray(0), generator.reinstate(intArrayOf(3)))
+        assertArrayEquals(intArrayOf(99), generator.reinstate(intArrayOf(99)))
+
+        assertEquals(3, generator.getPendingUpdateCount())
+        run.release()
+        assertEquals((1..5).toSet(), generator.trackedTransactions().keys)
+    }
+
+    @Test(timeout = 10_000)
+    fun makeSupersedesQueuedBatches() {
+        val generator = generator()
+        generator.make(base, maxUid = 5)
+        val run = HeldRun(generator)
+        generator.applyUpdates(newTxs = listOf(seen(6, fee = 500)))
+
+        generator.applyMake(listOf(seen(7, fee = 700)), emptyList())
+
+        assertEquals(0, generator.getPendingUpdateCount())
+        run.release()
+        assertEquals(setOf(7), generator.trackedTransactions().keys)
+    }
+
+    @Test(timeout = 30_000)
+    fun interleavedComputesEndInTheSequentialState() {
+        val batches = (0 until 200).map { i ->
+            val uid = 10 + i
+            when (i % 4) {
+                0 -> Batch(newTxs = listOf(seen(uid, fee = 100L + i)))
+                1 -> Batch(newTxs = listOf(seen(uid, fee = 300L + i, inputs = intArrayOf(uid - 1))))
+                2 -> Batch(newTxs = listOf(seen(uid, fee = 50L + i)), removeTxs = listOf(uid - 2), removeDescendants = true)
+                else -> Batch(accelerations = listOf(ThreadAcceleration(uid - 1, i.toLong())))
+            }
+        }
+        val generator = generator()
+        generator.make(base, maxUid = 5)
+        val done = AtomicBoolean(false)
+        val computer = thread {
+            while (!done.get()) generator.compute()
+        }
+
+        batches.forEach { generator.submit(it) }
+        done.set(true)
+        computer.join()
+        generator.compute()
+
+        assertEquals(0, generator.getPendingUpdateCount())
+        assertSameState(sequential(batches), generator)
+    }
+}
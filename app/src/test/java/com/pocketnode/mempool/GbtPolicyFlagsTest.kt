@@ -0,0 +1,61 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Test
+import java.io.File
+
+class GbtPolicyFlagsTest {
+    /** 2 has a large OP_RETURN and 3 spends it; 4 has a bare multisig output. */
+    private val mempool = listOf(
+        tx(1, fee = 1_000),
+        tx(2, fee = 5_000, policyFlags = ThreadTransaction.POLICY_LARGE_DATACARRIER),
+        tx(3, fee = 4_000, inputs = intArrayOf(2)),
+        tx(4, fee = 3_000, policyFlags = ThreadTransaction.POLICY_BARE_MULTISIG)
+    )
+
+    private fun withMask(mask: Int) = GbtGenerator.create(GbtConfig(acceptedPolicyMask = mask)).apply { setSeed(1L) }
+
+    private fun reasons(result: GbtResult) = result.overflow.indices.associate { result.overflow[it] to result.overflowReasons[it] }
+
+    @Test
+    fun defaultMaskAcceptsEverything() {
+        val result = generator().make(mempool, maxUid = 4)!!
+
+        assertEquals(setOf(1, 2, 3, 4), result.blocks[0].toSet())
+        assertEquals(0, result.overflow.size)
+    }
+
+    @Test
+    fun strictMaskExcludesFlaggedTransactionsAndTheirDescendants() {
+        val result = withMask(0).make(mempool, maxUid = 4)!!
+
+        assertArrayEquals(intArrayOf(1), result.blocks[0])
+        assertEquals(mapOf(2 to GbtResult.OVERFLOW_NON_STANDARD, 3 to GbtResult.OVERFLOW_NON_STANDARD, 4 to GbtResult.OVERFLOW_NON_STANDARD), reasons(result))
+        // Excluded transactions don't count as purge pressure.
+        assertEquals(0.0, result.overflowTopRate, 0.0)
+    }
+
+    @Test
+    fun maskAcceptsOnlyTheTraitsItNames() {
+        val result = withMask(ThreadTransaction.POLICY_BARE_MULTISIG).make(mempool, maxUid = 4)!!
+
+        assertEquals(setOf(1, 4), result.blocks[0].toSet())
+        assertEquals(setOf(2, 3), reasons(result).keys)
+    }
+
+    @Test
+    fun flagsAndMaskSurviveRecordAndReplay() {
+        val file = File.createTempFile("gbt", ".rec").apply { deleteOnExit() }
+        val generator = withMask(ThreadTransaction.POLICY_BARE_MULTISIG)
+        generator.setRecording(file.path)
+        val live = generator.make(mempool, maxUid = 4)!!
+        generator.setRecording(null)
+
+        val replayed = GbtReplay.replayFile(file).single()!!
+
+        assertArrayEquals(live.blocks[0], replayed.blocks[0])
+        assertEquals(reasons(live), reasons(replayed))
+        assertEquals(ThreadTransaction.POLICY_LARGE_DATACARRIER, generator.trackedTransactions().getValue(2).policyFlags)
+    }
+}
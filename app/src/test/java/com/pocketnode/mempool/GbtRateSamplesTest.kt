@@ -0,0 +1,57 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertTrue
+import org.junit.Test
+
+class GbtRateSamplesTest {
+    @Test
+    fun samplesAreNonIncreasingAndEndAtEachBlocksCutoff() {
+        // 30 transactions at 10.01..10.30 sat/vB, three 5,000 WU blocks.
+        val result = generator(maxBlockWeight = GbtGenerator.MIN_BLOCK_WEIGHT)
+            .make((1..30).map { tx(it, fee = 1_000L + it) }, maxUid = 30)!!
+
+        assertEquals(3, result.blockRateSamples.size)
+        assertArrayEquals((30 downTo 19).map { 10.0 + it / 100.0 }.toDoubleArray(), result.blockRateSamples[0], 1e-9)
+        for (samples in result.blockRateSamples) {
+            for (i in 1 until samples.size) assertTrue(samples[i] <= samples[i - 1])
+        }
+        assertEquals(result.nextBlockCutoffRate, result.blockRateSamples[0].last(), 0.0)
+    }
+
+    @Test
+    fun largeBlocksAreDownsampledKeepingFirstAndLast() {
+        val count = 5_000
+        val result = generator().make((1..count).map { tx(it, fee = 1_000L + it) }, maxUid = count)!!
+
+        val samples = result.blockRateSamples[0]
+        assertEquals(count, result.blocks[0].size)
+        assertTrue(samples.size in 2..100)
+        assertEquals((1_000.0 + count) / 100, samples.first(), 0.0)
+        assertEquals(10.01, samples.last(), 0.0)
+        for (i in 1 until samples.size) assertTrue(samples[i] <= samples[i - 1])
+    }
+
+    @Test
+    fun rescoredChildCanRiseAboveThePackageBeforeIt() {
+        // 3 carries the parent at 10 sat/vB; 2 then goes in alone at 200 sat/vB.
+        val mempool = listOf(
+            tx(1, fee = 50_000, weight = 200_000),
+            tx(2, fee = 30_000, weight = 600, inputs = intArrayOf(1)),
+            tx(3, fee = 451_500, weight = 600, inputs = intArrayOf(1))
+        )
+
+        val result = generator().make(mempool, maxUid = 3)!!
+
+        assertArrayEquals(doubleArrayOf(10.0, 200.0), result.blockRateSamples[0], 0.0)
+    }
+
+    @Test
+    fun leftOutWithoutTheDetailFlag() {
+        val generator = generator()
+        generator.make((1..5).map { tx(it, fee = 1_000) }, maxUid = 5)
+
+        assertEquals(0, generator.compute(detail = GbtResult.DETAIL_BLOCKS)!!.blockRateSamples.size)
+    }
+}
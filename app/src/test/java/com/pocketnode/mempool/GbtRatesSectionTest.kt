@@ -0,0 +1,47 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Test
+
+class GbtRatesSectionTest {
+    /** Parent 1 at 1 sat/vB, its child 2 at 19 sat/vB, and the unrelated 3 at 5 sat/vB. */
+    private val mempool = listOf(tx(1, fee = 100), tx(2, fee = 1_900, inputs = intArrayOf(1)), tx(3, fee = 500))
+
+    private fun rows(result: GbtResult) = result.rates.associate { it[0].toInt() to it.drop(1) }
+
+    @Test
+    fun acceleratedCpfpClusterShowsBaseAndFinalRates() {
+        // The parent's bump lifts the package from 10 to 12.5 sat/vB.
+        val result = generator().make(mempool, listOf(ThreadAcceleration(1, 500)), maxUid = 3)!!
+
+        val rows = rows(result)
+        assertEquals(setOf(1, 2), rows.keys)
+        // Parent: 1 sat/vB without the bump, packed with its child at (600 + 1,900) / 200.
+        assertEquals(listOf(1.0, 12.5), rows[1])
+        // Child: its own 19 sat/vB, packed at the package rate.
+        assertEquals(listOf(19.0, 12.5), rows[2])
+    }
+
+    @Test
+    fun cpfpAloneReportsTheUnacceleratedPackageRate() {
+        val rows = rows(generator().make(mempool, maxUid = 3)!!)
+
+        assertEquals(listOf(1.0, 10.0), rows[1])
+        assertEquals(listOf(19.0, 10.0), rows[2])
+    }
+
+    @Test
+    fun accelerationAloneReportsTheBump() {
+        val rows = rows(generator().make(mempool, listOf(ThreadAcceleration(3, 1_000)), maxUid = 3)!!)
+
+        assertEquals(listOf(5.0, 15.0), rows[3])
+    }
+
+    @Test
+    fun unadjustedTransactionsAreLeftOut() {
+        val result = generator().make(listOf(tx(1, fee = 100), tx(2, fee = 200)), maxUid = 2)!!
+
+        assertArrayEquals(emptyArray<DoubleArray>(), result.rates)
+    }
+}
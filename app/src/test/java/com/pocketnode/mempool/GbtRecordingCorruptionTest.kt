@@ -0,0 +1,151 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertEquals
+import org.junit.Assert.fail
+import org.junit.Test
+import java.io.ByteArrayOutputStream
+import java.io.DataOutputStream
+import java.io.EOFException
+import java.io.File
+import java.io.IOException
+import kotlin.random.Random
+
+class GbtRecordingCorruptionTest {
+    private fun tempFile() = File.createTempFile("gbt", ".rec").apply { deleteOnExit() }
+
+    private fun replay(bytes: ByteArray): List<GbtResult?> = GbtReplay.replayFile(tempFile().apply { writeBytes(bytes) })
+
+    /** Header and default config, followed by [body]. */
+    private fun recording(body: DataOutputStream.() -> Unit): ByteArray {
+        val bytes = ByteArrayOutputStream()
+        DataOutputStream(bytes).apply {
+            writeInt(GbtRecorder.MAGIC)
+            writeInt(GbtRecorder.VERSION)
+            GbtRecorder.writeConfig(this, GbtConfig())
+            body()
+        }
+        return bytes.toByteArray()
+    }
+
+    private fun validRecording(): ByteArray {
+        val file = tempFile()
+        val generator = generator()
+        generator.setRecording(file.path)
+        generator.make((1..10).map { tx(it, fee = 100L * it, inputs = if (it % 3 == 0) intArrayOf(it - 1) else intArrayOf()) }, maxUid = 10)
+        generator.update(newTxs = listOf(tx(11, fee = 5_000, inputs = intArrayOf(1))), removeTxs = listOf(2), maxUid = 11)
+        generator.reset()
+        generator.update(newTxs = listOf(tx(12, fee = 700)), maxUid = 12)
+        generator.setRecording(null)
+        return file.readBytes()
+    }
+
+    private inline fun assertRejected(block: () -> Unit) {
+        try {
+            block()
+            fail()
+        } catch (e: IOException) {
+            // expected
+        }
+    }
+
+    @Test
+    fun hugeTransactionCountIsRejectedBeforeAllocating() {
+        assertRejected {
+            replay(recording {
+                writeByte(GbtRecorder.RECORD_MAKE)
+                writeInt(Int.MAX_VALUE)
+            })
+        }
+    }
+
+    @Test
+    fun negativeInputCountIsRejected() {
+        assertRejected {
+            replay(recording {
+                writeByte(GbtRecorder.RECORD_MAKE)
+                writeInt(1)
+                // uid, order, fee, weight, sigops, rate, then the input count
+                writeInt(1)
+                writeInt(0)
+                writeLong(100)
+                writeInt(400)
+                writeInt(0)
+                writeDouble(1.0)
+                writeInt(-1)
+            })
+        }
+    }
+
+    @Test
+    fun lengthWithinACorruptConfigCapRunsIntoTheEndOfFile() {
+        // A config whose caps were corrupted to the maximum still can't make the reader reserve them.
+        val bytes = ByteArrayOutputStream()
+        DataOutputStream(bytes).apply {
+            writeInt(GbtRecorder.MAGIC)
+            writeInt(GbtRecorder.VERSION)
+            GbtRecorder.writeConfig(this, GbtConfig(maxTxsPerCall = Int.MAX_VALUE, maxInputsPerTx = Int.MAX_VALUE))
+            writeByte(GbtRecorder.RECORD_UPDATE)
+            writeInt(Int.MAX_VALUE - 1)
+        }
+
+        try {
+            replay(bytes.toByteArray())
+            fail()
+        } catch (e: EOFException) {
+            // expected
+        }
+    }
+
+    @Test
+    fun badHeaderAndRecordTypeAreRejected() {
+        val valid = validRecording()
+        assertEquals(3, replay(valid).size)
+
+        assertRejected { replay(valid.copyOf().also { it[0] = 0 }) }
+        assertRejected { replay(valid.copyOf().also { it[7] = 99 }) }
+        assertRejected { replay(recording { writeByte(42) }) }
+    }
+
+    @Test
+    fun everyTruncationFailsCleanly() {
+        val valid = validRecording()
+        for (length in 0 until valid.size) {
+            try {
+                replay(valid.copyOf(length))
+            } catch (e: IOException) {
+                // expected for most cut points; a cut between records replays the prefix
+            }
+        }
+    }
+
+    /**
+     * Mutation fuzzing: random byte flips, inserts and cuts of a valid recording either replay
+     * or fail with a format error. A transaction the recorded strict mode rejects is the one
+     * other structured failure.
+     */
+    @Test
+    fun mutatedRecordingsNeverCrash() {
+        val valid = validRecording()
+        for (seed in 1L..500L) {
+            val random = Random(seed)
+            val mutated = valid.toMutableList()
+            repeat(random.nextInt(1, 6)) {
+                val at = random.nextInt(mutated.size)
+                when (random.nextInt(3)) {
+                    0 -> mutated[at] = random.nextInt(256).toByte()
+                    1 -> mutated.add(at, random.nextInt(256).toByte())
+                    else -> mutated.removeAt(at)
+                }
+            }
+            try {
+                replay(mutated.toByteArray())
+            } catch (e: IOException) {
+                // format error
+            } catch (e: GbtInvalidInputException) {
+                // strict mode rejecting a corrupted transaction
+            } catch (e: Throwable) {
+                throw AssertionError("seed $seed: ${e.javaClass.simpleName} ${e.message}", e)
+            }
+        }
+    }
+}
@@ -0,0 +1,68 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertTrue
+import org.junit.Test
+
+class GbtReinstateTest {
+    private val mempool = (1..30).map { uid ->
+        tx(uid, fee = 200L + 53L * uid % 900, inputs = if (uid % 4 == 0) intArrayOf(uid - 1) else intArrayOf())
+    }
+
+    private fun started(removedCacheSize: Int = GbtGenerator.DEFAULT_REMOVED_CACHE_SIZE): GbtGenerator =
+        GbtGenerator.create(GbtGenerator.MIN_BLOCK_WEIGHT, 0, removedCacheSize = removedCacheSize).apply {
+            setSeed(1L)
+            make(mempool, maxUid = 30)
+        }
+
+    @Test
+    fun reinstatingAMinedBlockRestoresTheTemplate() {
+        val generator = started()
+        val before = generator.compute()!!
+        val mined = before.blocks[0]
+
+        val after = generator.update(removeTxs = mined.toList(), maxUid = 30)!!
+        assertEquals(30 - mined.size, after.blocks.sumOf { it.size })
+
+        val missing = generator.reinstate(mined)
+        val restored = generator.compute()!!
+
+        assertArrayEquals(intArrayOf(), missing)
+        assertEquals(before.blocks.size, restored.blocks.size)
+        for (i in before.blocks.indices) assertArrayEquals(before.blocks[i], restored.blocks[i])
+        assertEquals(before.templateHash, restored.templateHash)
+        assertTrue(generator.verifyState().isConsistent)
+    }
+
+    @Test
+    fun evictedTransactionsAreReportedForResending() {
+        val generator = started(removedCacheSize = 2)
+
+        generator.applyUpdates(removeTxs = listOf(1, 2, 3))
+        val missing = generator.reinstate(intArrayOf(1, 2, 3))
+
+        // The oldest removal fell out of the cache.
+        assertArrayEquals(intArrayOf(1), missing)
+        assertEquals(29, generator.size())
+    }
+
+    @Test
+    fun trackedUidsAreNotReportedMissing() {
+        val generator = started()
+
+        assertArrayEquals(intArrayOf(99), generator.reinstate(intArrayOf(5, 99)))
+        assertEquals(30, generator.size())
+    }
+
+    @Test
+    fun resetClearsTheCache() {
+        val generator = started()
+        generator.applyUpdates(removeTxs = listOf(1, 2))
+
+        generator.reset()
+
+        assertArrayEquals(intArrayOf(1, 2), generator.reinstate(intArrayOf(1, 2)))
+        assertEquals(0, generator.size())
+    }
+}
@@ -0,0 +1,96 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertNull
+import org.junit.Assert.assertTrue
+import org.junit.Test
+
+class GbtRelativesTest {
+    /** 1 <- 2 <- 3, and 4 spending both 1 and 2. */
+    private fun started(): GbtGenerator = generator().apply {
+        make(
+            listOf(
+                tx(1, fee = 1_000), tx(2, fee = 1_000, inputs = intArrayOf(1)),
+                tx(3, fee = 1_000, inputs = intArrayOf(2)), tx(4, fee = 1_000, inputs = intArrayOf(1, 2))
+            ),
+            maxUid = 4
+        )
+    }
+
+    private fun GbtGenerator.descendants(uid: Int) = getDescendants(uid)?.sorted()
+    private fun GbtGenerator.ancestors(uid: Int) = getAncestors(uid)?.sorted()
+
+    @Test
+    fun queriesWalkTheWholeGraph() {
+        val generator = started()
+
+        assertEquals(listOf(2, 3, 4), generator.descendants(1))
+        assertEquals(listOf(3, 4), generator.descendants(2))
+        assertEquals(listOf(1, 2), generator.ancestors(3))
+        assertEquals(listOf(1, 2), generator.ancestors(4))
+        assertArrayEquals(intArrayOf(), generator.getAncestors(1))
+        assertNull(generator.getAncestors(99))
+        assertNull(generator.getDescendants(99))
+    }
+
+    @Test
+    fun insertionExtendsTheIndex() {
+        val generator = started()
+
+        generator.applyUpdates(newTxs = listOf(tx(5, fee = 1_000, inputs = intArrayOf(3))))
+
+        assertEquals(listOf(2, 3, 4, 5), generator.descendants(1))
+        assertEquals(listOf(5), generator.descendants(3))
+        assertEquals(listOf(1, 2, 3), generator.ancestors(5))
+        assertTrue(generator.verifyState().isConsistent)
+    }
+
+    @Test
+    fun childArrivingBeforeItsParentIsLinkedOnceTheParentArrives() {
+        val generator = generator()
+        generator.make(listOf(tx(2, fee = 1_000, inputs = intArrayOf(1))), maxUid = 2)
+        assertArrayEquals(intArrayOf(), generator.getAncestors(2))
+
+        generator.applyUpdates(newTxs = listOf(tx(1, fee = 1_000)))
+
+        assertEquals(listOf(2), generator.descendants(1))
+        assertEquals(listOf(1), generator.ancestors(2))
+        assertTrue(generator.verifyState().isConsistent)
+    }
+
+    @Test
+    fun removalUnlinksTheTransaction() {
+        val generator = started()
+
+        generator.applyUpdates(removeTxs = listOf(2))
+
+        assertNull(generator.getDescendants(2))
+        assertEquals(listOf(4), generator.descendants(1))
+        // 3 now spends an untracked parent.
+        assertArrayEquals(intArrayOf(), generator.getAncestors(3))
+        assertEquals(listOf(1), generator.ancestors(4))
+        assertEquals(0, generator.verifyState().childIndexMismatchCount)
+
+        // The index keeps no stale entry for 2: re-adding it with a different parent
+        // links only its current relatives.
+        generator.applyUpdates(newTxs = listOf(tx(2, fee = 1_000)))
+        assertEquals(listOf(3, 4), generator.descendants(2))
+        assertEquals(listOf(4), generator.descendants(1))
+        assertTrue(generator.verifyState().isConsistent)
+    }
+
+    @Test
+    fun resetClearsTheIndex() {
+        val generator = started()
+
+        generator.reset()
+
+        assertNull(generator.getDescendants(1))
+        generator.applyUpdates(newTxs = listOf(tx(1, fee = 1_000), tx(3, fee = 1_000)))
+        // 3 spent 2 before the reset; nothing of that survives.
+        assertEquals(listOf<Int>(), generator.descendants(1))
+        assertArrayEquals(intArrayOf(), generator.getAncestors(3))
+        assertTrue(generator.verifyState().isConsistent)
+    }
+}
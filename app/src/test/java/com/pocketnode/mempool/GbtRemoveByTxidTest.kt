@@ -0,0 +1,70 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertTrue
+import org.junit.Test
+
+class GbtRemoveByTxidTest {
+    private fun txid(n: Int) = "%064x".format(n)
+
+    /** 1 (txid 1) spent by 2 (no txid); 3 (txid 3) unrelated. */
+    private fun tracked() = generator().apply {
+        make(
+            listOf(tx(1, fee = 1_000, txid = txid(1)), tx(2, fee = 1_000, inputs = intArrayOf(1)), tx(3, fee = 1_000, txid = txid(3))),
+            maxUid = 3
+        )
+    }
+
+    @Test
+    fun hitsRemoveWithDescendantsAndMissesAreReported() {
+        val generator = tracked()
+
+        val removal = generator.removeByTxid(listOf(txid(1), txid(7)))
+
+        assertArrayEquals(intArrayOf(2, 1), removal.removedUids)
+        assertArrayEquals(arrayOf(txid(7)), removal.missingTxids)
+        assertEquals(setOf(3), generator.trackedTransactions().keys)
+        assertTrue(generator.verifyState().isConsistent)
+    }
+
+    @Test
+    fun removedTxidIsMissingTheSecondTime() {
+        val generator = tracked()
+        generator.removeByTxid(listOf(txid(3)))
+
+        val again = generator.removeByTxid(listOf(txid(3)))
+
+        assertEquals(0, again.removedUids.size)
+        assertArrayEquals(arrayOf(txid(3)), again.missingTxids)
+    }
+
+    @Test
+    fun sharedPrefixResolvesByTheFullTxid() {
+        // Same first 8 bytes, different after.
+        val a = "00112233445566778899aabbccddeeff00112233445566778899aabbccddeeff"
+        val b = "0011223344556677ffeeddccbbaa998877665544332211000011223344556677"
+        val c = "0011223344556677000000000000000000000000000000000000000000000000"
+        val generator = generator()
+        generator.make(listOf(tx(1, fee = 1_000, txid = a), tx(2, fee = 1_000, txid = b)), maxUid = 2)
+
+        val removal = generator.removeByTxid(listOf(b, c))
+
+        assertArrayEquals(intArrayOf(2), removal.removedUids)
+        assertArrayEquals(arrayOf(c), removal.missingTxids)
+        assertEquals(setOf(1), generator.trackedTransactions().keys)
+    }
+
+    @Test
+    fun indexFollowsReinstateAndReset() {
+        val generator = tracked()
+        generator.removeByTxid(listOf(txid(3)))
+        generator.reinstate(intArrayOf(3))
+
+        assertArrayEquals(intArrayOf(3), generator.removeByTxid(listOf(txid(3))).removedUids)
+
+        generator.reset()
+
+        assertArrayEquals(arrayOf(txid(1)), generator.removeByTxid(listOf(txid(1))).missingTxids)
+    }
+}
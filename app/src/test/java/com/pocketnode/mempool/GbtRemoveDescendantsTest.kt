@@ -0,0 +1,80 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertTrue
+import org.junit.Test
+
+class GbtRemoveDescendantsTest {
+    /** Four levels: 1 <- 2 <- 3 <- 4, plus 5 spending 2 and the unrelated 9. */
+    private fun started(): GbtGenerator = generator().apply {
+        make(
+            listOf(
+                tx(1, fee = 1_000), tx(2, fee = 1_000, inputs = intArrayOf(1)), tx(3, fee = 1_000, inputs = intArrayOf(2)),
+                tx(4, fee = 1_000, inputs = intArrayOf(3)), tx(5, fee = 1_000, inputs = intArrayOf(2)), tx(9, fee = 1_000)
+            ),
+            maxUid = 9
+        )
+    }
+
+    /** Every uid comes after all of its descendants among [removed]. */
+    private fun assertLeavesFirst(removed: IntArray, parentOf: Map<Int, Int>) {
+        for ((child, parent) in parentOf) {
+            if (child in removed && parent in removed) assertTrue(removed.indexOf(child) < removed.indexOf(parent))
+        }
+    }
+
+    @Test
+    fun removingTheRootRemovesEveryLevel() {
+        val generator = started()
+
+        val removed = generator.removeWithDescendants(intArrayOf(1))
+
+        assertEquals(setOf(1, 2, 3, 4, 5), removed.toSet())
+        assertEquals(5, removed.size)
+        assertLeavesFirst(removed, mapOf(2 to 1, 3 to 2, 4 to 3, 5 to 2))
+        assertEquals(setOf(9), generator.trackedTransactions().keys)
+        assertTrue(generator.verifyState().isConsistent)
+    }
+
+    @Test
+    fun removingMidChainKeepsTheAncestors() {
+        val generator = started()
+
+        val removed = generator.removeWithDescendants(intArrayOf(3))
+
+        assertArrayEquals(intArrayOf(4, 3), removed)
+        assertEquals(setOf(1, 2, 5, 9), generator.trackedTransactions().keys)
+    }
+
+    @Test
+    fun unknownAndOverlappingUidsAreReportedOnce() {
+        val generator = started()
+
+        val removed = generator.removeWithDescendants(intArrayOf(3, 42, 2, 4))
+
+        assertEquals(setOf(2, 3, 4, 5), removed.toSet())
+        assertEquals(4, removed.size)
+    }
+
+    @Test
+    fun updateOptionRemovesTheClosureToo() {
+        val generator = started()
+
+        generator.applyUpdates(removeTxs = listOf(1), removeDescendants = true)
+
+        assertEquals(setOf(9), generator.trackedTransactions().keys)
+        // Removed leaves-first, so all of it can come back.
+        assertArrayEquals(intArrayOf(), generator.reinstate(intArrayOf(1, 2, 3, 4, 5)))
+        assertEquals(6, generator.size())
+    }
+
+    @Test
+    fun plainRemovalLeavesDescendantsBehind() {
+        val generator = started()
+
+        generator.applyUpdates(removeTxs = listOf(1))
+
+        assertEquals(setOf(2, 3, 4, 5, 9), generator.trackedTransactions().keys)
+    }
+}
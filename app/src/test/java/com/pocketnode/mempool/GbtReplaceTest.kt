@@ -0,0 +1,72 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertFalse
+import org.junit.Assert.assertTrue
+import org.junit.Assert.fail
+import org.junit.Test
+import java.io.File
+
+class GbtReplaceTest {
+    private val mempool = listOf(tx(1, fee = 1_000, firstSeen = 1_000), tx(2, fee = 2_000, firstSeen = 1_000))
+
+    @Test
+    fun identicalReinsertIsANoOp() {
+        val generator = generator()
+        generator.make(mempool, maxUid = 2)
+        val counter = generator.getChangeCounter()
+
+        // Without a first-seen time the tracked one is kept, so this is still identical.
+        generator.applyUpdates(newTxs = listOf(tx(1, fee = 1_000)))
+
+        assertEquals(counter, generator.getChangeCounter())
+        assertEquals(0L, generator.getCounters()[GbtGenerator.COUNTER_TXS_REPLACED])
+    }
+
+    @Test
+    fun differingReinsertReplacesAndCounts() {
+        val generator = generator()
+        generator.make(mempool, maxUid = 2)
+
+        val result = generator.update(newTxs = listOf(tx(1, fee = 5_000)), maxUid = 2)!!
+
+        assertFalse(result.fromCache)
+        assertArrayEquals(intArrayOf(1, 2), result.blocks[0])
+        assertEquals(1L, generator.getCounters()[GbtGenerator.COUNTER_TXS_REPLACED])
+    }
+
+    @Test
+    fun strictConflictThrowsBeforeAnythingIsApplied() {
+        val file = File.createTempFile("gbt", ".rec").apply { deleteOnExit() }
+        val generator = generator(strict = true)
+        generator.make(mempool, maxUid = 2)
+        generator.setRecording(file.path)
+        val recorded = file.length()
+        val counter = generator.getChangeCounter()
+
+        try {
+            generator.applyUpdates(newTxs = listOf(tx(3, fee = 100), tx(1, fee = 5_000)), removeTxs = listOf(2))
+            fail()
+        } catch (e: GbtInvalidInputException) {
+            assertEquals(1, e.uid)
+        }
+
+        assertEquals(counter, generator.getChangeCounter())
+        assertEquals(recorded, file.length())
+        assertTrue(generator.getAncestors(2) != null)
+        assertTrue(generator.getAncestors(3) == null)
+        assertTrue(generator.compute()!!.fromCache)
+    }
+
+    @Test
+    fun removeAndReinsertInOneBatchIsNotAConflict() {
+        val generator = generator(strict = true)
+        generator.make(mempool, maxUid = 2)
+
+        val result = generator.update(newTxs = listOf(tx(1, fee = 5_000)), removeTxs = listOf(1), maxUid = 2)!!
+
+        assertEquals(0L, generator.getCounters()[GbtGenerator.COUNTER_TXS_REPLACED])
+        assertArrayEquals(intArrayOf(1, 2), result.blocks[0])
+    }
+}
@@ -0,0 +1,99 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertTrue
+import org.junit.Test
+import java.io.File
+
+class GbtReplayTest {
+    private fun tempFile() = File.createTempFile("gbt", ".rec").apply { deleteOnExit() }
+
+    private fun hashes(results: List<GbtResult?>) = results.map { it!!.templateHash }
+
+    /**
+     * golden.gbtrec, recorded with 5,000 WU blocks and maxBlocks 2: a make of uids 1..14 paying
+     * 1..14 sat/vB; an update adding 15 (50 sat/vB, spending 1), removing 2 and accelerating 3
+     * by 2,000 sats; a reset; a make of 20 and its child 21. Each step is followed by a compute.
+     */
+    @Test
+    fun goldenRecordingReplaysToTheStoredTemplates() {
+        val file = File(javaClass.getResource("golden.gbtrec")!!.toURI())
+
+        val results = GbtReplay.replayFile(file).map { it!! }
+
+        assertEquals(3, results.size)
+        assertArrayEquals(intArrayOf(14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4, 3), results[0].blocks[0])
+        assertArrayEquals(intArrayOf(2, 1), results[0].blocks[1])
+        assertArrayEquals(intArrayOf(1, 15, 3, 14, 13, 12, 11, 10, 9, 8, 7, 6), results[1].blocks[0])
+        assertArrayEquals(intArrayOf(5, 4), results[1].blocks[1])
+        assertArrayEquals(intArrayOf(20, 21), results[2].blocks[0])
+        assertEquals(listOf(-8155765198629706861L, 4617401850687119458L, 6973240558700852955L), hashes(results))
+    }
+
+    @Test
+    fun replayUsesTheRecordedConfig() {
+        val file = tempFile()
+        val generator = generator(maxBlockWeight = GbtGenerator.MIN_BLOCK_WEIGHT, maxBlocks = 1)
+        generator.setRecording(file.path)
+        val live = generator.make((1..13).map { tx(it, fee = 100L * it) }, maxUid = 13)!!
+        generator.setRecording(null)
+
+        val replayed = GbtReplay.replayFile(file).single()!!
+
+        assertEquals(live.templateHash, replayed.templateHash)
+        assertArrayEquals(intArrayOf(1), replayed.overflow)
+    }
+
+    @Test
+    fun resetAndReinstateAreReplayed() {
+        val file = tempFile()
+        val generator = generator()
+        generator.setRecording(file.path)
+        val live = mutableListOf<GbtResult?>()
+        live.add(generator.make(listOf(tx(1, fee = 1_000), tx(2, fee = 2_000)), maxUid = 2))
+        live.add(generator.update(removeTxs = listOf(1), maxUid = 2))
+        generator.reinstate(intArrayOf(1))
+        live.add(generator.compute())
+        generator.reset()
+        live.add(generator.update(newTxs = listOf(tx(3, fee = 500)), maxUid = 3))
+        generator.setRecording(null)
+
+        val replayed = GbtReplay.replayFile(file)
+
+        assertEquals(hashes(live), hashes(replayed))
+        assertArrayEquals(intArrayOf(2, 1), replayed[2]!!.blocks[0])
+        assertArrayEquals(intArrayOf(3), replayed[3]!!.blocks[0])
+    }
+
+    @Test
+    fun recordingStartedMidStreamReplaysFromItsBaseState() {
+        val file = tempFile()
+        val generator = generator()
+        generator.make(listOf(tx(1, fee = 1_000), tx(2, fee = 2_000, inputs = intArrayOf(1))), maxUid = 2)
+        generator.update(accelerations = listOf(ThreadAcceleration(1, 5_000)), maxUid = 2)
+        generator.setRecording(file.path)
+
+        val live = generator.update(newTxs = listOf(tx(3, fee = 3_000)), maxUid = 3)
+        generator.setRecording(null)
+
+        assertEquals(hashes(listOf(live)), hashes(GbtReplay.replayFile(file)))
+    }
+
+    @Test
+    fun rotatedFileStartsFromTheStateAtRotation() {
+        val file = tempFile()
+        val generator = generator()
+        generator.setRecording(file.path, maxBytes = 1)
+        generator.make(listOf(tx(1, fee = 1_000)), maxUid = 1)
+        generator.update(newTxs = listOf(tx(2, fee = 2_000)), maxUid = 2)
+        val live = generator.update(newTxs = listOf(tx(3, fee = 3_000)), removeTxs = listOf(1), maxUid = 3)
+        generator.setRecording(null)
+
+        val previous = File(file.path + ".1").apply { deleteOnExit() }
+        assertTrue(previous.exists())
+        val replayed = GbtReplay.replayFile(file)
+        assertEquals(live!!.templateHash, replayed.last()!!.templateHash)
+        assertArrayEquals(intArrayOf(3, 2), replayed.last()!!.blocks[0])
+    }
+}
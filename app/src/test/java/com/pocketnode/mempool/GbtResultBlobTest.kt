@@ -0,0 +1,89 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertNotNull
+import org.junit.Assert.assertTrue
+import org.junit.Test
+import java.io.EOFException
+import java.nio.ByteBuffer
+
+class GbtResultBlobTest {
+    /** A result with every optional section filled: txids, overflow, CPFP, diff, watched and own positions. */
+    private fun fullResult(): GbtResult {
+        val generator = GbtGenerator.create(GbtGenerator.MIN_BLOCK_WEIGHT, 2, includeTxids = true).apply { setSeed(1L) }
+        val mempool = (1..30).map { uid ->
+            tx(uid, fee = 500L + 37L * uid % 400, inputs = if (uid % 3 == 0) intArrayOf(uid - 1) else intArrayOf(), txid = "%064x".format(uid))
+        }
+        generator.setWatched(intArrayOf(1, 2, 31))
+        generator.setOwnTransactions(intArrayOf(2, 3))
+        generator.make(mempool, listOf(ThreadAcceleration(4, 2_000)), maxUid = 30)
+        return generator.update(newTxs = listOf(tx(31, fee = 9_000, txid = "%064x".format(31))), removeTxs = listOf(5), maxUid = 31)!!
+    }
+
+    @Test
+    fun roundTripKeepsEverySection() {
+        val result = fullResult()
+        assertTrue(result.clusters.isNotEmpty() && result.rates.isNotEmpty() && result.overflow.isNotEmpty())
+        assertTrue(result.blockTxids.isNotEmpty() && result.blockRateSamples.isNotEmpty())
+        assertNotNull(result.txStats)
+        assertNotNull(result.diff)
+        assertNotNull(result.watchedChanges)
+        assertNotNull(result.ownTxPositions)
+
+        assertEquals(result, GbtResultBlob.decode(GbtResultBlob.encode(result)))
+    }
+
+    @Test
+    fun blobRespectsTheDetailMask() {
+        val generator = generator()
+        generator.make((1..10).map { tx(it, fee = 1_000L + it, inputs = if (it == 10) intArrayOf(9) else intArrayOf()) }, maxUid = 10)
+
+        val slim = GbtResultBlob.decode(generator.computeToBlob(GbtResult.DETAIL_BLOCKS)!!)
+        val full = GbtResultBlob.decode(generator.computeToBlob()!!)
+
+        assertEquals(generator.compute(detail = GbtResult.DETAIL_BLOCKS), slim)
+        assertTrue(slim.clusters.isEmpty() && slim.rates.isEmpty())
+        assertTrue(full.clusters.isNotEmpty() && full.rates.isNotEmpty())
+        assertTrue(GbtResultBlob.encode(slim).size < GbtResultBlob.encode(full).size)
+    }
+
+    @Test(expected = GbtFormatException::class)
+    fun wrongMagicThrows() {
+        val blob = GbtResultBlob.encode(GbtResult())
+        blob[0] = 0
+
+        GbtResultBlob.decode(blob)
+    }
+
+    @Test(expected = GbtFormatException::class)
+    fun unknownVersionThrows() {
+        val blob = GbtResultBlob.encode(GbtResult())
+        ByteBuffer.wrap(blob).putInt(4, 999)
+
+        GbtResultBlob.decode(blob)
+    }
+
+    @Test(expected = EOFException::class)
+    fun truncatedBlobThrows() {
+        val blob = GbtResultBlob.encode(fullResult())
+
+        // Cut inside the final field.
+        GbtResultBlob.decode(blob.copyOf(blob.size - 1))
+    }
+
+    @Test(expected = GbtFormatException::class)
+    fun trailingDataThrows() {
+        val blob = GbtResultBlob.encode(GbtResult())
+
+        GbtResultBlob.decode(blob + 0)
+    }
+
+    @Test(expected = GbtFormatException::class)
+    fun oversizedLengthThrows() {
+        // The block list claims more entries than the blob could hold.
+        val blob = GbtResultBlob.encode(GbtResult())
+        ByteBuffer.wrap(blob).putInt(8, Int.MAX_VALUE)
+
+        GbtResultBlob.decode(blob)
+    }
+}
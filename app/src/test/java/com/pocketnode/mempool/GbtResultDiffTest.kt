@@ -0,0 +1,60 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Test
+
+class GbtResultDiffTest {
+    // 5,000 WU blocks hold twelve 400 WU transactions.
+    private fun mempool() = (1..24).map { tx(it, fee = 1_000L * (25 - it)) }
+
+    @Test
+    fun firstRunReportsEverythingAsAdded() {
+        val result = generator(maxBlockWeight = 5_000).make(mempool(), maxUid = 24)!!
+
+        val diff = result.diff!!
+        assertArrayEquals((1..24).toList().toIntArray(), diff.addedUids.sortedArray())
+        assertEquals(0, diff.removedUids.size)
+        assertEquals(0, diff.movedUids.size)
+    }
+
+    @Test
+    fun smallUpdateReportsOnlyWhatChanged() {
+        val generator = generator(maxBlockWeight = 5_000)
+        generator.make(mempool(), maxUid = 24)
+
+        // Uid 25 takes the slot uid 1 leaves in block 0, so nothing else moves.
+        val result = generator.update(newTxs = listOf(tx(25, fee = 100_000)), removeTxs = listOf(1), maxUid = 25)!!
+
+        val diff = result.diff!!
+        assertArrayEquals(intArrayOf(25), diff.addedUids)
+        assertArrayEquals(intArrayOf(1), diff.removedUids)
+        assertArrayEquals(intArrayOf(), diff.movedUids)
+    }
+
+    @Test
+    fun movedTransactionsCarryBothBlocks() {
+        val generator = generator(maxBlockWeight = 5_000)
+        generator.make(mempool(), maxUid = 24)
+
+        // Two new top payers push the last two transactions of blocks 0 and 1 one block down.
+        val result = generator.update(newTxs = listOf(tx(25, fee = 100_000), tx(26, fee = 90_000)), maxUid = 26)!!
+
+        val diff = result.diff!!
+        assertArrayEquals(intArrayOf(25, 26), diff.addedUids.sortedArray())
+        assertArrayEquals(intArrayOf(), diff.removedUids)
+        val moves = diff.movedUids.indices.associate { diff.movedUids[it] to (diff.movedFromBlock[it] to diff.movedToBlock[it]) }
+        assertEquals(mapOf(11 to (0 to 1), 12 to (0 to 1), 23 to (1 to 2), 24 to (1 to 2)), moves)
+    }
+
+    @Test
+    fun resetStartsOverWithEverythingAdded() {
+        val generator = generator(maxBlockWeight = 5_000)
+        generator.make(mempool(), maxUid = 24)
+        generator.reset()
+
+        val result = generator.make(mempool().take(3), maxUid = 3)!!
+
+        assertArrayEquals(intArrayOf(1, 2, 3), result.diff!!.addedUids.sortedArray())
+    }
+}
@@ -0,0 +1,77 @@
+package com.pocketnode.mempool
+
+import org.json.JSONObject
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertTrue
+import org.junit.Test
+import java.io.File
+import java.util.concurrent.LinkedBlockingQueue
+import java.util.concurrent.TimeUnit
+
+class GbtRunIdTest {
+    @Test
+    fun sequentialRunsGetIncreasingIds() {
+        val generator = generator()
+
+        val first = generator.make(listOf(tx(1, fee = 1_000)), maxUid = 1)!!
+        val second = generator.update(newTxs = listOf(tx(2, fee = 1_000)), maxUid = 2)!!
+
+        assertTrue(first.runId > 0)
+        assertTrue(second.runId > first.runId)
+        assertEquals(second.runId, generator.getResultRunId())
+    }
+
+    @Test
+    fun cachedResultKeepsItsRunId() {
+        val generator = generator()
+        val fresh = generator.make(listOf(tx(1, fee = 1_000)), maxUid = 1)!!
+
+        val cached = generator.compute()!!
+
+        assertTrue(cached.fromCache)
+        assertEquals(fresh.runId, cached.runId)
+    }
+
+    @Test
+    fun detachedRunTakesAnIdWithoutReplacingTheRetainedOne() {
+        val generator = generator()
+        val fresh = generator.make(listOf(tx(1, fee = 1_000)), maxUid = 1)!!
+
+        val detached = generator.compute(maxBlocksOverride = 1)!!
+
+        assertTrue(detached.runId > fresh.runId)
+        assertEquals(fresh.runId, generator.getResultRunId())
+        assertEquals(detached.runId + 1, generator.update(newTxs = listOf(tx(2, fee = 1_000)), maxUid = 2)!!.runId)
+    }
+
+    @Test
+    fun traceEventsCarryTheRunId() {
+        val file = File.createTempFile("gbt", ".json").apply { deleteOnExit() }
+        val generator = generator()
+        generator.startTrace(file.path)
+
+        val result = generator.make((1..3).map { tx(it, fee = 1_000) }, maxUid = 3)!!
+        generator.stopTrace()
+
+        val events = JSONObject(file.readText()).getJSONArray("traceEvents")
+        assertTrue(events.length() > 0)
+        for (i in 0 until events.length()) {
+            assertEquals(result.runId, events.getJSONObject(i).getJSONObject("args").getLong("run"))
+        }
+    }
+
+    @Test(timeout = 10_000)
+    fun autoComputeResultsMatchTheRetainedRun() {
+        val generator = generator()
+        val made = generator.make(listOf(tx(1, fee = 1_000)), maxUid = 1)!!
+        val results = LinkedBlockingQueue<Long>()
+        generator.startAutoCompute(10) { results.add(it.runId) }
+
+        generator.applyUpdates(newTxs = listOf(tx(2, fee = 1_000)))
+        val runId = results.poll(5, TimeUnit.SECONDS)!!
+        generator.stopAutoCompute()
+
+        assertTrue(runId > made.runId)
+        assertEquals(generator.getResultRunId(), runId)
+    }
+}
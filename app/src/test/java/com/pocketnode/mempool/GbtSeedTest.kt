@@ -0,0 +1,54 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertNotEquals
+import org.junit.Test
+
+class GbtSeedTest {
+    /** Eleven clear winners, then 12 and 13 tied for the last slot of a 5,000 WU block. */
+    private val contested = (1..11).map { tx(it, fee = 10_000) } + tx(12, fee = 1_000) + tx(13, fee = 1_000)
+
+    private fun seeded(seed: Long) = GbtGenerator.create(GbtGenerator.MIN_BLOCK_WEIGHT, 0).apply {
+        setSeed(seed)
+        make(contested, maxUid = 13)
+    }
+
+    @Test
+    fun differentSeedsGiveDifferentProbabilities() {
+        val a = seeded(7L).computeEnsemble(200)
+        val b = seeded(8L).computeEnsemble(200)
+
+        assertNotEquals(a.blockZero.asList(), b.blockZero.asList())
+    }
+
+    @Test
+    fun reseedingReplaysTheSameRuns() {
+        val generator = seeded(7L)
+        val first = generator.computeEnsemble(64)
+        generator.computeEnsemble(64)
+
+        generator.setSeed(7L)
+
+        assertEquals(first, generator.computeEnsemble(64))
+    }
+
+    @Test
+    fun resultsRecordTheSeed() {
+        val generator = seeded(42L)
+
+        assertEquals(42L, generator.compute()!!.seed)
+        assertEquals(42L, generator.computeEnsemble(4).seed)
+
+        generator.setSeed(43L)
+
+        assertEquals(43L, generator.update(newTxs = listOf(tx(14, fee = 1_000)), maxUid = 14)!!.seed)
+    }
+
+    @Test
+    fun unseededGeneratorsDrawTheirOwnSeed() {
+        val a = GbtGenerator.create(GbtGenerator.MIN_BLOCK_WEIGHT, 0).make(contested, maxUid = 13)!!
+        val b = GbtGenerator.create(GbtGenerator.MIN_BLOCK_WEIGHT, 0).make(contested, maxUid = 13)!!
+
+        assertNotEquals(a.seed, b.seed)
+    }
+}
@@ -0,0 +1,122 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertTrue
+import org.junit.Test
+import java.util.Random
+
+class GbtSelectionStrategyTest {
+    private fun generator(strategy: GbtSelectionStrategy, maxBlockWeight: Int) = GbtGenerator.create(
+        GbtConfig(maxBlockWeight = maxBlockWeight, maxBlocks = 0, strategy = strategy)
+    )
+
+    /**
+     * Reference miner written from Core's BlockAssembler rather than from the generator:
+     * recomputes every unselected ancestor set from scratch and picks the best score under
+     * CompareTxMemPoolEntryByAncestorFee, min(own rate, ancestor package rate). A package that
+     * doesn't fit is skipped until one of its ancestors is selected or the next block starts.
+     */
+    private fun coreBlocks(txs: List<ThreadTransaction>, maxBlockWeight: Int): List<Set<Int>> {
+        val byUid = txs.associateBy { it.uid }
+        val selected = HashSet<Int>()
+        fun unselectedAncestors(uid: Int): Set<Int> {
+            val found = HashSet<Int>()
+            val stack = ArrayDeque(byUid.getValue(uid).inputs.asList())
+            while (stack.isNotEmpty()) {
+                val parent = stack.removeLast()
+                if (parent !in byUid || parent in selected || !found.add(parent)) continue
+                stack.addAll(byUid.getValue(parent).inputs.asList())
+            }
+            return found
+        }
+        fun score(tx: ThreadTransaction, ancestors: Set<Int>): Double {
+            val own = tx.fee.toDouble() / weightToVsize(tx.weight)
+            val fee = tx.fee + ancestors.sumOf { byUid.getValue(it).fee }
+            val weight = tx.weight + ancestors.sumOf { byUid.getValue(it).weight }
+            return minOf(own, fee.toDouble() / weightToVsize(weight))
+        }
+        val ranking = compareByDescending<Pair<ThreadTransaction, Double>> { it.second }
+            .thenBy { it.first.order }
+            .thenBy { it.first.uid }
+        val blocks = mutableListOf<Set<Int>>()
+        while (selected.size < txs.size) {
+            val block = HashSet<Int>()
+            var weight = 0
+            // Uid -> unselected ancestor count when its package last didn't fit.
+            val skipped = HashMap<Int, Int>()
+            while (true) {
+                val (best, _) = txs.asSequence()
+                    .filter { it.uid !in selected }
+                    .map { it to unselectedAncestors(it.uid) }
+                    .filter { (tx, ancestors) -> skipped[tx.uid] != ancestors.size }
+                    .map { (tx, ancestors) -> tx to score(tx, ancestors) }
+                    .sortedWith(ranking)
+                    .firstOrNull() ?: break
+                val members = unselectedAncestors(best.uid) + best.uid
+                val packageWeight = members.sumOf { byUid.getValue(it).weight }
+                if (weight + packageWeight > maxBlockWeight) {
+                    skipped[best.uid] = members.size - 1
+                    continue
+                }
+                weight += packageWeight
+                block.addAll(members)
+                selected.addAll(members)
+            }
+            if (block.isEmpty()) break
+            blocks.add(block)
+        }
+        return blocks
+    }
+
+    /** Random DAG: weights in multiples of 4 WU, up to two in-mempool parents among earlier uids. */
+    private fun randomMempool(random: Random, size: Int): List<ThreadTransaction> = (1..size).map { uid ->
+        val parents = if (uid == 1) intArrayOf() else IntArray(random.nextInt(3)) { 1 + random.nextInt(uid - 1) }.distinct().toIntArray()
+        val weight = 4 * (100 + random.nextInt(900))
+        // Some zero-fee and cheap parents, so children have to pay for them.
+        val fee = if (random.nextInt(5) == 0) 0L else random.nextInt(weight * 5).toLong()
+        tx(uid, fee = fee, weight = weight, inputs = parents)
+    }
+
+    @Test
+    fun ancestorScoreMatchesTheReferenceMiner() {
+        for (seed in 1L..20L) {
+            val random = Random(seed)
+            val maxBlockWeight = 20_000 + random.nextInt(20_000)
+            val mempool = randomMempool(random, 80)
+            val generator = generator(GbtSelectionStrategy.ANCESTOR_SCORE, maxBlockWeight)
+
+            val result = generator.make(mempool, maxUid = mempool.size)!!
+
+            assertEquals("seed $seed", coreBlocks(mempool, maxBlockWeight), result.blocks.map { it.toSet() })
+            assertEquals("seed $seed", 0, result.overflow.size)
+        }
+    }
+
+    @Test
+    fun ancestorScoreTakesTheLowerOfOwnAndPackageRate() {
+        val strategy = GbtSelectionStrategy.ANCESTOR_SCORE
+
+        // 1 sat/vB child of a 50 sat/vB parent: the package's 25.5 doesn't lift it.
+        assertEquals(1.0, strategy.score(99.0, 100, 100, 5_100, 200, hasAncestors = true), 0.0)
+        // 30 sat/vB child of a zero-fee parent: held back to the package's 15.
+        assertEquals(15.0, strategy.score(99.0, 3_000, 100, 3_000, 200, hasAncestors = true), 0.0)
+        // No ancestors: the provided rate is ignored either way.
+        assertEquals(7.0, strategy.score(99.0, 700, 100, 700, 100, hasAncestors = false), 0.0)
+    }
+
+    @Test
+    fun effectiveRateUsesProvidedRatesWhereAncestorScoreDoesNot() {
+        // Twelve 400 WU slots for thirteen transactions. 2 pays 1 sat/vB but comes with a provided
+        // 40 sat/vB, e.g. lifted by out-of-band CPFP data; 3 at 10 sat/vB is the cheapest otherwise.
+        val txs = listOf(tx(1, fee = 2_000), tx(2, fee = 100).copy(effectiveFeePerVsize = 40.0), tx(3, fee = 1_000)) +
+            (10 until 20).map { tx(it, fee = 1_500) }
+        val weight = GbtGenerator.MIN_BLOCK_WEIGHT
+
+        val byEffective = generator(GbtSelectionStrategy.EFFECTIVE_RATE, weight).make(txs, maxUid = 19)!!
+        val byAncestor = generator(GbtSelectionStrategy.ANCESTOR_SCORE, weight).make(txs, maxUid = 19)!!
+
+        assertTrue(2 in byEffective.blocks[0] && 3 !in byEffective.blocks[0])
+        assertTrue(3 in byAncestor.blocks[0] && 2 !in byAncestor.blocks[0])
+        assertEquals(coreBlocks(txs, weight), byAncestor.blocks.map { it.toSet() })
+    }
+}
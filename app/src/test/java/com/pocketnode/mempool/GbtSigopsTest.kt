@@ -0,0 +1,53 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Test
+
+class GbtSigopsTest {
+    private fun pricing(bytesPerSigop: Int, maxBlockWeight: Int = 4_000_000): GbtGenerator =
+        GbtGenerator.create(maxBlockWeight, 0, bytesPerSigop = bytesPerSigop).apply { setSeed(1L) }
+
+    /** 1 pays 10 sat/vB raw but costs 1,000 WU once its 50 sigops are priced in; 2 pays 9 sat/vB. */
+    private val mempool = listOf(tx(1, fee = 1_000, sigops = 50), tx(2, fee = 900))
+
+    @Test
+    fun sigopHeavyTransactionIsOutrankedOncePriced() {
+        val result = pricing(GbtGenerator.DEFAULT_BYTES_PER_SIGOP).make(mempool, maxUid = 2)!!
+
+        assertArrayEquals(intArrayOf(2, 1), result.blocks[0])
+        assertArrayEquals(intArrayOf(800), result.blockWeights)
+        assertArrayEquals(intArrayOf(200), result.blockVsizes)
+        assertArrayEquals(intArrayOf(350), result.blockAdjustedVsizes)
+    }
+
+    @Test
+    fun zeroBytesPerSigopDisablesPricing() {
+        val result = pricing(0).make(mempool, maxUid = 2)!!
+
+        assertArrayEquals(intArrayOf(1, 2), result.blocks[0])
+        assertArrayEquals(intArrayOf(200), result.blockAdjustedVsizes)
+    }
+
+    @Test
+    fun pricedSigopsTakeBlockSpace() {
+        // Five 1,000 WU-priced transactions fill a 5,000 WU block although they weigh 2,000 WU.
+        val txs = (1..6).map { tx(it, fee = 1_000L + it, sigops = 50) }
+
+        val result = pricing(GbtGenerator.DEFAULT_BYTES_PER_SIGOP, GbtGenerator.MIN_BLOCK_WEIGHT).make(txs, maxUid = 6)!!
+
+        assertArrayEquals(intArrayOf(6, 5, 4, 3, 2), result.blocks[0])
+        assertArrayEquals(intArrayOf(1), result.blocks[1])
+        assertArrayEquals(intArrayOf(2_000, 400), result.blockWeights)
+    }
+
+    @Test
+    fun blockSigopsLimitHoldsWithPricingOff() {
+        val txs = listOf(tx(1, fee = 2_000, sigops = 40_000), tx(2, fee = 1_000, sigops = 40_001), tx(3, fee = 500, sigops = 1))
+
+        val result = pricing(0).make(txs, maxUid = 3)!!
+
+        // 1 and 3 fit under 80,000 sigops together; 2 would push past it.
+        assertArrayEquals(intArrayOf(1, 3), result.blocks[0])
+        assertArrayEquals(intArrayOf(2), result.blocks[1])
+    }
+}
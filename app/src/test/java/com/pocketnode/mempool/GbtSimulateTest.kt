@@ -0,0 +1,82 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertFalse
+import org.junit.Assert.assertTrue
+import org.junit.Test
+import java.util.concurrent.CountDownLatch
+import java.util.concurrent.TimeUnit
+import kotlin.concurrent.thread
+
+class GbtSimulateTest {
+    private val mempool = (1..40).map { tx(it, fee = 100L * it, inputs = if (it % 5 == 0) intArrayOf(it - 1) else intArrayOf()) }
+
+    private fun started(): GbtGenerator =
+        generator(maxBlockWeight = GbtGenerator.MIN_BLOCK_WEIGHT, maxBlocks = 2).apply { make(mempool, maxUid = 40) }
+
+    private fun assertSameBlocks(expected: GbtResult, actual: GbtResult) {
+        assertEquals(expected.blocks.size, actual.blocks.size)
+        for (i in expected.blocks.indices) assertArrayEquals(expected.blocks[i], actual.blocks[i])
+        assertArrayEquals(expected.overflow, actual.overflow)
+        assertEquals(expected.templateHash, actual.templateHash)
+    }
+
+    @Test
+    fun simulationLeavesTheNextComputeUnchanged() {
+        val control = started()
+        val simulated = started()
+        val update = listOf(tx(41, fee = 9_000))
+
+        val scenario = simulated.simulate(maxBlockWeight = 10_000, maxBlocks = 8)!!
+        val afterSimulation = simulated.update(newTxs = update, maxUid = 41)!!
+        val expected = control.update(newTxs = update, maxUid = 41)!!
+
+        assertEquals(listOf(25, 15), scenario.blocks.map { it.size })
+        assertSameBlocks(expected, afterSimulation)
+        assertArrayEquals(expected.diff!!.addedUids, afterSimulation.diff!!.addedUids)
+        assertArrayEquals(expected.diff!!.movedUids, afterSimulation.diff!!.movedUids)
+    }
+
+    @Test
+    fun cachedResultAndHashAreUntouched() {
+        val generator = started()
+        val before = generator.compute()!!
+        val hash = generator.getTemplateHash()
+
+        generator.simulate(maxBlockWeight = GbtGenerator.MAX_BLOCK_WEIGHT, maxBlocks = 1)
+
+        assertEquals(hash, generator.getTemplateHash())
+        assertEquals(GbtResult.OVERFLOW_BLOCK, generator.getTxPosition(1))
+        val after = generator.compute()!!
+        assertTrue(after.fromCache)
+        assertSameBlocks(before, after)
+    }
+
+    @Test(timeout = 10_000)
+    fun simulationWaitsForAnInFlightCompute() {
+        val generator = started()
+        generator.applyUpdates(newTxs = listOf(tx(41, fee = 9_000)))
+        val inRun = CountDownLatch(1)
+        val released = CountDownLatch(1)
+        generator.afterSnapshot = {
+            generator.afterSnapshot = null
+            inRun.countDown()
+            released.await()
+        }
+        val runner = thread { generator.compute() }
+        assertTrue(inRun.await(5, TimeUnit.SECONDS))
+
+        val simulated = CountDownLatch(1)
+        val simulation = thread {
+            generator.simulate(maxBlockWeight = 10_000, maxBlocks = 0)
+            simulated.countDown()
+        }
+
+        assertFalse(simulated.await(200, TimeUnit.MILLISECONDS))
+        released.countDown()
+        runner.join()
+        simulation.join()
+        assertEquals(0L, simulated.count)
+    }
+}
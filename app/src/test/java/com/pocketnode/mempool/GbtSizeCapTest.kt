@@ -0,0 +1,82 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertTrue
+import org.junit.Test
+
+class GbtSizeCapTest {
+    private fun capped(maxTxCount: Int): GbtGenerator =
+        GbtGenerator.create(4_000_000, 0, maxTxCount = maxTxCount).apply { setSeed(1L) }
+
+    /** No tracked transaction spends one that was evicted. */
+    private fun assertNoDanglingParents(generator: GbtGenerator, evicted: IntArray) {
+        val tracked = generator.trackedTransactions()
+        for (tx in tracked.values) for (input in tx.inputs) assertTrue("${tx.uid} spends evicted $input", input !in evicted)
+        assertTrue(generator.verifyState().isConsistent)
+    }
+
+    @Test
+    fun cheapestTransactionGoesFirst() {
+        val generator = capped(5)
+
+        generator.make((1..6).map { tx(it, fee = 1_000L * it) }, maxUid = 6)
+
+        assertArrayEquals(intArrayOf(1), generator.getEvictedSinceLastCall())
+        assertEquals(setOf(2, 3, 4, 5, 6), generator.trackedTransactions().keys)
+        assertEquals(10.0, generator.getMempoolMinFee(), 0.01)
+        // Reported once.
+        assertArrayEquals(intArrayOf(), generator.getEvictedSinceLastCall())
+    }
+
+    @Test
+    fun childPaysForItsParentsPlace() {
+        val generator = capped(4)
+
+        // 5 pays 1 sat/vB alone, but with its child the package pays 50.5 sat/vB.
+        generator.make(
+            listOf(tx(1, fee = 1_000), tx(2, fee = 2_000), tx(3, fee = 3_000), tx(5, fee = 100), tx(6, fee = 10_000, inputs = intArrayOf(5))),
+            maxUid = 6
+        )
+
+        assertArrayEquals(intArrayOf(1), generator.getEvictedSinceLastCall())
+        assertTrue(5 in generator.trackedTransactions())
+    }
+
+    @Test
+    fun cheapPackageIsEvictedWithItsDescendants() {
+        val generator = capped(4)
+        generator.make((1..3).map { tx(it, fee = 1_000L * it) }, maxUid = 3)
+
+        // 8 and its child 9 pay 1.5 sat/vB together, below every singleton.
+        generator.update(newTxs = listOf(tx(8, fee = 100), tx(9, fee = 200, inputs = intArrayOf(8))), maxUid = 9)
+
+        val evicted = generator.getEvictedSinceLastCall()
+        assertArrayEquals(intArrayOf(9, 8), evicted)
+        assertEquals(setOf(1, 2, 3), generator.trackedTransactions().keys)
+        assertNoDanglingParents(generator, evicted)
+    }
+
+    @Test
+    fun evictingAParentTakesTheWholeChain() {
+        val generator = capped(5)
+        // Chain 1 <- 2 <- 3 at 1, 2 and 3 sat/vB. 1 scores lowest, 2 sat/vB with both descendants.
+        val chain = listOf(tx(1, fee = 100), tx(2, fee = 200, inputs = intArrayOf(1)), tx(3, fee = 300, inputs = intArrayOf(2)))
+
+        generator.make(chain + (4..6).map { tx(it, fee = 1_000L * it) }, maxUid = 6)
+
+        val evicted = generator.getEvictedSinceLastCall()
+        assertEquals(setOf(1, 2, 3), evicted.toSet())
+        assertEquals(setOf(4, 5, 6), generator.trackedTransactions().keys)
+        assertNoDanglingParents(generator, evicted)
+    }
+
+    @Test
+    fun accelerationProtectsATransaction() {
+        val generator = capped(2)
+
+        generator.make(listOf(tx(1, fee = 100), tx(2, fee = 2_000), tx(3, fee = 3_000)), listOf(ThreadAcceleration(1, 10_000)), maxUid = 3)
+
+        assertArrayEquals(intArrayOf(2), generator.getEvictedSinceLastCall())
+    }
+}
@@ -0,0 +1,45 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertNotEquals
+import org.junit.Test
+
+class GbtTemplateHashTest {
+    private val mempool = (1..30).map { tx(it, fee = 100L + 31L * it % 500, inputs = if (it % 5 == 0) intArrayOf(it - 1) else intArrayOf()) }
+
+    private fun hashOf(txs: List<ThreadTransaction>): Long =
+        generator(maxBlockWeight = GbtGenerator.MIN_BLOCK_WEIGHT).make(txs, maxUid = 40)!!.templateHash
+
+    @Test
+    fun identicalInputGivesEqualHashes() {
+        val first = hashOf(mempool)
+
+        assertEquals(first, hashOf(mempool))
+        assertEquals(first, hashOf(mempool.reversed()))
+    }
+
+    @Test
+    fun addedTransactionChangesTheHash() {
+        assertNotEquals(hashOf(mempool), hashOf(mempool + tx(31, fee = 50)))
+    }
+
+    @Test
+    fun reorderingWithinABlockChangesTheHash() {
+        // Same two transactions in one block, in either order.
+        val a = hashOf(listOf(tx(1, fee = 2_000), tx(2, fee = 1_000)))
+        val b = hashOf(listOf(tx(1, fee = 1_000), tx(2, fee = 2_000)))
+
+        assertNotEquals(a, b)
+    }
+
+    @Test
+    fun getterMatchesTheLastRun() {
+        val generator = generator()
+        assertEquals(0L, generator.getTemplateHash())
+
+        val result = generator.make(mempool, maxUid = 30)!!
+
+        assertEquals(result.templateHash, generator.getTemplateHash())
+        assertNotEquals(0L, result.templateHash)
+    }
+}
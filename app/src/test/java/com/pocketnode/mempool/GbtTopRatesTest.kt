@@ -0,0 +1,66 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Test
+
+class GbtTopRatesTest {
+    private val mempool = listOf(
+        tx(1, fee = 500), tx(2, fee = 3_000), tx(3, fee = 3_000), tx(4, fee = 1_000), tx(5, fee = 2_000)
+    )
+
+    @Test
+    fun returnsTheBestNWithTiesByUid() {
+        val generator = generator()
+        generator.make(mempool, maxUid = 5)
+
+        val top = generator.getTopRates(3)
+
+        assertArrayEquals(intArrayOf(2, 3, 5), top.uids)
+        assertArrayEquals(doubleArrayOf(30.0, 30.0, 20.0), top.rates, 0.0)
+    }
+
+    @Test
+    fun nLargerThanTheMempoolReturnsEverything() {
+        val generator = generator()
+        generator.make(mempool, maxUid = 5)
+
+        val top = generator.getTopRates(100)
+
+        assertArrayEquals(intArrayOf(2, 3, 5, 4, 1), top.uids)
+    }
+
+    @Test
+    fun zeroOrNegativeNReturnsNothing() {
+        val generator = generator()
+        generator.make(mempool, maxUid = 5)
+
+        assertEquals(0, generator.getTopRates(0).uids.size)
+        assertEquals(0, generator.getTopRates(-1).rates.size)
+        assertEquals(0, generator().getTopRates(5).uids.size)
+    }
+
+    @Test
+    fun beforeTheFirstRunUsesTheProvidedRates() {
+        val generator = generator()
+        generator.applyMake(mempool, emptyList())
+
+        val top = generator.getTopRates(2)
+
+        assertArrayEquals(intArrayOf(2, 3), top.uids)
+        assertArrayEquals(doubleArrayOf(30.0, 30.0), top.rates, 0.0)
+    }
+
+    @Test
+    fun afterARunUsesThePackedRates() {
+        val generator = generator()
+        // 1 pays 1 sat/vB on its own but is carried by its child.
+        val result = generator.make(listOf(tx(1, fee = 100), tx(2, fee = 5_000, inputs = intArrayOf(1)), tx(3, fee = 1_500)), maxUid = 3)!!
+
+        val top = generator.getTopRates(3)
+        val packedParentRate = result.rates.single { it[0].toInt() == 1 }[2]
+
+        assertEquals(packedParentRate, top.rates[top.uids.indexOf(1)], 0.0)
+        assertEquals(3, top.uids.last())
+    }
+}
@@ -0,0 +1,63 @@
+package com.pocketnode.mempool
+
+import org.json.JSONObject
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertFalse
+import org.junit.Assert.assertTrue
+import org.junit.Test
+import java.io.File
+
+class GbtTracerTest {
+    private fun tempFile() = File.createTempFile("gbt", ".json").apply { delete(); deleteOnExit() }
+
+    @Test
+    fun runWritesALoadableChromeTraceWithEveryPhase() {
+        val file = tempFile()
+        val generator = generator(maxBlockWeight = GbtGenerator.MIN_BLOCK_WEIGHT)
+
+        generator.startTrace(file.path)
+        // 30 transactions, three 5,000 WU blocks.
+        generator.make((1..30).map { tx(it, fee = 1_000L + it) }, maxUid = 30)
+        assertTrue(generator.stopTrace())
+
+        val events = JSONObject(file.readText()).getJSONArray("traceEvents")
+        val byName = (0 until events.length()).map { events.getJSONObject(it) }.groupBy { it.getString("name") }
+        assertTrue(byName.keys.containsAll(listOf("gbt.run", "gbt.ancestors", "gbt.block", "gbt.overflow", "gbt.clusters")))
+        assertEquals(listOf(0, 1, 2), byName.getValue("gbt.block").map { it.getJSONObject("args").getInt("index") })
+        assertEquals(30, byName.getValue("gbt.run").single().getJSONObject("args").getInt("txs"))
+        for (event in byName.values.flatten()) {
+            assertEquals("X", event.getString("ph"))
+            assertTrue(event.getLong("ts") >= 0 && event.getLong("dur") >= 0)
+        }
+    }
+
+    @Test
+    fun nothingIsRecordedOnceStopped() {
+        val file = tempFile()
+        val generator = generator()
+        generator.startTrace(file.path)
+        generator.make(listOf(tx(1, fee = 1_000)), maxUid = 1)
+        generator.stopTrace()
+        val written = file.readText()
+
+        generator.update(newTxs = listOf(tx(2, fee = 2_000)), maxUid = 2)
+
+        assertEquals(written, file.readText())
+    }
+
+    @Test
+    fun stopWithoutStartWritesNothing() {
+        assertTrue(generator().stopTrace())
+    }
+
+    @Test
+    fun unwritableTraceReportsFailure() {
+        val directory = File.createTempFile("gbt", "").apply { delete(); mkdirs(); deleteOnExit() }
+        val generator = generator()
+
+        generator.startTrace(directory.path)
+        generator.make(listOf(tx(1, fee = 1_000)), maxUid = 1)
+
+        assertFalse(generator.stopTrace())
+    }
+}
@@ -0,0 +1,51 @@
+package com.pocketnode.mempool
+
+import android.content.ComponentCallbacks2
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertTrue
+import org.junit.Test
+
+class GbtTrimMemoryTest {
+    private fun loaded(count: Int): GbtGenerator = generator().apply {
+        applyMake((1..count).map { tx(it, fee = 100L + it % 1_000, txid = "tx$it") }, emptyList())
+    }
+
+    @Test
+    fun largeRemovalShrinksTheMaps() {
+        val generator = loaded(300_000)
+        assertEquals(300_000, generator.mapSizedFor())
+
+        generator.applyUpdates(removeTxs = (1..280_000).toList())
+
+        assertEquals(20_000, generator.mapSizedFor())
+        assertEquals(20_000, generator.size())
+        assertTrue(generator.verifyState().isConsistent)
+    }
+
+    @Test
+    fun trimMemoryShrinksWhatAutomaticShrinkingLeaves() {
+        val generator = loaded(300_000)
+        // 100k left is above a quarter of the peak, so no automatic shrink.
+        generator.applyUpdates(removeTxs = (1..200_000).toList())
+        assertEquals(300_000, generator.mapSizedFor())
+
+        generator.trimMemory(ComponentCallbacks2.TRIM_MEMORY_RUNNING_MODERATE)
+
+        assertEquals(100_000, generator.mapSizedFor())
+        assertEquals(100_000, generator.size())
+        assertTrue(generator.verifyState().isConsistent)
+    }
+
+    @Test
+    fun onlyRunningLowAndAboveDropsTheReinstateCache() {
+        val generator = loaded(10)
+        generator.applyUpdates(removeTxs = listOf(1, 2))
+
+        generator.trimMemory(ComponentCallbacks2.TRIM_MEMORY_RUNNING_MODERATE)
+        assertArrayEquals(intArrayOf(), generator.reinstate(intArrayOf(1)))
+
+        generator.trimMemory(ComponentCallbacks2.TRIM_MEMORY_RUNNING_LOW)
+        assertArrayEquals(intArrayOf(2), generator.reinstate(intArrayOf(2)))
+    }
+}
@@ -0,0 +1,103 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertFalse
+import org.junit.Assert.assertTrue
+import org.junit.Test
+
+class GbtTrucTest {
+    private fun run(vararg txs: ThreadTransaction): GbtResult = generator().make(txs.toList(), maxUid = txs.size)!!
+
+    private fun reasons(result: GbtResult) = result.overflow.indices.associate { result.overflow[it] to result.overflowReasons[it] }
+
+    private fun packed(result: GbtResult) = result.blocks.flatMap { it.asList() }.toSet()
+
+    @Test
+    fun trucWithTwoInMempoolParentsIsInvalid() {
+        val result = run(tx(1, fee = 1_000), tx(2, fee = 1_000), tx(3, fee = 1_000, inputs = intArrayOf(1, 2), isTruc = true))
+
+        assertEquals(mapOf(3 to GbtResult.OVERFLOW_TRUC_INVALID), reasons(result))
+        assertEquals(setOf(1, 2), packed(result))
+    }
+
+    @Test
+    fun oversizedTrucChildAndParentGoToOverflow() {
+        val result = run(
+            tx(1, fee = 1_000, isTruc = true),
+            tx(2, fee = 50_000, weight = 4_004, inputs = intArrayOf(1), isTruc = true),
+            tx(3, fee = 500_000, weight = 40_004, isTruc = true)
+        )
+
+        assertEquals(GbtResult.OVERFLOW_TRUC_OVERSIZED, reasons(result)[2])
+        assertEquals(GbtResult.OVERFLOW_TRUC_OVERSIZED, reasons(result)[3])
+        assertEquals(setOf(1), packed(result))
+    }
+
+    @Test
+    fun trucParentKeepsOnlyItsBestChild() {
+        val result = run(
+            tx(1, fee = 100, isTruc = true),
+            tx(2, fee = 2_000, inputs = intArrayOf(1), isTruc = true),
+            tx(3, fee = 3_000, inputs = intArrayOf(1), isTruc = true)
+        )
+
+        assertEquals(mapOf(2 to GbtResult.OVERFLOW_TRUC_SIBLING_EVICTED), reasons(result))
+        assertEquals(setOf(1, 3), packed(result))
+    }
+
+    @Test
+    fun siblingTieGoesToTheLowerUid() {
+        val result = run(
+            tx(1, fee = 100, isTruc = true),
+            tx(2, fee = 2_000, inputs = intArrayOf(1), isTruc = true),
+            tx(3, fee = 2_000, inputs = intArrayOf(1), isTruc = true)
+        )
+
+        assertEquals(mapOf(3 to GbtResult.OVERFLOW_TRUC_SIBLING_EVICTED), reasons(result))
+    }
+
+    @Test
+    fun unflaggedTransactionsAreUnaffected() {
+        val result = run(
+            tx(1, fee = 1_000),
+            tx(2, fee = 1_000),
+            tx(3, fee = 1_000, weight = 40_004, inputs = intArrayOf(1, 2)),
+            tx(4, fee = 1_000, inputs = intArrayOf(1))
+        )
+
+        assertTrue(result.overflow.isEmpty())
+        assertEquals(setOf(1, 2, 3, 4), packed(result))
+    }
+
+    @Test
+    fun descendantsOfAnExcludedTrucFollowItIntoOverflow() {
+        // The invalid TRUC transaction's child and grandchild pay enough to be picked first
+        // if they were still scorable.
+        val result = run(
+            tx(1, fee = 1_000),
+            tx(2, fee = 1_000),
+            tx(3, fee = 1_000, inputs = intArrayOf(1, 2), isTruc = true),
+            tx(4, fee = 90_000, inputs = intArrayOf(3)),
+            tx(5, fee = 90_000, inputs = intArrayOf(4))
+        )
+
+        val reasons = reasons(result)
+        assertEquals(GbtResult.OVERFLOW_TRUC_INVALID, reasons[3])
+        assertEquals(GbtResult.OVERFLOW_TRUC_ANCESTOR_EXCLUDED, reasons[4])
+        assertEquals(GbtResult.OVERFLOW_TRUC_ANCESTOR_EXCLUDED, reasons[5])
+        assertEquals(setOf(1, 2), packed(result))
+    }
+
+    @Test
+    fun evictedSiblingTakesItsChildAlong() {
+        val result = run(
+            tx(1, fee = 100, isTruc = true),
+            tx(2, fee = 2_000, inputs = intArrayOf(1), isTruc = true),
+            tx(3, fee = 3_000, inputs = intArrayOf(1), isTruc = true),
+            tx(4, fee = 5_000, inputs = intArrayOf(2))
+        )
+
+        assertEquals(GbtResult.OVERFLOW_TRUC_ANCESTOR_EXCLUDED, reasons(result)[4])
+        assertFalse(4 in packed(result))
+    }
+}
@@ -0,0 +1,54 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Test
+
+class GbtTxStatsTest {
+    /**
+     *     1
+     *    / \
+     *   2   3
+     *    \ /
+     *     4
+     * Naive per-parent summing counts 1 twice for 4.
+     */
+    private val diamond = listOf(
+        tx(1, fee = 1_000, weight = 400),
+        tx(2, fee = 2_000, weight = 800, inputs = intArrayOf(1)),
+        tx(3, fee = 3_000, weight = 1_200, inputs = intArrayOf(1)),
+        tx(4, fee = 4_000, weight = 1_600, inputs = intArrayOf(2, 3))
+    )
+
+    @Test
+    fun diamondCountsSharedAncestorsOnce() {
+        val stats = generator().make(diamond, maxUid = 4)!!.txStats!!
+
+        assertArrayEquals(intArrayOf(1, 2, 3, 4), stats.uids)
+        assertArrayEquals(intArrayOf(0, 1, 1, 3), stats.ancestorCounts)
+        assertArrayEquals(intArrayOf(3, 1, 1, 0), stats.descendantCounts)
+        assertArrayEquals(longArrayOf(400, 1_200, 1_600, 4_000), stats.ancestorWeights)
+        assertArrayEquals(longArrayOf(1_000, 3_000, 4_000, 10_000), stats.ancestorFees)
+    }
+
+    @Test
+    fun ancestorFeesIncludeAccelerations() {
+        val stats = generator().make(diamond, listOf(ThreadAcceleration(1, 500)), maxUid = 4)!!.txStats!!
+
+        assertArrayEquals(longArrayOf(1_500, 3_500, 4_500, 10_500), stats.ancestorFees)
+    }
+
+    @Test
+    fun statsFollowTheCurrentGraph() {
+        val generator = generator()
+        generator.make(diamond, maxUid = 4)
+
+        // Dropping one side of the diamond.
+        val stats = generator.update(removeTxs = listOf(3), maxUid = 4)!!.txStats!!
+
+        assertArrayEquals(intArrayOf(1, 2, 4), stats.uids)
+        assertArrayEquals(intArrayOf(0, 1, 2), stats.ancestorCounts)
+        assertArrayEquals(intArrayOf(2, 1, 0), stats.descendantCounts)
+        assertEquals(7_000L, stats.ancestorFees[2])
+    }
+}
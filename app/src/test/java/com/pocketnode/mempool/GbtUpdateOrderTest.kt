@@ -0,0 +1,66 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertNull
+import org.junit.Test
+
+class GbtUpdateOrderTest {
+    private fun started(): GbtGenerator = generator().apply {
+        make(listOf(tx(1, fee = 1_000), tx(2, fee = 2_000), tx(3, fee = 3_000, inputs = intArrayOf(1))), maxUid = 3)
+    }
+
+    @Test
+    fun removalOnlyRemoves() {
+        val generator = started()
+
+        generator.applyUpdates(removeTxs = listOf(2))
+
+        assertNull(generator.getAncestors(2))
+        assertEquals(2, generator.size())
+    }
+
+    @Test
+    fun uidInBothListsKeepsTheNewTransaction() {
+        val generator = started()
+
+        generator.applyUpdates(newTxs = listOf(tx(2, fee = 9_000)), removeTxs = listOf(2))
+
+        assertEquals(9_000L, generator.trackedTransactions().getValue(2).fee)
+        assertEquals(3, generator.size())
+    }
+
+    @Test
+    fun removalOfAnUntrackedUidAlsoInsertedStillInserts() {
+        val generator = started()
+
+        generator.applyUpdates(newTxs = listOf(tx(7, fee = 700)), removeTxs = listOf(7))
+
+        assertEquals(700L, generator.trackedTransactions().getValue(7).fee)
+    }
+
+    @Test
+    fun duplicateUidInNewTxsKeepsTheLastOccurrence() {
+        val generator = started()
+
+        generator.applyUpdates(newTxs = listOf(tx(4, fee = 100), tx(4, fee = 400), tx(5, fee = 500)))
+
+        assertEquals(400L, generator.trackedTransactions().getValue(4).fee)
+        assertEquals(5, generator.size())
+    }
+
+    @Test
+    fun reinsertWithChangedInputsRewiresTheGraph() {
+        val generator = started()
+
+        // 3 moves from spending 1 to spending 2.
+        generator.applyUpdates(newTxs = listOf(tx(3, fee = 3_000, inputs = intArrayOf(2))), removeTxs = listOf(3))
+
+        assertArrayEquals(intArrayOf(2), generator.getAncestors(3))
+        assertArrayEquals(intArrayOf(), generator.getDescendants(1))
+        assertArrayEquals(intArrayOf(3), generator.getDescendants(2))
+        // Removing the old parent no longer takes 3 with it.
+        generator.removeWithDescendants(intArrayOf(1))
+        assertEquals(setOf(2, 3), generator.trackedTransactions().keys)
+    }
+}
@@ -0,0 +1,101 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertTrue
+import org.junit.Assert.fail
+import org.junit.Test
+import java.io.File
+
+class GbtValidationTest {
+    private val garbage = listOf(
+        tx(10, fee = 1_000, weight = 0),
+        tx(11, fee = 1_000, weight = 4_000_001),
+        tx(12, fee = -1),
+        tx(13, fee = 1_000).copy(effectiveFeePerVsize = Double.POSITIVE_INFINITY),
+        tx(14, fee = 1_000).copy(effectiveFeePerVsize = -2.0),
+        tx(15, fee = 1_000, sigops = 80_001),
+        tx(16, fee = 1_000, inputs = intArrayOf(16)),
+        tx(17, fee = 1_000, inputs = intArrayOf(1, 1))
+    )
+
+    @Test
+    fun garbageIsDroppedAndCounted() {
+        val generator = generator()
+        val result = generator.make(listOf(tx(1, fee = 1_000)) + garbage, maxUid = 17)!!
+
+        assertEquals(1, generator.size())
+        assertEquals(garbage.size, result.invalidTxCount)
+        assertEquals(garbage.size.toLong(), generator.getCounters()[GbtGenerator.COUNTER_TXS_REJECTED])
+        assertArrayEquals(intArrayOf(1), result.blocks[0])
+    }
+
+    @Test
+    fun eachShapeThrowsInStrictMode() {
+        for (bad in garbage) {
+            val generator = generator(strict = true)
+            try {
+                generator.applyUpdates(newTxs = listOf(bad))
+                fail("accepted ${bad.uid}")
+            } catch (e: GbtInvalidInputException) {
+                assertEquals(bad.uid, e.uid)
+            }
+        }
+    }
+
+    @Test
+    fun strictUpdateRejectionLeavesStateUntouched() {
+        val generator = generator(strict = true)
+        generator.make(listOf(tx(1, fee = 1_000), tx(2, fee = 500)), maxUid = 2)
+        val counter = generator.getChangeCounter()
+
+        // The removal and the valid insert come before the bad transaction in the batch.
+        try {
+            generator.applyUpdates(newTxs = listOf(tx(3, fee = 700), tx(4, fee = -5)), removeTxs = listOf(1))
+            fail()
+        } catch (e: GbtInvalidInputException) {
+            assertEquals(4, e.uid)
+        }
+
+        assertEquals(counter, generator.getChangeCounter())
+        assertEquals(2, generator.size())
+        assertEquals(0L, generator.getCounters()[GbtGenerator.COUNTER_UPDATES])
+        assertTrue(generator.compute()!!.fromCache)
+    }
+
+    @Test
+    fun strictMakeRejectionKeepsThePreviousMempool() {
+        val generator = generator(strict = true)
+        generator.make(listOf(tx(1, fee = 1_000)), maxUid = 1)
+
+        try {
+            generator.applyMake(listOf(tx(5, fee = 1_000), tx(6, fee = 1_000, weight = 0)), emptyList())
+            fail()
+        } catch (e: GbtInvalidInputException) {
+            assertEquals(6, e.uid)
+        }
+
+        assertEquals(1, generator.size())
+        val result = generator.compute()!!
+        assertTrue(result.fromCache)
+        assertArrayEquals(intArrayOf(1), result.blocks[0])
+    }
+
+    @Test
+    fun rejectedBatchIsNotRecorded() {
+        val file = File.createTempFile("gbt", ".rec").apply { deleteOnExit() }
+        val generator = generator(strict = true)
+        generator.setRecording(file.path)
+        generator.make(listOf(tx(1, fee = 1_000)), maxUid = 1)
+        val recorded = file.length()
+
+        try {
+            generator.applyUpdates(newTxs = listOf(tx(2, fee = 1_000, inputs = intArrayOf(2))))
+            fail()
+        } catch (e: GbtInvalidInputException) {
+            // expected
+        }
+
+        assertEquals(recorded, file.length())
+    }
+}
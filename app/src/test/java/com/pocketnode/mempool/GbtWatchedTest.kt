@@ -0,0 +1,86 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Test
+
+class GbtWatchedTest {
+    private val unknown = GbtResult.UNKNOWN_UID
+    private val overflow = GbtResult.OVERFLOW_BLOCK
+
+    /** Uids 1..30 at uid sat/vB: block 0 is 30..19, block 1 18..7, overflow 6..1. */
+    private fun watching(vararg uids: Int): Pair<GbtGenerator, GbtWatchedChanges> {
+        val generator = generator(maxBlockWeight = GbtGenerator.MIN_BLOCK_WEIGHT, maxBlocks = 2)
+        generator.setWatched(uids)
+        val first = generator.make((1..30).map { tx(it, fee = 100L * it) }, maxUid = 30)!!
+        return generator to first.watchedChanges!!
+    }
+
+    /** Twelve transactions outbidding everything, pushing the rest back one block. */
+    private fun GbtGenerator.outbid(): GbtWatchedChanges =
+        update(newTxs = (31..42).map { tx(it, fee = 10_000L + it) }, maxUid = 42)!!.watchedChanges!!
+
+    @Test
+    fun firstRunReportsWhereWatchedTransactionsStart() {
+        val (_, changes) = watching(25, 15, 5)
+
+        assertArrayEquals(intArrayOf(5, 15, 25), changes.uids)
+        assertArrayEquals(intArrayOf(unknown, unknown, unknown), changes.oldBlocks)
+        assertArrayEquals(intArrayOf(overflow, 1, 0), changes.newBlocks)
+        assertArrayEquals(doubleArrayOf(5.0, 15.0, 25.0), changes.newRates, 0.0)
+    }
+
+    @Test
+    fun movingBetweenBlocks() {
+        val (generator, _) = watching(25)
+
+        val changes = generator.outbid()
+
+        assertArrayEquals(intArrayOf(25), changes.uids)
+        assertArrayEquals(intArrayOf(0), changes.oldBlocks)
+        assertArrayEquals(intArrayOf(1), changes.newBlocks)
+    }
+
+    @Test
+    fun droppingToOverflow() {
+        val (generator, _) = watching(15)
+
+        val changes = generator.outbid()
+
+        assertArrayEquals(intArrayOf(15), changes.uids)
+        assertArrayEquals(intArrayOf(1), changes.oldBlocks)
+        assertArrayEquals(intArrayOf(overflow), changes.newBlocks)
+    }
+
+    @Test
+    fun unchangedTransactionsAreNotReported() {
+        val (generator, _) = watching(5)
+
+        assertEquals(0, generator.outbid().uids.size)
+    }
+
+    @Test
+    fun removalIsReportedOnce() {
+        val (generator, _) = watching(25)
+
+        val removed = generator.update(removeTxs = listOf(25), maxUid = 30)!!.watchedChanges!!
+        val next = generator.update(newTxs = listOf(tx(31, fee = 50)), maxUid = 31)!!.watchedChanges!!
+
+        assertArrayEquals(intArrayOf(25), removed.uids)
+        assertArrayEquals(intArrayOf(0), removed.oldBlocks)
+        assertArrayEquals(intArrayOf(unknown), removed.newBlocks)
+        assertArrayEquals(doubleArrayOf(25.0), removed.oldRates, 0.0)
+        assertArrayEquals(doubleArrayOf(0.0), removed.newRates, 0.0)
+        assertEquals(0, next.uids.size)
+    }
+
+    @Test
+    fun neverTrackedUidIsReportedOnceAsUnknown() {
+        val (generator, first) = watching(99)
+
+        assertArrayEquals(intArrayOf(99), first.uids)
+        assertArrayEquals(intArrayOf(unknown), first.oldBlocks)
+        assertArrayEquals(intArrayOf(unknown), first.newBlocks)
+        assertEquals(0, generator.outbid().uids.size)
+    }
+}
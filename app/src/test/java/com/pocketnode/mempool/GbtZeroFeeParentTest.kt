@@ -0,0 +1,39 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertTrue
+import org.junit.Test
+
+class GbtZeroFeeParentTest {
+    /** Zero-fee parent 20 (200 vB) with anchor child 21 paying 20,000 sats: 66.7 sat/vB as a pair. */
+    private val parent = tx(20, fee = 0, weight = 800)
+    private val child = tx(21, fee = 20_000, inputs = intArrayOf(20))
+
+    @Test
+    fun parentHasAFiniteZeroRate() {
+        assertEquals(0.0, parent.effectiveFeePerVsize, 0.0)
+    }
+
+    @Test
+    fun pairLandsAdjacentInTheBlockItsPackageRateEarns() {
+        // Nine at 100 sat/vB go first, then the pair; the 50 sat/vB ones no longer fit block 0.
+        val fillers = (1..9).map { tx(it, fee = 10_000) } + (10..19).map { tx(it, fee = 5_000) }
+
+        val result = generator(maxBlockWeight = GbtGenerator.MIN_BLOCK_WEIGHT)
+            .make(fillers + parent + child, maxUid = 21)!!
+
+        val block = result.blocks[0].toList()
+        assertEquals((1..9).toSet() + setOf(20, 21), block.toSet())
+        assertEquals(block.indexOf(20) + 1, block.indexOf(21))
+        assertTrue(result.blocks.drop(1).flatMap { it.asList() }.containsAll((10..19).toList()))
+    }
+
+    @Test
+    fun ratesReportTheCpfpRateRatherThanZero() {
+        val result = generator().make(listOf(parent, child), maxUid = 21)!!
+
+        val rows = result.rates.associate { it[0].toInt() to it.drop(1) }
+        assertEquals(listOf(0.0, 20_000.0 / 300), rows[20])
+        assertEquals(listOf(200.0, 20_000.0 / 300), rows[21])
+    }
+}
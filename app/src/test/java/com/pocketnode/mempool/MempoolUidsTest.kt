@@ -0,0 +1,47 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertFalse
+import org.junit.Assert.assertNull
+import org.junit.Test
+
+class MempoolUidsTest {
+    @Test
+    fun releaseReturnsTheUidsItForgets() {
+        val uids = MempoolUids()
+        val a = uids.assign("aa")
+        val b = uids.assign("bb")
+
+        assertEquals(listOf(a), uids.release(listOf("aa", "unknown")))
+        assertNull(uids.uidOf("aa"))
+        assertNull(uids.txIdOf(a))
+        assertEquals(b, uids.uidOf("bb"))
+    }
+
+    @Test
+    fun releasedUidsRemoveTransactionsFromTheGenerator() {
+        val uids = MempoolUids()
+        val generator = generator()
+        val mempool = listOf("aa", "bb", "cc").map { txId -> tx(uids.assign(txId), fee = 1_000, txid = txId) }
+        generator.make(mempool, maxUid = uids.nextUid)
+
+        // The service's poll loop: forget the mined txid first, then update with its old uid.
+        val mined = uids.release(listOf("bb"))
+        val result = generator.update(removeTxs = mined, maxUid = uids.nextUid)!!
+
+        assertFalse(positions(result).containsKey(mined.single()))
+        assertEquals(2, generator.size())
+        assertEquals(GbtResult.UNKNOWN_UID, generator.getTxPosition(mined.single()))
+    }
+
+    @Test
+    fun clearRestartsNumbering() {
+        val uids = MempoolUids()
+        uids.assign("aa")
+        uids.assign("bb")
+        uids.clear()
+
+        assertEquals(1, uids.assign("cc"))
+        assertNull(uids.uidOf("aa"))
+    }
+}
@@ -0,0 +1,38 @@
+package com.pocketnode.mempool
+
+/** Transaction paying [fee] sats for [weight] WU, with the matching effective rate. */
+internal fun tx(
+    uid: Int,
+    fee: Long,
+    weight: Int = 400,
+    inputs: IntArray = intArrayOf(),
+    sigops: Int = 0,
+    txid: String? = null,
+    firstSeen: Long? = null,
+    isTruc: Boolean = false,
+    policyFlags: Int = 0
+) = ThreadTransaction(
+    uid = uid,
+    order = 0,
+    fee = fee,
+    weight = weight,
+    sigops = sigops,
+    effectiveFeePerVsize = fee.toDouble() / weightToVsize(weight),
+    inputs = inputs,
+    txid = txid,
+    firstSeen = firstSeen,
+    isTruc = isTruc,
+    policyFlags = policyFlags
+)
+
+/** Generator with [maxBlockWeight] WU blocks, [maxBlocks] of them (0 = unlimited). */
+internal fun generator(maxBlockWeight: Int = 4_000_000, maxBlocks: Int = 0, strict: Boolean = false): GbtGenerator =
+    GbtGenerator.create(maxBlockWeight, maxBlocks, strict = strict).apply { setSeed(1L) }
+
+/** Block index of every uid in [result], [GbtResult.OVERFLOW_BLOCK] for overflow. */
+internal fun positions(result: GbtResult): Map<Int, Int> {
+    val positions = HashMap<Int, Int>()
+    result.blocks.forEachIndexed { index, block -> block.forEach { positions[it] = index } }
+    result.overflow.forEach { positions[it] = GbtResult.OVERFLOW_BLOCK }
+    return positions
+}
@@ -0,0 +1,46 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Test
+
+class ThreadTransactionFeeTest {
+    @Test
+    fun btcAmountsRoundToTheNearestSat() {
+        assertEquals(30_000_000L, ThreadTransaction.btcToSats(0.1 + 0.2))
+        assertEquals(1_234L, ThreadTransaction.btcToSats(0.00001234))
+        assertEquals(1L, ThreadTransaction.btcToSats(0.000000009))
+        // Every sat of the full supply survives the conversion.
+        assertEquals(2_099_999_997_690_000L, ThreadTransaction.btcToSats(20_999_999.9769))
+    }
+
+    /**
+     * A 10M + 20M sat CPFP package and a lone 15M sat transaction, both 150,000 sat/vB. Summed
+     * as BTC doubles the package would come out as 0.30000000000000004 and win outright; in
+     * sats the rates tie exactly and the lower uid goes first, whichever side that is.
+     */
+    @Test
+    fun exactFeeSumsTieAndBreakByUid() {
+        fun run(loneUid: Int): IntArray = generator().make(
+            listOf(
+                tx(loneUid, fee = 15_000_000),
+                tx(2, fee = 10_000_000),
+                tx(3, fee = 20_000_000, inputs = intArrayOf(2))
+            ),
+            maxUid = 4
+        )!!.blocks[0]
+
+        assertArrayEquals(intArrayOf(1, 2, 3), run(loneUid = 1))
+        assertArrayEquals(intArrayOf(2, 3, 4), run(loneUid = 4))
+    }
+
+    @Test
+    fun mempoolWideFeeTotalsAreExact() {
+        val txs = (1..1_000).map { tx(it, fee = 2_100_000_000_000L + it) }
+
+        val generator = generator().apply { make(txs, maxUid = 1_000) }
+
+        val projection = generator.computeMulti(intArrayOf(GbtGenerator.MAX_BLOCK_WEIGHT)).single()
+        assertEquals(1_000L * 2_100_000_000_000L + 500_500L, projection.blockFees.single())
+    }
+}
@@ -0,0 +1,46 @@
+package com.pocketnode.mempool
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Test
+
+class UnitsTest {
+    @Test
+    fun vsizeRoundsUp() {
+        assertEquals(0, weightToVsize(0))
+        assertEquals(1, weightToVsize(1))
+        assertEquals(100, weightToVsize(400))
+        assertEquals(101, weightToVsize(401))
+        assertEquals(101, weightToVsize(403))
+        assertEquals(101, weightToVsize(404))
+        assertEquals(1_000_000, weightToVsize(4_000_000))
+    }
+
+    @Test
+    fun summedWeightsRoundOnce() {
+        assertEquals(101L, weightToVsize(401L))
+        assertEquals(1_000_000_001L, weightToVsize(4_000_000_001L))
+        // Three 401 WU transactions are 303 vB each rounded, 301 vB as one sum.
+        assertEquals(301L, weightToVsize(3 * 401L))
+    }
+
+    @Test
+    fun ratesUseTheRoundedVsize() {
+        // 1,010 sats over 401 WU is 10 sat/vB at 101 vB, not 10.07 at 100.25.
+        val result = GbtGenerator.create(4_000_000, 0, deriveRates = true)
+            .make(listOf(tx(1, fee = 1_010, weight = 401), tx(2, fee = 20_000, weight = 801, inputs = intArrayOf(1))), maxUid = 2)!!
+
+        assertArrayEquals(intArrayOf(1, 2), result.blocks[0])
+        assertArrayEquals(intArrayOf(101 + 201), result.blockVsizes)
+        // The package is priced on its summed weight, 1,202 WU rounded once to 301 vB.
+        assertEquals(21_010.0 / 301, result.rates.single { it[0] == 1.0 }[2], 1e-9)
+    }
+
+    @Test
+    fun sigopAdjustedWeight() {
+        assertEquals(400, sigopAdjustedWeight(400, 10, 20))
+        assertEquals(2_000, sigopAdjustedWeight(400, 100, 20))
+        assertEquals(400, sigopAdjustedWeight(400, 100, 0))
+        assertEquals(Int.MAX_VALUE, sigopAdjustedWeight(400, Int.MAX_VALUE, 20))
+    }
+}